@@ -0,0 +1,780 @@
+use crate::data::blob_uuid_cache::BlobUuidCache;
+use crate::data::path_diff;
+use crate::data::path_tree_storage::{PathTreeStorage, TreePathError};
+use crate::data::uuid::{MetaBlobError, MetaInfo, MetaReadError, Uuid, DEFAULT_GUID_KEY};
+use crate::data::uuid_storage::{ChangeKind, GuidCollision, UuidStorage};
+use crate::diagnostics::Diagnostics;
+use crate::glob::GlobPattern;
+use git2::{Delta, Diff, DiffDelta, ErrorCode, Repository, Tree, TreeWalkMode, TreeWalkResult};
+use std::collections::{BTreeSet, HashSet};
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+// Everything found in a diff. Nothing gets printed while analyzing, it is up to the caller to present this.
+#[derive(Default)]
+pub struct AnalysisResult {
+	pub uuid_storage: UuidStorage,
+	pub addition_tree: PathTreeStorage,
+	pub removal_tree: PathTreeStorage,
+	// GUIDs used by multiple added (or removed) files.
+	pub collisions: Vec<GuidCollision>,
+	pub skipped: Vec<SkippedDelta>,
+	// Paths which kept existing, but got a different GUID. Their GUIDs are part of the storage as addition and removal too.
+	pub replaced: Vec<ReplacedAsset>,
+	// Everything above which needs the attention of the user, as messages. Callers may add their own.
+	pub diagnostics: Diagnostics,
+}
+
+impl AnalysisResult {
+	// Drops the GUIDs without any path inside one of the folders, nothing is dropped without folders.
+	// Git pairs up similar meta files as renames, thus a delta which touches a folder can carry an unrelated change from elsewhere.
+	pub fn retain_subdirs(&mut self, subdirs: &[PathBuf]) {
+		if subdirs.is_empty() {
+			return;
+		}
+		let is_inside = |path: &PathBuf| subdirs.iter().any(|subdir| path.starts_with(subdir));
+		self.uuid_storage.retain(|_, entry| {
+			entry.added.iter().chain(&entry.removed).chain(&entry.modified).chain(&entry.copied_from).any(is_inside)
+		});
+		let lookup = &self.uuid_storage.lookup;
+		self.addition_tree.retain(|uuid| lookup.contains_key(&uuid));
+		self.removal_tree.retain(|uuid| lookup.contains_key(&uuid));
+		self.collisions = self.uuid_storage.collisions();
+		self.diagnostics = Diagnostics::default();
+		collect_diagnostics(self);
+	}
+	
+	// Drops everything but renames (and moves) from the storage and both trees. Diagnostics are kept.
+	pub fn retain_renames(&mut self) {
+		self.uuid_storage.retain(|_, entry| entry.classify() == ChangeKind::Renamed);
+		let lookup = &self.uuid_storage.lookup;
+		self.addition_tree.retain(|uuid| lookup.contains_key(&uuid));
+		self.removal_tree.retain(|uuid| lookup.contains_key(&uuid));
+		self.replaced.clear();
+	}
+	
+	// Swaps additions and removals, as if the two sides of the diff had been passed the other way around.
+	// The diagnostics of the analysis are collected again, thus call this before adding own diagnostics.
+	pub fn invert(&mut self) {
+		self.uuid_storage.invert();
+		std::mem::swap(&mut self.addition_tree, &mut self.removal_tree);
+		for replaced in &mut self.replaced {
+			std::mem::swap(&mut replaced.old_guid, &mut replaced.new_guid);
+		}
+		for collision in &mut self.collisions {
+			collision.is_adding = !collision.is_adding;
+		}
+		for skipped in &mut self.skipped {
+			if let SkippedDelta::NilGuid { is_adding, .. } = skipped {
+				*is_adding = !*is_adding;
+			}
+		}
+		self.diagnostics = Diagnostics::default();
+		collect_diagnostics(self);
+	}
+	
+	// Adds the meta file changes of a submodule, the deltas are relative to the submodule at 'submodule_path'.
+	// The GUIDs share the storage with the outer repository, thus collisions across repositories are found too.
+	// Like invert(), the diagnostics are collected again.
+	pub fn add_submodule(&mut self, repository: &Repository, diffs: &[DiffDelta], submodule_path: &Path, excludes: &[GlobPattern], guid_key: &str) -> Result<(), AnalysisError> {
+		add_deltas(self, repository, diffs, submodule_path, excludes, guid_key, &|_, _| {})?;
+		self.collisions = self.uuid_storage.collisions();
+		self.diagnostics = Diagnostics::default();
+		collect_diagnostics(self);
+		Ok(())
+	}
+	
+	// For submodules which changed, but cannot be inspected. For example as they are not initialized. Like invert(), the diagnostics are collected again.
+	pub fn skip_submodule(&mut self, submodule_path: PathBuf, reason: String) {
+		self.skipped.push(SkippedDelta::Submodule { path: submodule_path, reason });
+		self.diagnostics = Diagnostics::default();
+		collect_diagnostics(self);
+	}
+	
+	// GUIDs used by multiple files, and GUIDs involved in paths which got two GUIDs. Sorted, each only once.
+	pub fn conflicting_guids(&self) -> Vec<Uuid> {
+		let path_conflicts = self.addition_tree.conflicts().iter().chain(self.removal_tree.conflicts());
+		let mut guids: Vec<Uuid> = self.collisions.iter().map(|collision| collision.uuid)
+			.chain(path_conflicts.flat_map(|conflict| [conflict.kept, conflict.ignored]))
+			.collect();
+		guids.sort();
+		guids.dedup();
+		guids
+	}
+	
+	// Shortens the displayed paths below the prefix, for example 'Assets/Characters' turns 'Assets/Characters/Hero.prefab' into 'Hero.prefab'.
+	// Paths outside the prefix stay unchanged. The messages of the diagnostics keep the full paths.
+	pub fn strip_prefix(&mut self, prefix: &Path) {
+		let strip = |path: &mut PathBuf| {
+			if let Some(rest) = path_diff::strip_prefix(path, prefix) {
+				*path = rest.to_path_buf();
+			}
+		};
+		self.uuid_storage.strip_prefix(prefix);
+		self.addition_tree.strip_prefix(prefix);
+		self.removal_tree.strip_prefix(prefix);
+		for collision in &mut self.collisions {
+			collision.paths.iter_mut().for_each(strip);
+		}
+		for replaced in &mut self.replaced {
+			strip(&mut replaced.path);
+		}
+	}
+}
+
+// The asset at a path got replaced by a different one (or its meta file got regenerated), rather than renamed.
+// All references to the old GUID break, even though the path did not change.
+pub struct ReplacedAsset {
+	// Without the '.meta' extension, like the paths of the storage.
+	pub path: PathBuf,
+	pub old_guid: Uuid,
+	pub new_guid: Uuid,
+}
+
+// A meta file change, which is not part of the storages.
+pub enum SkippedDelta {
+	// Unity writes an all-zero GUID for placeholder or broken assets, it does not identify anything.
+	NilGuid { path: PathBuf, is_adding: bool },
+	// For example a file got replaced by a symlink. There is no meta file content to compare on both sides.
+	Typechange { path: PathBuf },
+	// The path cannot be shown as part of a tree, for example as nothing is left of it after normalization.
+	InvalidPath { path: PathBuf, error: TreePathError },
+	// A changed submodule, whose meta files could not be read. Only with submodules being inspected.
+	Submodule { path: PathBuf, reason: String },
+}
+
+// Why the analysis of a diff stopped. Meta files which cannot be read leave no way to tell what happened to their GUID.
+#[derive(Debug)]
+pub enum AnalysisError {
+	Blob(MetaBlobError),
+	Disk(MetaReadError),
+	// For example a conflicted or unreadable delta, there is no meta file content to look at.
+	UnsupportedDelta { path: PathBuf, status: Delta },
+	// Neither the old nor the new path of the delta is set. Should never happen, but then nothing can be looked up.
+	MissingPaths { status: Delta },
+}
+
+impl Display for AnalysisError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		match self {
+			AnalysisError::Blob(error) => write!(f, "{error}"),
+			AnalysisError::Disk(error) => write!(f, "{error}"),
+			AnalysisError::UnsupportedDelta { path, status } => write!(f, "Cannot yet handle diff delta type of {status:?} for path '{}'", path.display()),
+			AnalysisError::MissingPaths { status } => write!(f, "Neither the old nor the new path of the diff delta (of type {status:?}) had been set"),
+		}
+	}
+}
+
+impl Error for AnalysisError {
+	fn source(&self) -> Option<&(dyn Error + 'static)> {
+		match self {
+			AnalysisError::Blob(error) => Some(error),
+			AnalysisError::Disk(error) => Some(error),
+			AnalysisError::UnsupportedDelta { .. } | AnalysisError::MissingPaths { .. } => None,
+		}
+	}
+}
+
+impl From<MetaBlobError> for AnalysisError {
+	fn from(error: MetaBlobError) -> Self {
+		AnalysisError::Blob(error)
+	}
+}
+
+impl From<MetaReadError> for AnalysisError {
+	fn from(error: MetaReadError) -> Self {
+		AnalysisError::Disk(error)
+	}
+}
+
+// Which side of the diff a meta file path is on.
+#[derive(Copy, Clone, Eq, PartialEq)]
+enum Direction {
+	Added,
+	Removed,
+}
+
+// Where the new side of a diff is.
+pub enum DiffTarget<'a> {
+	WorkDirectory,
+	Index,
+	Tree(Tree<'a>),
+}
+
+// Collects the GUID changes of all meta files in the diff.
+pub fn analyze(repository: &Repository, diff: &Diff) -> Result<AnalysisResult, AnalysisError> {
+	analyze_deltas(repository, &gather_filtered_deltas_from_diff(diff, &[], &[]))
+}
+
+// Same as analyze(), but only for the given deltas. See gather_filtered_deltas_from_diff().
+pub fn analyze_deltas(repository: &Repository, diffs: &[DiffDelta]) -> Result<AnalysisResult, AnalysisError> {
+	analyze_deltas_with_progress(repository, diffs, &[], DEFAULT_GUID_KEY, &|_, _| {})
+}
+
+// Same as analyze_deltas(), reading the meta files from Git reports (read, total) to the callback. See BlobUuidCache::prefetch_with_progress().
+// The deltas still contain renames with one excluded side (see gather_filtered_deltas_from_diff()), 'excludes' leaves out that side.
+// The GUIDs are read from the lines starting with 'guid_key', see DEFAULT_GUID_KEY.
+// On error, nothing is returned - a partial result would silently miss GUID changes.
+pub fn analyze_deltas_with_progress(
+	repository: &Repository, diffs: &[DiffDelta], excludes: &[GlobPattern], guid_key: &str, progress: &(dyn Fn(usize, usize) + Sync),
+) -> Result<AnalysisResult, AnalysisError> {
+	let mut result = AnalysisResult::default();
+	add_deltas(&mut result, repository, diffs, Path::new(""), excludes, guid_key, progress)?;
+	result.collisions = result.uuid_storage.collisions();
+	collect_diagnostics(&mut result);
+	Ok(result)
+}
+
+// Records the deltas into the storages and trees. All paths get 'path_prefix' in front, which is where the repository is inside the outer repository (if any).
+// Excluded paths are not recorded, thus a rename with one excluded side becomes an addition or removal.
+// On error, the deltas before the failing one are already recorded.
+fn add_deltas(
+	result: &mut AnalysisResult, repository: &Repository, diffs: &[DiffDelta], path_prefix: &Path, excludes: &[GlobPattern], guid_key: &str, progress: &(dyn Fn(usize, usize) + Sync),
+) -> Result<(), AnalysisError> {
+	// Meta files in Git are fully parsed. The folder flag is also set, when the folder exists in the work directory (only the GUID is read from disk).
+	// The paths given to these functions are relative to the root of 'repository' - not to the current directory, and without submodule prefix.
+	fn old_file_meta(repository: &Repository, blob_cache: &mut BlobUuidCache, delta: &DiffDelta, path: &Path) -> Result<MetaInfo, AnalysisError> {
+		let info = blob_cache.meta_info_from_blob(repository, delta.old_file().id())?;
+		Ok(MetaInfo { is_folder: info.is_folder || is_folder_asset(repository, path), ..info })
+	}
+	
+	// When diffing against the work directory, the new file only exists on disk. libgit2 either does not hash it at all,
+	// or (to compare files of equal size) hashes it without storing the blob - thus the blob cannot be found then.
+	fn new_file_meta(repository: &Repository, blob_cache: &mut BlobUuidCache, guid_key: &str, delta: &DiffDelta, path: &Path) -> Result<MetaInfo, AnalysisError> {
+		let hash = delta.new_file().id();
+		let from_disk = || match repository.workdir() {
+			Some(workdir) => Uuid::from_disk(&workdir.join(path), guid_key).map(|guid| MetaInfo { guid, is_folder: false }),
+			// A bare repository has no files on disk to fall back to.
+			None => Err(MetaReadError::Io { path: path.to_path_buf(), error: io::Error::from(io::ErrorKind::NotFound) }),
+		};
+		let info = if hash.is_zero() {
+			from_disk()?
+		} else {
+			match blob_cache.meta_info_from_blob(repository, hash) {
+				Err(MetaBlobError::Lookup { error, .. }) if error.code() == ErrorCode::NotFound => from_disk()?,
+				result => result?,
+			}
+		};
+		Ok(MetaInfo { is_folder: info.is_folder || is_folder_asset(repository, path), ..info })
+	}
+	
+	// A folder asset has a meta file next to it, just like a file asset.
+	fn is_folder_asset(repository: &Repository, meta_path: &Path) -> bool {
+		repository.workdir().is_some_and(|workdir| workdir.join(meta_path).with_extension("").is_dir())
+	}
+	
+	let mut blob_cache = BlobUuidCache::new(guid_key);
+	// Parsing blobs is independent per delta, thus done in parallel first. Typechanges are skipped, they have no meta content.
+	let blob_hashes = diffs.iter()
+		.filter(|delta| delta.status() != Delta::Typechange)
+		.flat_map(|delta| [delta.old_file().id(), delta.new_file().id()]);
+	blob_cache.prefetch_with_progress(repository, blob_hashes, progress)?;
+	for delta in diffs.iter() {
+		// When working with libgit2, old/new paths are mostly the same. They only differ for renames and copies, or when a file moved and changed.
+		// A missing path is filled in with the other one - for example a deletion in the work directory may come without new path.
+		// Only if both are missing something is seriously wrong - stop then.
+		let (old_relative, new_relative) = match (delta.old_file().path(), delta.new_file().path()) {
+			(Some(old), Some(new)) => (old, new),
+			(Some(path), None) | (None, Some(path)) => (path, path),
+			(None, None) => return Err(AnalysisError::MissingPaths { status: delta.status() }),
+		};
+		// Submodule paths are shown relative to the outer repository, but read relative to the submodule.
+		let (old_path, new_path) = (path_prefix.join(old_relative), path_prefix.join(new_relative));
+		// Not sure why this would ever happen. But let's not take the chance - an empty path cannot be shown or read from disk.
+		if let Some(empty_path) = [&old_path, &new_path].into_iter().find(|path| path.iter().next().is_none()) {
+			result.skipped.push(SkippedDelta::InvalidPath { path: empty_path.clone(), error: TreePathError::Empty { path: empty_path.clone() } });
+			continue;
+		}
+		
+		match delta.status() {
+			Delta::Untracked => {
+				// The work-directory file (at path) was not in Git and is freshly added.
+				let info = new_file_meta(repository, &mut blob_cache, guid_key, delta, new_relative)?;
+				record(result, excludes, Direction::Added, &new_path, info);
+			}
+			Delta::Added => {
+				// The file (at path) is added to Git.
+				let info = new_file_meta(repository, &mut blob_cache, guid_key, delta, new_relative)?;
+				record(result, excludes, Direction::Added, &new_path, info);
+			}
+			Delta::Deleted => {
+				// The file (at path) was removed from Git
+				let info = old_file_meta(repository, &mut blob_cache, delta, old_relative)?;
+				record(result, excludes, Direction::Removed, &old_path, info);
+			}
+			Delta::Modified if old_path == new_path => {
+				// The file path has not changed, but the content did.
+				let info_from = old_file_meta(repository, &mut blob_cache, delta, old_relative)?;
+				let info_to = new_file_meta(repository, &mut blob_cache, guid_key, delta, new_relative)?;
+				// For the purpose of this program, only care about this file, when the UUID changed.
+				// As in all other cases, everything is expected and okay.
+				if info_from.guid != info_to.guid {
+					if !info_from.guid.is_nil() && !info_to.guid.is_nil() {
+						result.replaced.push(ReplacedAsset { path: new_path.with_extension(""), old_guid: info_from.guid, new_guid: info_to.guid });
+					}
+					record(result, excludes, Direction::Added, &new_path, info_to);
+					record(result, excludes, Direction::Removed, &old_path, info_from);
+				} else if !is_excluded(excludes, &new_path) {
+					// Still remember it, some users want to know about meta files with changed importer settings.
+					result.uuid_storage.modified(info_to.guid, new_path);
+				}
+			}
+			Delta::Renamed | Delta::Modified => {
+				// Git detected that the file moved from the old path to the new path (only when rename detection is enabled).
+				// A modification with differing paths is the same - the file moved and got changed.
+				// If the GUID stayed the same, UUID storage turns this into a single rename entry.
+				// Otherwise, it is a removal and an unrelated addition - meta files are small and similar, Git pairs them up easily.
+				let info_from = old_file_meta(repository, &mut blob_cache, delta, old_relative)?;
+				let info_to = new_file_meta(repository, &mut blob_cache, guid_key, delta, new_relative)?;
+				record(result, excludes, Direction::Removed, &old_path, info_from);
+				record(result, excludes, Direction::Added, &new_path, info_to);
+			}
+			Delta::Copied => {
+				// The file got copied to the new path (only when copy detection is enabled). The source file still exists.
+				// If the copy kept the GUID, both files use it now - the source is remembered to report that.
+				let info_from = old_file_meta(repository, &mut blob_cache, delta, old_relative)?;
+				let info_to = new_file_meta(repository, &mut blob_cache, guid_key, delta, new_relative)?;
+				let is_duplicate = info_from.guid == info_to.guid && !info_to.guid.is_nil();
+				record(result, excludes, Direction::Added, &new_path, info_to);
+				if is_duplicate && !is_excluded(excludes, &old_path) {
+					result.uuid_storage.copied_from(info_from.guid, old_path);
+				}
+			}
+			Delta::Typechange => {
+				result.skipped.push(SkippedDelta::Typechange { path: new_path });
+			}
+			status => {
+				return Err(AnalysisError::UnsupportedDelta { path: new_path, status });
+			}
+		}
+	}
+	Ok(())
+}
+
+// Records one side of a delta. Both directions are handled the same way, they only differ in which storage and tree the path goes to.
+fn record(result: &mut AnalysisResult, excludes: &[GlobPattern], direction: Direction, path: &Path, info: MetaInfo) {
+	if is_excluded(excludes, path) {
+		return;
+	}
+	// Rename detection may pair one removed meta file with multiple added ones (they are small and similar). It is still a single removal.
+	if result.uuid_storage.contains(info.guid, direction == Direction::Added, path) {
+		return;
+	}
+	if info.guid.is_nil() {
+		result.skipped.push(SkippedDelta::NilGuid { path: path.to_path_buf(), is_adding: direction == Direction::Added });
+		return;
+	}
+	// GUID collisions are kept and reported after the trees. The tree goes first, as it may reject the path.
+	let added_to_tree = match direction {
+		Direction::Added => result.addition_tree.add_to_tree(path, info.guid),
+		Direction::Removed => result.removal_tree.add_to_tree(path, info.guid),
+	};
+	if let Err(error) = added_to_tree {
+		result.skipped.push(SkippedDelta::InvalidPath { path: path.to_path_buf(), error });
+		return;
+	}
+	match direction {
+		Direction::Added => result.uuid_storage.added(info.guid, path.to_path_buf()),
+		Direction::Removed => result.uuid_storage.removed(info.guid, path.to_path_buf()),
+	}
+	if info.is_folder {
+		result.uuid_storage.mark_folder(info.guid);
+	}
+}
+
+fn is_excluded(excludes: &[GlobPattern], path: &Path) -> bool {
+	excludes.iter().any(|pattern| pattern.matches(path))
+}
+
+fn collect_diagnostics(result: &mut AnalysisResult) {
+	let direction = |is_adding: bool| if is_adding { "added" } else { "removed" };
+	let diagnostics = &mut result.diagnostics;
+	for skipped in &result.skipped {
+		match skipped {
+			SkippedDelta::NilGuid { path, is_adding } => diagnostics.info(
+				format!("Skipping {} path '{}', as its Unity GUID is all zeros (placeholder or broken asset).", direction(*is_adding), path.display()),
+				vec![path.clone()],
+			),
+			SkippedDelta::Typechange { path } => diagnostics.warning(
+				format!("Skipping '{}', as its type changed (for example file <=> symlink).", path.display()),
+				vec![path.clone()],
+			),
+			SkippedDelta::InvalidPath { path, error } => diagnostics.warning(
+				format!("Skipping '{}': {error}.", path.display()),
+				vec![path.clone()],
+			),
+			SkippedDelta::Submodule { path, reason } => diagnostics.warning(
+				format!("Skipping submodule '{}': {reason}.", path.display()),
+				vec![path.clone()],
+			),
+		}
+	}
+	for collision in &result.collisions {
+		diagnostics.warning(
+			format!("GUID {} is {} by {} files. Normally a GUID is supposed to be UNIQUE (to a single path).", collision.uuid, direction(collision.is_adding), collision.paths.len()),
+			collision.paths.clone(),
+		);
+	}
+	for (conflict, is_adding) in result.addition_tree.conflicts().iter().map(|conflict| (conflict, true))
+		.chain(result.removal_tree.conflicts().iter().map(|conflict| (conflict, false))) {
+		diagnostics.warning(
+			format!(
+				"Path '{}' got {} with two GUIDs ({} & {}), the tree only shows the first. Normally a GUID is supposed to be UNIQUE (to a single path).",
+				conflict.path.display(), direction(is_adding), conflict.kept, conflict.ignored,
+			),
+			vec![conflict.path.clone()],
+		);
+	}
+}
+
+// Added meta files, whose asset (file or folder) does not exist on the new side of the diff. Unity deletes such meta files on import.
+// Returns the asset paths.
+pub fn find_orphans(repository: &Repository, target: &DiffTarget, uuid_storage: &UuidStorage) -> Result<Vec<PathBuf>, git2::Error> {
+	// The index only knows files. A folder exists, if any file is inside - thus the parent folders of all files are collected too.
+	// Collected once, as big projects have a lot of index entries.
+	let mut index_paths = HashSet::new();
+	if let DiffTarget::Index = target {
+		for entry in repository.index()?.iter() {
+			let path = PathBuf::from(&*String::from_utf8_lossy(&entry.path));
+			for ancestor in path.ancestors() {
+				// Its parents got collected together with it.
+				if !index_paths.insert(ancestor.to_path_buf()) {
+					break;
+				}
+			}
+		}
+	}
+	let asset_exists = |asset_path: &Path| match target {
+		DiffTarget::WorkDirectory => repository.workdir().is_some_and(|workdir| workdir.join(asset_path).exists()),
+		DiffTarget::Tree(tree) => tree.get_path(asset_path).is_ok(),
+		DiffTarget::Index => index_paths.contains(asset_path),
+	};
+	// The storage already keeps the paths without the '.meta' extension.
+	Ok(uuid_storage.iter()
+		.flat_map(|(_, entry)| entry.added.iter())
+		.filter(|asset_path| !asset_exists(asset_path))
+		.cloned()
+		.collect())
+}
+
+// Assets which appeared in the diff, without a meta file appearing next to them. Unity would generate a new GUID for them.
+// Unlike the other functions, this looks at all deltas - not only the meta files. The folder filters are the same as for the meta files.
+pub fn find_assets_without_meta(diff: &Diff, subdirs: &[PathBuf], excludes: &[GlobPattern]) -> Vec<PathBuf> {
+	// Unity only imports files inside the 'Assets' and 'Packages' folders of the project.
+	let is_inside_project = |path: &Path, project: &Path| path.strip_prefix(project).ok()
+		.and_then(|rest| rest.iter().next())
+		.is_some_and(|root| root == "Assets" || root == "Packages");
+	// The project is in the repository root, or in a requested folder - which might also be inside the project (like 'Game/Assets/Characters').
+	let root = [PathBuf::new()];
+	let projects = if subdirs.is_empty() { &root[..] } else { subdirs };
+	// And it ignores hidden files and files ending with '~'.
+	let is_asset = |path: &Path| {
+		let file_name = path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+		!file_name.starts_with('.') && !file_name.ends_with('~') && !file_name.ends_with(".meta")
+			&& projects.iter().any(|subdir| path.starts_with(subdir) && subdir.ancestors().any(|project| is_inside_project(path, project)))
+			&& !is_excluded(excludes, path)
+	};
+	let appeared = diff.deltas()
+		.filter(|delta| matches!(delta.status(), Delta::Added | Delta::Untracked | Delta::Renamed | Delta::Copied))
+		.filter_map(|delta| delta.new_file().path());
+	let mut new_meta_files = HashSet::new();
+	let mut new_assets = Vec::new();
+	for path in appeared {
+		if path.to_str().unwrap().ends_with(".meta") {
+			new_meta_files.insert(path);
+		} else if is_asset(path) {
+			new_assets.push(path);
+		}
+	}
+	new_assets.into_iter()
+		.filter(|asset_path| {
+			let mut meta_path = asset_path.as_os_str().to_owned();
+			meta_path.push(".meta");
+			!new_meta_files.contains(Path::new(&meta_path))
+		})
+		.map(Path::to_path_buf)
+		.collect()
+}
+
+// Meta files in the work directory, which are next to the added (or renamed to) paths but not changed themselves. Gives context to where something went.
+// Returns the meta file paths, relative to the repository root. A bare repository has no work directory, thus no siblings.
+pub fn find_context_siblings(repository: &Repository, uuid_storage: &UuidStorage) -> Vec<PathBuf> {
+	let Some(workdir) = repository.workdir() else {
+		return Vec::new();
+	};
+	let changed: HashSet<&Path> = uuid_storage.iter().flat_map(|(_, entry)| entry.added.iter().map(PathBuf::as_path)).collect();
+	let folders: BTreeSet<&Path> = changed.iter().filter_map(|asset_path| asset_path.parent()).collect();
+	let mut siblings = Vec::new();
+	for folder in folders {
+		let Ok(entries) = fs::read_dir(workdir.join(folder)) else {
+			continue;
+		};
+		for entry in entries.flatten() {
+			let meta_path = folder.join(entry.file_name());
+			// The storage keeps the paths without the '.meta' extension.
+			if meta_path.to_str().is_some_and(|path| path.ends_with(".meta")) && !changed.contains(meta_path.with_extension("").as_path()) {
+				siblings.push(meta_path);
+			}
+		}
+	}
+	siblings.sort();
+	siblings
+}
+
+// All meta files on the new side of the diff (inside the requested folders), changed or not. Gives context to the amount of changes.
+// The work directory is walked on disk, skipping what Git ignores (like Unity's 'Library' folder). This has a cost for big projects.
+pub fn count_meta_files(repository: &Repository, target: &DiffTarget, subdirs: &[PathBuf]) -> Result<usize, git2::Error> {
+	let is_counted = |path: &Path| path.to_str().is_some_and(|path| path.ends_with(".meta"))
+		&& (subdirs.is_empty() || subdirs.iter().any(|subdir| path.starts_with(subdir)));
+	let mut count = 0;
+	match target {
+		DiffTarget::WorkDirectory => {
+			let Some(workdir) = repository.workdir() else {
+				return Ok(0);
+			};
+			// Paths relative to the repository root, as Git wants them for the ignore check.
+			let mut stack = vec![PathBuf::new()];
+			while let Some(folder) = stack.pop() {
+				let Ok(entries) = fs::read_dir(workdir.join(&folder)) else {
+					continue;
+				};
+				for entry in entries.flatten() {
+					let path = folder.join(entry.file_name());
+					if path == Path::new(".git") || repository.is_path_ignored(&path)? {
+						continue;
+					}
+					if entry.file_type().is_ok_and(|file_type| file_type.is_dir()) {
+						stack.push(path);
+					} else if is_counted(&path) {
+						count += 1;
+					}
+				}
+			}
+		}
+		DiffTarget::Index => {
+			count = repository.index()?.iter()
+				.filter(|entry| is_counted(Path::new(&*String::from_utf8_lossy(&entry.path))))
+				.count();
+		}
+		DiffTarget::Tree(tree) => {
+			tree.walk(TreeWalkMode::PreOrder, |folder, entry| {
+				if entry.name().is_some_and(|name| is_counted(&Path::new(folder).join(name))) {
+					count += 1;
+				}
+				TreeWalkResult::Ok
+			})?;
+		}
+	}
+	Ok(count)
+}
+
+pub fn gather_filtered_deltas_from_diff<'a>(diff: &'a Diff<'a>, subdirs: &[PathBuf], excludes: &[GlobPattern]) -> Vec<DiffDelta<'a>> {
+	gather_filtered_submodule_deltas(diff, Path::new(""), subdirs, excludes)
+}
+
+// Same as gather_filtered_deltas_from_diff(), for the diff of the submodule at 'submodule_path'. The filters stay relative to the outer repository.
+pub fn gather_filtered_submodule_deltas<'a>(diff: &'a Diff<'a>, submodule_path: &Path, subdirs: &[PathBuf], excludes: &[GlobPattern]) -> Vec<DiffDelta<'a>> {
+	// A path is relevant, if it is a meta file - and inside one of the requested folders (if any), but not matched by any exclude pattern.
+	let is_relevant = |path: Option<&Path>| path.is_some_and(|path| {
+		let path = &submodule_path.join(path);
+		path.to_str().unwrap().ends_with(".meta")
+			&& (subdirs.is_empty() || subdirs.iter().any(|subdir| path.starts_with(subdir)))
+			&& !excludes.iter().any(|pattern| pattern.matches(path))
+	});
+	diff.deltas().filter(|delta| {
+		// New/Old paths are mostly the same, but differ for renames.
+		// Anyway, check if either path is relevant. This also keeps renames into or out of the requested folders.
+		// Renames with one excluded side are kept as well, the analysis only records their other side (see analyze_deltas_with_progress()).
+		is_relevant(delta.old_file().path()) || is_relevant(delta.new_file().path())
+	}).collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	use crate::data::path_tree_storage::TreePrintOptions;
+	use crate::data::uuid::test_helpers::{temp_repository, uuid};
+	use crate::output::color::AnsiStripper;
+	use git2::{DiffFindOptions, DiffOptions, Oid};
+	use std::path::PathBuf;
+	
+	// Meta file content with the GUID of the digit (see uuid()).
+	fn meta(digit: char, user_data: &str) -> String {
+		format!("fileFormatVersion: 2\nguid: {}\nDefaultImporter:\n  externalObjects: {{}}\n  userData: {user_data}\n  assetBundleName: \n", uuid(digit))
+	}
+	
+	// A tree with the files in its root folder. A mode of 0o120000 makes the file a symlink to its content.
+	fn tree(repository: &Repository, files: &[(&str, i32, &str)]) -> Oid {
+		let mut builder = repository.treebuilder(None).unwrap();
+		for &(name, mode, content) in files {
+			builder.insert(name, repository.blob(content.as_bytes()).unwrap(), mode).unwrap();
+		}
+		builder.write().unwrap()
+	}
+	
+	// Diffs the trees with rename and copy detection, which the binary does not enable (yet).
+	// Returns the storage and the delta kinds.
+	fn sorted(repository: &Repository, old: Oid, new: Oid, options: &mut DiffOptions) -> (UuidStorage, Vec<Delta>) {
+		let old = repository.find_tree(old).unwrap();
+		let new = repository.find_tree(new).unwrap();
+		let mut diff = repository.diff_tree_to_tree(Some(&old), Some(&new), Some(options)).unwrap();
+		diff.find_similar(Some(DiffFindOptions::new().renames(true).copies(true).rename_threshold(10).copy_threshold(10))).unwrap();
+		let deltas = gather_filtered_deltas_from_diff(&diff, &[], &[]);
+		let kinds = deltas.iter().map(|delta| delta.status()).collect();
+		(analyze_deltas(repository, &deltas).unwrap().uuid_storage, kinds)
+	}
+	
+	#[test]
+	fn path_conflicts_are_reported() {
+		let (first, second) = (uuid('1'), uuid('2'));
+		let mut result = AnalysisResult::default();
+		result.removal_tree.add_to_tree(Path::new("Assets/x.png.meta"), first).unwrap();
+		result.removal_tree.add_to_tree(Path::new("Assets/x.png.meta"), second).unwrap();
+		collect_diagnostics(&mut result);
+		
+		assert_eq!(result.conflicting_guids(), vec![first, second]);
+		let messages: Vec<_> = result.diagnostics.iter().map(|diagnostic| diagnostic.message.as_str()).collect();
+		assert_eq!(messages, vec![format!(
+			"Path 'Assets/x.png' got removed with two GUIDs ({first} & {second}), the tree only shows the first. Normally a GUID is supposed to be UNIQUE (to a single path).",
+		)]);
+	}
+	
+	#[test]
+	fn renamed_delta_is_a_single_rename() {
+		let (path, repository) = temp_repository("renamed-delta");
+		let old = tree(&repository, &[("x.png.meta", 0o100644, &meta('1', ""))]);
+		let new = tree(&repository, &[("y.png.meta", 0o100644, &meta('1', ""))]);
+		let (uuid_storage, kinds) = sorted(&repository, old, new, &mut DiffOptions::new());
+		assert!(kinds.contains(&Delta::Renamed));
+		assert_eq!(uuid_storage.lookup.len(), 1);
+		let entry = &uuid_storage.lookup[&uuid('1')];
+		assert_eq!(entry.removed, vec![PathBuf::from("x.png")]);
+		assert_eq!(entry.added, vec![PathBuf::from("y.png")]);
+		assert_eq!(uuid_storage.renames().count(), 1);
+		std::fs::remove_dir_all(path).unwrap();
+	}
+	
+	#[test]
+	fn renamed_delta_with_new_guid_is_a_removal_and_an_addition() {
+		let (path, repository) = temp_repository("renamed-delta-new-guid");
+		let old = tree(&repository, &[("x.png.meta", 0o100644, &meta('1', ""))]);
+		let new = tree(&repository, &[("y.png.meta", 0o100644, &meta('2', ""))]);
+		let (uuid_storage, kinds) = sorted(&repository, old, new, &mut DiffOptions::new());
+		assert!(kinds.contains(&Delta::Renamed));
+		assert_eq!(uuid_storage.renames().count(), 0);
+		assert_eq!(uuid_storage.lookup[&uuid('1')].removed, vec![PathBuf::from("x.png")]);
+		assert_eq!(uuid_storage.lookup[&uuid('2')].added, vec![PathBuf::from("y.png")]);
+		std::fs::remove_dir_all(path).unwrap();
+	}
+	
+	#[test]
+	fn moved_and_changed_meta_is_a_rename() {
+		let (path, repository) = temp_repository("moved-and-changed");
+		let old = tree(&repository, &[("x.png.meta", 0o100644, &meta('1', ""))]);
+		// Unity rewrites the importer settings on import.
+		let new = tree(&repository, &[("y.png.meta", 0o100644, &meta('1', "changed"))]);
+		let (uuid_storage, kinds) = sorted(&repository, old, new, &mut DiffOptions::new());
+		assert_eq!(kinds.len(), 1);
+		let renames: Vec<_> = uuid_storage.renames().collect();
+		assert_eq!(renames.len(), 1);
+		assert_eq!(renames[0].1.removed, vec![PathBuf::from("x.png")]);
+		assert_eq!(renames[0].1.added, vec![PathBuf::from("y.png")]);
+		std::fs::remove_dir_all(path).unwrap();
+	}
+	
+	#[test]
+	fn copy_keeping_the_guid_is_reported_as_collision() {
+		let (path, repository) = temp_repository("copied-delta");
+		let old = tree(&repository, &[("x.png.meta", 0o100644, &meta('1', ""))]);
+		// Libgit2 only uses modified files as copy source.
+		let new = tree(&repository, &[("x.png.meta", 0o100644, &meta('1', "changed")), ("y.png.meta", 0o100644, &meta('1', ""))]);
+		let (uuid_storage, kinds) = sorted(&repository, old, new, &mut DiffOptions::new());
+		assert!(kinds.contains(&Delta::Copied));
+		let entry = &uuid_storage.lookup[&uuid('1')];
+		assert_eq!(entry.added, vec![PathBuf::from("y.png")]);
+		assert_eq!(entry.copied_from, vec![PathBuf::from("x.png")]);
+		assert!(entry.removed.is_empty());
+		let collisions = uuid_storage.collisions();
+		assert_eq!(collisions.len(), 1);
+		assert_eq!(collisions[0].paths, vec![PathBuf::from("y.png"), PathBuf::from("x.png")]);
+		assert_eq!(uuid_storage.summary().conflicts, 1);
+		std::fs::remove_dir_all(path).unwrap();
+	}
+	
+	#[test]
+	fn copy_with_new_guid_is_a_plain_addition() {
+		let (path, repository) = temp_repository("copied-delta-new-guid");
+		let old = tree(&repository, &[("x.png.meta", 0o100644, &meta('1', ""))]);
+		let new = tree(&repository, &[("x.png.meta", 0o100644, &meta('1', "changed")), ("y.png.meta", 0o100644, &meta('2', ""))]);
+		let (uuid_storage, kinds) = sorted(&repository, old, new, &mut DiffOptions::new());
+		assert!(kinds.contains(&Delta::Copied));
+		let entry = &uuid_storage.lookup[&uuid('2')];
+		assert_eq!(entry.added, vec![PathBuf::from("y.png")]);
+		assert!(entry.copied_from.is_empty());
+		assert!(uuid_storage.collisions().is_empty());
+		std::fs::remove_dir_all(path).unwrap();
+	}
+	
+	#[test]
+	fn typechange_is_skipped() {
+		let (path, repository) = temp_repository("typechange-delta");
+		let old = tree(&repository, &[("x.png.meta", 0o100644, &meta('1', ""))]);
+		let new = tree(&repository, &[("x.png.meta", 0o120000, "y.png.meta")]);
+		// Without this, libgit2 reports a deletion and an addition instead.
+		let (uuid_storage, kinds) = sorted(&repository, old, new, DiffOptions::new().include_typechange(true));
+		assert_eq!(kinds, vec![Delta::Typechange]);
+		assert!(uuid_storage.lookup.is_empty());
+		std::fs::remove_dir_all(path).unwrap();
+	}
+	
+	#[test]
+	fn missing_paths_are_an_error() {
+		let error = AnalysisError::MissingPaths { status: Delta::Modified };
+		assert_eq!(error.to_string(), "Neither the old nor the new path of the diff delta (of type Modified) had been set");
+		assert!(error.source().is_none());
+	}
+	
+	// Records the same meta files in one direction, and returns the storage paths, the tree and the skipped paths - with the direction words replaced.
+	fn recorded(direction: Direction) -> (Vec<PathBuf>, String, Vec<(PathBuf, bool)>) {
+		let mut result = AnalysisResult::default();
+		for (path, info) in [
+			("Assets/x.png.meta", MetaInfo { guid: uuid('1'), is_folder: false }),
+			("Assets/Folder.meta", MetaInfo { guid: uuid('2'), is_folder: true }),
+			("Assets/Placeholder.png.meta", MetaInfo { guid: uuid('0'), is_folder: false }),
+		] {
+			record(&mut result, &[], direction, Path::new(path), info);
+		}
+		
+		let is_adding = direction == Direction::Added;
+		let paths = result.uuid_storage.iter()
+			.flat_map(|(_, entry)| if is_adding { &entry.added } else { &entry.removed })
+			.cloned()
+			.collect();
+		let mut tree = Vec::new();
+		result.addition_tree.write_tree(&mut AnsiStripper::new(&mut tree), &result.uuid_storage, true, &TreePrintOptions::default()).unwrap();
+		result.removal_tree.write_tree(&mut AnsiStripper::new(&mut tree), &result.uuid_storage, false, &TreePrintOptions::default()).unwrap();
+		let tree = String::from_utf8(tree).unwrap().replace("REMOVED", "ADDED");
+		// The side is flipped back, to compare both directions.
+		let skipped = result.skipped.iter().map(|skipped| match skipped {
+			SkippedDelta::NilGuid { path, is_adding: skipped_adding } => (path.clone(), *skipped_adding == is_adding),
+			SkippedDelta::Typechange { path } | SkippedDelta::InvalidPath { path, .. } | SkippedDelta::Submodule { path, .. } => (path.clone(), false),
+		}).collect();
+		assert!(result.uuid_storage.lookup[&uuid('2')].is_folder);
+		(paths, tree, skipped)
+	}
+	
+	#[test]
+	fn both_directions_are_recorded_alike() {
+		let (added_paths, added_tree, added_skipped) = recorded(Direction::Added);
+		assert_eq!(added_paths, vec![PathBuf::from("Assets/x.png"), PathBuf::from("Assets/Folder")]);
+		assert!(added_tree.contains("x.png: ADDED") && added_tree.contains("Folder: ADDED"), "{added_tree}");
+		assert_eq!(added_skipped, vec![(PathBuf::from("Assets/Placeholder.png.meta"), true)]);
+		// Only the folder counts tell the sides apart.
+		let added_tree = added_tree.replace("(+2 -0 ~0)", "(+0 -2 ~0)");
+		assert_eq!(recorded(Direction::Removed), (added_paths, added_tree, added_skipped));
+	}
+}
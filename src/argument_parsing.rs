@@ -1,32 +1,135 @@
-use git2::{Diff, DiffOptions, Repository, Tree};
-use std::{env, process};
-use std::path::Path;
+use crate::time_point::parse_time_point;
+use git2::{Diff, DiffFindOptions, DiffOptions, ErrorCode, FileMode, Oid, Repository, Sort, Tree};
+use std::{env, fs, mem, process};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use unity_engine_meta_git_differ::analysis::DiffTarget;
+use unity_engine_meta_git_differ::glob::GlobPattern;
+use unity_engine_meta_git_differ::output::color::ColorChoice;
+use unity_engine_meta_git_differ::output::format::OutputFormat;
+use unity_engine_meta_git_differ::output::log;
 /*
 	Supported argument format:
 	./exe => Diff HEAD with workdir
 	./exe <hash> => Diff commit with workdir
 	./exe <hash> <hash> => Diff 2 commits
-	Same again with <path to repository>. Any first <hash> argument that does not look like a hash is treated as <path>.
+	Any <hash> can also be a branch, tag or other revision like 'HEAD~2'.
+	Same again with <path to repository>. Any first <hash> argument that does not look like a hash, but is an existing folder, is treated as <path>.
 	./exe <path>
 	./exe <path> <hash>
 	./exe <path> <hash> <hash>
+	Instead of two <hash> arguments, a single range <hash>..<hash> can be used.
 	Same again, but with explicit path (in case of collision with <hash>:
 	./exe --path <path>
 	./exe --path <path> <hash>
 	./exe --path <path> <hash> <hash>
 	Any other input will print the help:
 	./exe anything-else => Help
+	Options (starting with '--') can be placed anywhere in between, values can be attached with '=':
+	--color <auto|always|never>
+	--ascii
+	--root-label <text>
+	--unified
+	--context
+	--subdir <path> (repeatable)
+	--exclude <glob> (repeatable)
+	--guid-key <key>
+	--staged (only without <hash>)
+	--index (same as --staged, but also with a single <hash>)
+	--format <tree|json|porcelain|csv|dot|markdown>[:<file>] (repeatable with <file>)
+	--output <file>
+	-q, --quiet
+	--exit-code
+	--fail-on-conflict
+	--list-files
+	--no-untracked
+	--recurse-submodules
+	--check-orphans
+	--summary
+	--by-extension
+	--group-moves
+	-v, --verbose
+	--since <date|duration> [--until <date|duration>] (instead of <hash>)
+	--rename-threshold <percent>
+	--batch (instead of <hash>, reads '<from> <to>' lines from stdin)
+	--git-dir <path> / --work-tree <path> (instead of <path>)
+	--only-renames
+	--only-conflicts
+	--stats
+	--invert
+	--no-legend
+	--no-sort
+	--strip-prefix <path>
+	--interactive
+	--timings
+	--watch (without <hash>, needs the 'notify' feature)
+	-h, --help / -V, --version (print and exit)
+	Defaults for these options can be put into a '.unitymetadiff' file in the repository root, see apply_config_file().
  */
 
+// Options committed to the root folder of the repository.
+const CONFIG_FILE_NAME: &str = ".unitymetadiff";
+
+fn write_help(out: &mut dyn Write) -> io::Result<()> {
+	writeln!(out, "Help: This tool will create a diff for a Unity Git repository and read the changed meta files to display which assets got added/removed/renamed.")?;
+	writeln!(out, " ./{} [[--path] <path>] [hash 1] [hash 2]", Path::new(&env::args().next().unwrap()).iter().next_back().unwrap().display())?;
+	writeln!(out, " - If <path> is provided, the current execution directory is changed. Can be any folder inside the repository.")?;
+	writeln!(out, " - If no <hash> is provided, the diff will be created between head commit and work directory.")?;
+	writeln!(out, " - If one <hash> is provided, the diff will be created between provided commit and work directory.")?;
+	writeln!(out, " - If two <hashes> are provided, the diff will be created between these two provided commits.")?;
+	writeln!(out, "   Instead of two separate <hashes>, a range '<hash 1>..<hash 2>' can be provided.")?;
+	writeln!(out, " - Instead of a <hash>, any branch, tag or revision like 'HEAD~2' can be provided.")?;
+	writeln!(out, "Options:")?;
+	writeln!(out, " --color <auto|always|never>  Colored output, 'auto' colors only terminals and respects NO_COLOR. Default: auto")?;
+	writeln!(out, " --ascii                      Draw the trees with ASCII characters only.")?;
+	writeln!(out, " --root-label <text>          Print this as root node above the trees. Empty ('--root-label=') uses the name of the repository folder.")?;
+	writeln!(out, " --unified                    Print a single tree with additions, removals and renames, instead of one tree per direction.")?;
+	writeln!(out, " --context                    Also list the unchanged meta files (dimmed) next to added and renamed assets. Read from the work directory.")?;
+	writeln!(out, " --subdir <path>              Only inspect meta files below this repository folder. Can be used multiple times.")?;
+	writeln!(out, " --exclude <glob>             Ignore meta files matching this pattern, like '.gitignore' does. For example 'Temp/' or '**/Generated/**'. Repeatable.")?;
+	writeln!(out, " --guid-key <key>             Read the GUID from the line starting with '<key>:' instead of 'guid:', for custom sidecar files. Default: guid")?;
+	writeln!(out, " --staged                     Diff the head commit with the index (staged changes only). Cannot be combined with <hash>.")?;
+	writeln!(out, " --format <format>            How to print the results: tree, json, porcelain (one 'A/D/R <guid> <path>' line per change), csv, dot (Graphviz) or markdown. Default: tree")?;
+	writeln!(out, "                              With ':<file>' (for example 'json:out.json') the format is written to that file, additionally to the normal output.")?;
+	writeln!(out, "                              Can be used multiple times. ':-' stands for the normal output (stdout or '--output').")?;
+	writeln!(out, " --output <file>              Write the results to this file (created or truncated) instead of stdout. Never colored with 'auto'.")?;
+	writeln!(out, " -q, --quiet                  Do not print progress, informational and warning messages. Errors are still printed.")?;
+	writeln!(out, " --exit-code                  Exit with 2 if GUIDs got added, removed or renamed, 0 otherwise. Errors exit with 1.")?;
+	writeln!(out, " --fail-on-conflict           Exit with 3 if a GUID is used by multiple files or a path got multiple GUIDs. Takes precedence over '--exit-code'.")?;
+	writeln!(out, " --list-files                 Only list the meta files (and their Git status) which would be inspected, then exit.")?;
+	writeln!(out, " --no-untracked               Ignore files in the work directory which are not tracked by Git.")?;
+	writeln!(out, " --recurse-submodules         Also inspect the meta files inside changed submodules. Their paths start with the submodule folder.")?;
+	writeln!(out, " --check-orphans              Only list added meta files, whose asset file or folder does not exist, then exit.")?;
+	writeln!(out, " --index                      Diff the (single) provided commit with the index instead of the work directory. Same as '--staged' without <hash>.")?;
+	writeln!(out, " --since <date|duration>      Use the newest commit at or before this time as first commit. ISO date (UTC) like '2024-05-01' or duration like '7d'.")?;
+	writeln!(out, " --until <date|duration>      Use the newest commit at or before this time as second commit, instead of the work directory.")?;
+	writeln!(out, " --summary                    Only print the summary line with the amount of changes. Implies '--quiet'.")?;
+	writeln!(out, " --by-extension               Instead of the trees, print how many assets got added, removed, renamed and modified per file extension.")?;
+	writeln!(out, " --group-moves                Instead of the trees, print renames grouped by their moved folder, like '42 assets moved from Old/ to New/'.")?;
+	writeln!(out, " -v, --verbose                With '--group-moves', also list each moved asset below its group.")?;
+	writeln!(out, " --rename-threshold <percent> How similar (0-100) a file has to be, for Git to detect it as renamed. Default: 50")?;
+	writeln!(out, " --only-renames               Only show renamed and moved assets, in all formats. Added and removed assets are left out.")?;
+	writeln!(out, " --only-conflicts             Only show GUIDs used by multiple files and paths with multiple GUIDs. Exit code 2 with '--exit-code'.")?;
+	writeln!(out, " --stats                      Also count all meta files on the new side of the diff and the changed folders vs files. Walks the whole work directory, if that is the new side.")?;
+	writeln!(out, " --invert                     Swap additions and removals, for when the two commits got passed in the wrong order.")?;
+	writeln!(out, " --no-legend                  Do not explain the colors and labels above the trees. Also left out with '--quiet'.")?;
+	writeln!(out, " --no-sort                    Keep the order in which Git reported the changes, instead of sorting by path (trees) or GUID.")?;
+	writeln!(out, " --strip-prefix <path>        Leave out this leading folder path from all shown paths, for example 'Assets/Characters'.")?;
+	writeln!(out, " --interactive                Ask whether renames between very different paths are actual renames, or unrelated removals and additions.")?;
+	writeln!(out, " --timings                    Print how long each phase took (creating the diff, reading blobs, printing, ...) to stderr.")?;
+	writeln!(out, " --watch                      Print the results again whenever a meta file in the work directory changes. Needs a build with the 'notify' feature.")?;
+	writeln!(out, " --batch                      Read '<from> <to>' commit pairs from stdin (one per line) and print the results for each pair.")?;
+	writeln!(out, " --git-dir <path>             Use this '.git' folder, instead of searching for the repository. Like Git's own option.")?;
+	writeln!(out, " --work-tree <path>           Use this folder as work directory of the repository. Like Git's own option.")?;
+	writeln!(out, " -h, --help                   Print this help and exit.")?;
+	writeln!(out, " -V, --version                Print the version and exit.")?;
+	Ok(())
+}
+
 fn print_help_and_quit(error_message: &str) -> ! {
 	eprintln!("{error_message}");
 	eprintln!();
-	eprintln!("Help: This tool will create a diff for a Unity Git repository and read the changed meta files to display which assets got added/removed/renamed.");
-	eprintln!(" ./{} [[--path] <path>] [hash 1] [hash 2]", Path::new(&env::args().next().unwrap()).iter().next_back().unwrap().display());
-	eprintln!(" - If <path> is provided, the current execution directory is changed. Should be the root folder of the repository.");
-	eprintln!(" - If no <hash> is provided, the diff will be created between head commit and work directory.");
-	eprintln!(" - If one <hash> is provided, the diff will be created between provided commit and work directory.");
-	eprintln!(" - If two <hashes> are provided, the diff will be created between these two provided commits.");
+	write_help(&mut io::stderr()).expect("Failed to write to stderr.");
 	process::exit(1);
 }
 
@@ -43,60 +146,341 @@ pub struct ArgumentTemporaryData {
 	potential_hash_b: Option<String>,
 }
 
-pub fn parse_arguments_open_repo() -> (Repository, ArgumentTemporaryData) {
-	let mut potential_path = None;
-	let mut potential_hash_a : Option<String> = None;
-	let mut potential_hash_b : Option<String> = None;
-	
-	// Ensure there are at most 4 arguments:
-	// (./exe) --path <path> <hash> <hash>
-	if env::args().len() > (1 + 4) {
-		print_help_and_quit("Too many arguments.");
+// A '--format <format>:<file>', written next to the normal output.
+pub struct ExtraOutput {
+	pub format: OutputFormat,
+	pub path: PathBuf,
+}
+
+// Options which do not affect which repository or diff is used.
+#[derive(Default)]
+pub struct Options {
+	pub color: ColorChoice,
+	pub ascii: bool,
+	// Root node of the trees, empty for the name of the repository folder.
+	pub root_label: Option<String>,
+	pub unified: bool,
+	// Show the unchanged siblings of added and renamed assets in the trees.
+	pub context: bool,
+	// Repository relative folders, empty means everything.
+	pub subdirs: Vec<PathBuf>,
+	// Meta files matching any of these are ignored.
+	pub excludes: Vec<GlobPattern>,
+	// Key of the GUID line in meta files, None for Unity's 'guid'.
+	pub guid_key: Option<String>,
+	pub staged: bool,
+	pub format: OutputFormat,
+	// The results are written to these files too, each in its own format.
+	pub extra_outputs: Vec<ExtraOutput>,
+	// Results are written to this file instead of stdout.
+	pub output: Option<PathBuf>,
+	// Suppresses informational and warning messages.
+	pub quiet: bool,
+	// Exit with a distinct code if GUIDs changed.
+	pub exit_code: bool,
+	// Exit with a distinct code if GUIDs conflict.
+	pub fail_on_conflict: bool,
+	// Only print the meta file deltas which would be analyzed.
+	pub list_files: bool,
+	pub no_untracked: bool,
+	// Inspect changed submodules like the repository itself.
+	pub recurse_submodules: bool,
+	// Only report added meta files without their asset.
+	pub check_orphans: bool,
+	// Unix timestamps, replacing the commit arguments with the newest commit at or before this time.
+	pub since: Option<i64>,
+	pub until: Option<i64>,
+	// Compare the first commit with the index, instead of the work directory.
+	pub index: bool,
+	// Only print the summary line, implies quiet.
+	pub summary: bool,
+	// Print a table of the changes per asset extension, instead of the trees.
+	pub by_extension: bool,
+	// Print the renames grouped by old and new folder, instead of the trees.
+	pub group_moves: bool,
+	// Print more details, currently the assets of each move group.
+	pub verbose: bool,
+	// Similarity in percent for Git rename detection, None keeps the libgit2 default.
+	pub rename_threshold: Option<u16>,
+	// Commit pairs are read from stdin, instead of the arguments.
+	pub batch: bool,
+	// Leave out pure additions and removals.
+	pub only_renames: bool,
+	// Only print GUID collisions and path conflicts.
+	pub only_conflicts: bool,
+	// Count the unchanged meta files too.
+	pub stats: bool,
+	// Swap the added and removed side of the results.
+	pub invert: bool,
+	pub no_legend: bool,
+	// Keep the order of the diff.
+	pub no_sort: bool,
+	// Repository relative folder, left out of the displayed paths.
+	pub strip_prefix: Option<PathBuf>,
+	// Ask the user to confirm unlikely renames. Only when stdin is a terminal.
+	pub interactive: bool,
+	// Re-run on every meta file change in the work directory.
+	pub watch: bool,
+	// Measure the phases of the run.
+	pub timings: bool,
+}
+
+// Matches '--name value' and '--name=value'. Returns None if the argument is a different one.
+fn take_flag_value(name: &str, argument: &str, argument_iterator: &mut impl Iterator<Item = String>) -> Option<String> {
+	if argument == name {
+		Some(argument_iterator.next().unwrap_or_else(|| print_help_and_quit(&format!("Missing value after '{name}'."))))
+	} else {
+		argument.strip_prefix(name).and_then(|rest| rest.strip_prefix('=')).map(str::to_owned)
 	}
-	let mut argument_iterator = env::args().skip(1).peekable(); // Skip executable path.
-	
-	// Only triggers when the first argument is '--path'
-	// Consumes 2 arguments if triggers.
-	if let Some(path_hint) = argument_iterator.peek() {
-		if path_hint.eq_ignore_ascii_case("--path") {
-			argument_iterator.next();
+}
+
+// Returns false if the argument is not a known option.
+fn parse_option(options: &mut Options, argument: &str, argument_iterator: &mut impl Iterator<Item = String>) -> bool {
+	if let Some(value) = take_flag_value("--color", argument, argument_iterator) {
+		options.color = value.parse().unwrap_or_else(|error: String| print_help_and_quit(&error));
+	} else if argument == "--ascii" {
+		options.ascii = true;
+	} else if let Some(value) = take_flag_value("--root-label", argument, argument_iterator) {
+		options.root_label = Some(value);
+	} else if argument == "--unified" {
+		options.unified = true;
+	} else if argument == "--context" {
+		options.context = true;
+	} else if let Some(value) = take_flag_value("--subdir", argument, argument_iterator) {
+		options.subdirs.push(PathBuf::from(value));
+	} else if let Some(value) = take_flag_value("--exclude", argument, argument_iterator) {
+		options.excludes.push(value.parse().unwrap_or_else(|error: String| print_help_and_quit(&error)));
+	} else if let Some(value) = take_flag_value("--guid-key", argument, argument_iterator) {
+		// The key is matched at the very start of a line, up to the colon.
+		if value.is_empty() || value.contains(':') || value.contains(char::is_whitespace) {
+			print_help_and_quit(&format!("Invalid GUID key '{value}', it must not be empty or contain colons or spaces."));
+		}
+		options.guid_key = Some(value);
+	} else if argument == "--staged" {
+		options.staged = true;
+	} else if let Some(value) = take_flag_value("--format", argument, argument_iterator) {
+		// Paths may contain ':' themselves (like 'C:\out.json'), but format names never do.
+		let (format, destination) = value.split_once(':').map_or((value.as_str(), None), |(format, destination)| (format, Some(destination)));
+		let format = format.parse().unwrap_or_else(|error: String| print_help_and_quit(&error));
+		match destination {
+			None | Some("-") => options.format = format,
+			Some("") => print_help_and_quit(&format!("Missing file after ':' in '--format {value}', use '-' for the normal output.")),
+			Some(path) => options.extra_outputs.push(ExtraOutput { format, path: PathBuf::from(path) }),
+		}
+	} else if let Some(value) = take_flag_value("--output", argument, argument_iterator) {
+		options.output = Some(PathBuf::from(value));
+	} else if argument == "--quiet" || argument == "-q" {
+		options.quiet = true;
+	} else if argument == "--exit-code" {
+		options.exit_code = true;
+	} else if argument == "--fail-on-conflict" {
+		options.fail_on_conflict = true;
+	} else if argument == "--list-files" {
+		options.list_files = true;
+	} else if argument == "--no-untracked" {
+		options.no_untracked = true;
+	} else if argument == "--recurse-submodules" {
+		options.recurse_submodules = true;
+	} else if argument == "--check-orphans" {
+		options.check_orphans = true;
+	} else if argument == "--index" {
+		options.index = true;
+	} else if argument == "--summary" {
+		options.summary = true;
+		options.quiet = true;
+	} else if argument == "--by-extension" {
+		options.by_extension = true;
+	} else if argument == "--group-moves" {
+		options.group_moves = true;
+	} else if argument == "--verbose" || argument == "-v" {
+		options.verbose = true;
+	} else if let Some(value) = take_flag_value("--since", argument, argument_iterator) {
+		options.since = Some(parse_time_point(&value).unwrap_or_else(|error| print_help_and_quit(&error)));
+	} else if let Some(value) = take_flag_value("--until", argument, argument_iterator) {
+		options.until = Some(parse_time_point(&value).unwrap_or_else(|error| print_help_and_quit(&error)));
+	} else if argument == "--only-renames" {
+		options.only_renames = true;
+	} else if argument == "--only-conflicts" {
+		options.only_conflicts = true;
+	} else if argument == "--stats" {
+		options.stats = true;
+	} else if argument == "--invert" {
+		options.invert = true;
+	} else if argument == "--no-legend" {
+		options.no_legend = true;
+	} else if argument == "--no-sort" {
+		options.no_sort = true;
+	} else if let Some(value) = take_flag_value("--strip-prefix", argument, argument_iterator) {
+		options.strip_prefix = Some(PathBuf::from(value));
+	} else if argument == "--interactive" {
+		options.interactive = true;
+	} else if argument == "--timings" {
+		options.timings = true;
+	} else if argument == "--watch" {
+		options.watch = true;
+	} else if argument == "--batch" {
+		options.batch = true;
+	} else if let Some(value) = take_flag_value("--rename-threshold", argument, argument_iterator) {
+		options.rename_threshold = match value.parse() {
+			Ok(percent @ 0..=100) => Some(percent),
+			_ => print_help_and_quit(&format!("Invalid rename threshold '{value}', expected a percentage from 0 to 100.")),
+		};
+	} else {
+		return false;
+	}
+	true
+}
+
+// Arguments which select the repository and the commits. Everything else ends up in the Options.
+#[derive(Default)]
+struct SortedArguments {
+	positional_arguments: Vec<String>,
+	potential_path: Option<String>,
+	git_dir: Option<PathBuf>,
+	work_tree: Option<PathBuf>,
+}
+
+// Sort out all options first, only the path and hash arguments remain.
+fn sort_arguments(options: &mut Options) -> SortedArguments {
+	let mut sorted = SortedArguments::default();
+	let mut argument_iterator = env::args().skip(1); // Skip executable path.
+	while let Some(argument) = argument_iterator.next() {
+		// Requested on purpose, thus not an error:
+		if argument == "--help" || argument == "-h" {
+			write_help(&mut io::stdout()).expect("Failed to write to stdout.");
+			process::exit(0);
+		} else if argument == "--version" || argument == "-V" {
+			println!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+			process::exit(0);
+		} else if argument.eq_ignore_ascii_case("--path") {
 			if let Some(path) = argument_iterator.next() {
-				potential_path = Some(path);
+				sorted.potential_path = Some(path);
 			} else {
 				print_help_and_quit("Missing path argument after '--path'.");
 			}
+		} else if let Some(path) = take_flag_value("--git-dir", &argument, &mut argument_iterator) {
+			sorted.git_dir = Some(PathBuf::from(path));
+		} else if let Some(path) = take_flag_value("--work-tree", &argument, &mut argument_iterator) {
+			sorted.work_tree = Some(PathBuf::from(path));
+		} else if parse_option(options, &argument, &mut argument_iterator) {
+			// Consumed.
+		} else if argument.starts_with("--") {
+			print_help_and_quit(&format!("Unknown option '{argument}'."));
+		} else {
+			sorted.positional_arguments.push(argument);
 		}
 	}
-	
-	// Assume no path and consume up to two hash arguments
-	if let Some(hint_1) = argument_iterator.next() {
-		potential_hash_a = Some(hint_1);
-	}
-	if let Some(hint_2) = argument_iterator.next() {
-		potential_hash_b = Some(hint_2);
+	sorted
+}
+
+// Defaults for a repository, committed as '.unitymetadiff' into its root folder. One option per line, without the leading '--':
+//  format=json
+//  ascii
+// Empty lines and lines starting with '#' are ignored.
+// Options which change how the tool runs (instead of what it shows) stay on the command line, a committed file would surprise the user.
+const OPTIONS_NOT_IN_CONFIG: [&str; 4] = ["batch", "watch", "interactive", "output"];
+
+fn apply_config_file(options: &mut Options, config_path: &Path) {
+	let content = fs::read_to_string(config_path).unwrap_or_else(|error| {
+		eprintln!("Could not read config file '{}': {error}", config_path.display());
+		process::exit(1);
+	});
+	for (line_index, line) in content.lines().enumerate() {
+		let line = line.trim();
+		if line.is_empty() || line.starts_with('#') {
+			continue;
+		}
+		let key = line.split_once('=').map_or(line, |(key, _)| key).trim();
+		if OPTIONS_NOT_IN_CONFIG.contains(&key) {
+			print_help_and_quit(&format!("Option '--{key}' in line {} of '{}' can only be given on the command line.", line_index + 1, config_path.display()));
+		}
+		let argument = match line.split_once('=') {
+			Some((key, value)) => format!("--{}={}", key.trim(), value.trim()),
+			None => format!("--{line}"),
+		};
+		if !parse_option(options, &argument, &mut std::iter::empty()) {
+			print_help_and_quit(&format!("Unknown option '{line}' in line {} of '{}'.", line_index + 1, config_path.display()));
+		}
 	}
-	
-	// If there still is an argument now, there was no '--path <path>' previously.
-	// Thus, the arguments are '<path> <hash> <hash>'. Shift them appropriately and consume the last hash.
-	if let Some(hint_3) = argument_iterator.next() {
-		potential_path = potential_hash_a;
-		potential_hash_a = potential_hash_b;
-		potential_hash_b = Some(hint_3);
+}
+
+pub fn parse_arguments_open_repo() -> (Repository, Options, ArgumentTemporaryData) {
+	let mut options = Options::default();
+	let mut potential_hash_a : Option<String> = None;
+	let mut potential_hash_b : Option<String> = None;
+	let SortedArguments { mut positional_arguments, mut potential_path, git_dir, work_tree } = sort_arguments(&mut options);
+	// Needed right away, as opening the repository already reports.
+	log::set_quiet(options.quiet);
+	if options.batch && !positional_arguments.is_empty() && (potential_path.is_some() || positional_arguments.len() > 1 || !Path::new(&positional_arguments[0]).is_dir()) {
+		print_help_and_quit("Option '--batch' reads the commits from stdin, only a <path> can be provided as argument.");
 	}
 	
-	// At this point it is possible, that just the path and an optional hash was provided:
-	// <path>
-	// <path> <hash>
-	if potential_path.is_none() && potential_hash_a.is_some() {
-		// Check if the first argument could not be a hash:
-		if !is_hash_like(potential_hash_a.as_ref().unwrap()) {
-			// Cannot be a hash, shift it to be a path.
+	// A range '<from>..<to>' provides both commits at once. Paths like '../repo' also contain '..', but exist on disk.
+	if let Some(index) = positional_arguments.iter().position(|argument| argument.contains("..") && !Path::new(argument).exists()) {
+		let range = positional_arguments.remove(index);
+		// Reject missing sides and the symmetric difference syntax '<from>...<to>':
+		let Some((from, to)) = range.split_once("..").filter(|(from, to)| !from.is_empty() && !to.is_empty() && !to.starts_with('.')) else {
+			print_help_and_quit(&format!("Invalid range '{range}', expected '<from>..<to>'."));
+		};
+		potential_hash_a = Some(from.to_owned());
+		potential_hash_b = Some(to.to_owned());
+		// Only the path may remain:
+		if positional_arguments.len() > if potential_path.is_some() { 0 } else { 1 } {
+			print_help_and_quit("Too many arguments, the range already provides both commits.");
+		}
+		if let Some(path) = positional_arguments.pop() {
+			potential_path = Some(path);
+		}
+	} else {
+		// Ensure there are at most 3 positional arguments:
+		// (./exe) [--path] <path> <hash> <hash>
+		if positional_arguments.len() > if potential_path.is_some() { 2 } else { 3 } {
+			print_help_and_quit("Too many arguments.");
+		}
+		let mut argument_iterator = positional_arguments.into_iter();
+		
+		// Assume no path and consume up to two hash arguments
+		if let Some(hint_1) = argument_iterator.next() {
+			potential_hash_a = Some(hint_1);
+		}
+		if let Some(hint_2) = argument_iterator.next() {
+			potential_hash_b = Some(hint_2);
+		}
+		
+		// If there still is an argument now, there was no '--path <path>' previously.
+		// Thus, the arguments are '<path> <hash> <hash>'. Shift them appropriately and consume the last hash.
+		if let Some(hint_3) = argument_iterator.next() {
 			potential_path = potential_hash_a;
 			potential_hash_a = potential_hash_b;
-			potential_hash_b = None; // If it was not already.
+			potential_hash_b = Some(hint_3);
+		}
+		
+		// At this point it is possible, that just the path and an optional hash was provided:
+		// <path>
+		// <path> <hash>
+		if potential_path.is_none() && potential_hash_a.is_some() {
+			// Check if the first argument is rather a folder than a revision (hashes always win):
+			let hint = potential_hash_a.as_ref().unwrap();
+			if !is_hash_like(hint) && Path::new(hint).is_dir() {
+				// Not a hash, but an existing folder - shift it to be a path.
+				potential_path = potential_hash_a;
+				potential_hash_a = potential_hash_b;
+				potential_hash_b = None; // If it was not already.
+			}
 		}
 	}
+	
 	// Find Git repository:
+	for (name, path) in [("--git-dir", &git_dir), ("--work-tree", &work_tree)] {
+		if let Some(path) = path {
+			if !path.is_dir() {
+				print_help_and_quit(&format!("No existing folder at '{}' for '{name}'.", path.display()));
+			}
+			if potential_path.is_some() {
+				print_help_and_quit(&format!("Option '{name}' already selects the repository, it cannot be combined with <path>."));
+			}
+		}
+	}
 	if let Some(argument_path) = potential_path {
 		let path = Path::new(&argument_path);
 		if !path.exists() || !path.is_dir(){
@@ -104,52 +488,269 @@ pub fn parse_arguments_open_repo() -> (Repository, ArgumentTemporaryData) {
 		}
 		env::set_current_dir(path).expect("Failed to change directory.");
 	}
-	let repo = match Repository::open(".") {
+	// Like Git itself, find the repository from any folder inside it. Unless told where it is.
+	let opened = match &git_dir {
+		Some(git_dir) => Repository::open(git_dir),
+		None => Repository::discover(work_tree.as_deref().unwrap_or(Path::new("."))),
+	};
+	let repo = match opened {
 		Ok(repo) => repo,
 		Err(e) => {
-			eprintln!("Did not find OR could not open repository at location: {}", env::current_dir().unwrap().display());
+			let location = git_dir.as_ref().or(work_tree.as_ref()).cloned().unwrap_or_else(|| env::current_dir().unwrap());
+			eprintln!("Did not find OR could not open repository at location: {}", location.display());
 			eprintln!(" Details (by gitlib2): {e}");
 			process::exit(1);
 		},
 	};
-	println!("Using Git repository at path: {}", env::current_dir().unwrap().display());
+	if let Some(work_tree) = work_tree {
+		// Not persisted, the repository configuration stays untouched.
+		repo.set_workdir(&work_tree, false).unwrap_or_else(|error| {
+			eprintln!("Could not use '{}' as work directory.", work_tree.display());
+			eprintln!(" Details (by gitlib2): {error}");
+			process::exit(1);
+		});
+	}
+	// The config file only provides defaults, thus the options of the command line get applied on top again.
+	// It lives in the root folder, a bare repository has none - then it is looked up in the current directory.
+	let config_path = repo.workdir().map_or_else(|| PathBuf::from(CONFIG_FILE_NAME), |workdir| workdir.join(CONFIG_FILE_NAME));
+	if config_path.is_file() {
+		options = Options::default();
+		apply_config_file(&mut options, &config_path);
+		// Repeatable options add up. Given on the command line, they replace the ones of the config file instead.
+		let config_subdirs = mem::take(&mut options.subdirs);
+		let config_excludes = mem::take(&mut options.excludes);
+		let config_extra_outputs = mem::take(&mut options.extra_outputs);
+		sort_arguments(&mut options);
+		if options.subdirs.is_empty() {
+			options.subdirs = config_subdirs;
+		}
+		if options.excludes.is_empty() {
+			options.excludes = config_excludes;
+		}
+		if options.extra_outputs.is_empty() {
+			options.extra_outputs = config_extra_outputs;
+		}
+		log::set_quiet(options.quiet);
+	}
+	log::message(format_args!("Using Git repository at path: {}", repo.workdir().unwrap_or(repo.path()).display()));
 	
-	(repo, ArgumentTemporaryData {
+	if options.only_conflicts && !matches!(options.format, OutputFormat::Tree | OutputFormat::Json) {
+		print_help_and_quit("Option '--only-conflicts' only supports the tree and json format.");
+	}
+	if !options.extra_outputs.is_empty() {
+		if options.batch || options.list_files || options.check_orphans || options.only_conflicts {
+			print_help_and_quit("Option '--format <format>:<file>' cannot be combined with '--batch', '--list-files', '--check-orphans' or '--only-conflicts'.");
+		}
+		let mut paths: Vec<&PathBuf> = options.extra_outputs.iter().map(|extra| &extra.path).chain(&options.output).collect();
+		paths.sort();
+		if let Some(pair) = paths.windows(2).find(|pair| pair[0] == pair[1]) {
+			print_help_and_quit(&format!("File '{}' is used as output more than once.", pair[0].display()));
+		}
+	}
+	if options.batch {
+		if options.staged || options.index || options.since.is_some() {
+			print_help_and_quit("Option '--batch' always diffs two commits, it cannot be combined with '--staged', '--index' or '--since'.");
+		}
+		if matches!(options.format, OutputFormat::Porcelain | OutputFormat::Csv | OutputFormat::Dot) {
+			print_help_and_quit("Option '--batch' has no way to separate the commit pairs in this format, use tree, json or markdown.");
+		}
+	}
+	if options.watch {
+		if !cfg!(feature = "notify") {
+			print_help_and_quit("Option '--watch' needs file system notifications, build with 'cargo build --features notify'.");
+		}
+		if potential_hash_a.is_some() || options.since.is_some() || options.staged || options.index || options.batch {
+			print_help_and_quit("Option '--watch' always diffs the head commit with the work directory, it cannot be combined with commits, '--batch', '--staged' or '--index'.");
+		}
+		if options.output.is_some() || !options.extra_outputs.is_empty() || options.interactive {
+			print_help_and_quit("Option '--watch' reprints the results on the terminal, it cannot be combined with '--output', '--format <format>:<file>' or '--interactive'.");
+		}
+		if repo.workdir().is_none() {
+			print_help_and_quit("Option '--watch' needs a work directory, but the repository is bare.");
+		}
+	}
+	
+	(repo, options, ArgumentTemporaryData {
 		potential_hash_a,
 		potential_hash_b,
 	})
 }
 
-pub fn parse_arguments_create_diff(repo: &Repository, temp_data: ArgumentTemporaryData) -> Diff {
-	// Validate arguments:
-	fn validate_hash<'a>(repo: &'a Repository, hash_text: &str) -> Tree<'a> {
-		if !is_hash_like(hash_text) {
-			print_help_and_quit(&format!("Argument does not appear to be a git commit hash: '{hash_text}'"));
+// None while there are no commits yet. Diffing against no tree is the same as against an empty tree - everything is new.
+// A detached HEAD (as common in CI checkouts) resolves just like a branch.
+fn head_tree(repo: &Repository) -> Option<Tree<'_>> {
+	match repo.head().and_then(|head| head.peel_to_tree()) {
+		Ok(tree) => Some(tree),
+		Err(error) if error.code() == ErrorCode::UnbornBranch => None,
+		Err(error) => {
+			eprintln!("Could not resolve HEAD of the repository at location: {}", repo.workdir().unwrap_or(repo.path()).display());
+			eprintln!(" Details (by gitlib2): {error}");
+			process::exit(1);
 		}
-		match repo.find_commit_by_prefix(hash_text) {
-			Ok(commit) => match commit.tree() {
-				Ok(tree) => tree,
-				Err(error) => print_help_and_quit(&format!("Did not find OR could not load commit hash: {hash_text}\nDetails (by gitlib2): {error}"))
+	}
+}
+
+// Also returns where the new side of the diff is, for looking up files which are not part of the diff.
+pub fn parse_arguments_create_diff<'a>(repo: &'a Repository, options: &Options, temp_data: ArgumentTemporaryData) -> (Diff<'a>, DiffTarget<'a>) {
+	if options.staged && (temp_data.potential_hash_a.is_some() || options.since.is_some()) {
+		print_help_and_quit("Option '--staged' always diffs the head commit with the index, it cannot be combined with commit hashes.");
+	}
+	if options.since.is_some() && temp_data.potential_hash_a.is_some() {
+		print_help_and_quit("Option '--since' already selects the first commit, it cannot be combined with commit hashes.");
+	}
+	if options.until.is_some() && options.since.is_none() {
+		print_help_and_quit("Option '--until' selects the second commit, it requires '--since' for the first one.");
+	}
+	
+	// Walks back from HEAD, as 'git log --before' would.
+	fn resolve_time<'a>(repo: &'a Repository, time: i64) -> Tree<'a> {
+		let found = repo.revwalk().and_then(|mut walk| {
+			walk.set_sorting(Sort::TIME)?;
+			walk.push_head()?;
+			for oid in walk {
+				let commit = repo.find_commit(oid?)?;
+				if commit.time().seconds() <= time {
+					return commit.tree().map(Some);
+				}
 			}
-			Err(error) => print_help_and_quit(&format!("Did not find OR could not load commit hash: {hash_text}\nDetails (by gitlib2): {error}"))
+			Ok(None)
+		});
+		match found {
+			Ok(Some(tree)) => tree,
+			Ok(None) => print_help_and_quit(&format!("There is no commit at or before the requested time (Unix time {time}).")),
+			Err(error) => print_help_and_quit(&format!("Could not walk the commit history.\nDetails (by gitlib2): {error}")),
 		}
 	}
-	let hash_first = temp_data.potential_hash_a.map(|arg| validate_hash(repo, &arg));
-	let hash_second = temp_data.potential_hash_b.map(|arg| validate_hash(repo, &arg));
+	// Both revisions are resolved before anything else, so that a typo in either is reported together with the other one.
+	let (revision_first, revision_second) = resolve_revision_pair(repo, temp_data.potential_hash_a.as_deref(), temp_data.potential_hash_b.as_deref())
+		.unwrap_or_else(|error| print_help_and_quit(&error));
+	let hash_first = match options.since {
+		Some(time) => Some(resolve_time(repo, time)),
+		None => revision_first,
+	};
+	let hash_second = match options.until {
+		Some(time) => Some(resolve_time(repo, time)),
+		None => revision_second,
+	};
+	
+	if options.index && hash_second.is_some() {
+		print_help_and_quit("Option '--index' replaces the second commit with the index, it can only be combined with a single commit hash.");
+	}
 	
-	if let Some(hash_second) = hash_second {
+	let (mut diff, target) = if options.staged || options.index {
+		// Only what would be committed next. With '--staged' compared to the head commit, with '--index' to the given commit (head by default).
+		let first = hash_first.or_else(|| head_tree(repo));
+		(repo.diff_tree_to_index(first.as_ref(), None, None).unwrap(), DiffTarget::Index)
+	} else if let Some(hash_second) = hash_second {
 		let hash_first = hash_first.unwrap();
-		repo.diff_tree_to_tree(Some(&hash_first), Some(&hash_second), None).unwrap()
+		(repo.diff_tree_to_tree(Some(&hash_first), Some(&hash_second), None).unwrap(), DiffTarget::Tree(hash_second))
 	} else {
-		let first = if let Some(hash_first) = hash_first {
-			hash_first
-		} else {
-			repo.head().unwrap().peel_to_commit().unwrap().tree().unwrap()
-		};
-		
-		let mut diff_opts = DiffOptions::new();
-		diff_opts.include_untracked(true);
-		diff_opts.recurse_untracked_dirs(true);
-		repo.diff_tree_to_workdir_with_index(Some(&first), Some(&mut diff_opts)).unwrap()
+		let first = hash_first.or_else(|| head_tree(repo));
+		(diff_to_workdir(repo, first.as_ref(), options).unwrap(), DiffTarget::WorkDirectory)
+	};
+	find_renames(&mut diff, options);
+	(diff, target)
+}
+
+fn diff_to_workdir<'a>(repo: &'a Repository, first: Option<&Tree>, options: &Options) -> Result<Diff<'a>, git2::Error> {
+	let mut diff_opts = DiffOptions::new();
+	// Files not yet added to Git are usually part of the change. Unless only tracked changes are of interest.
+	diff_opts.include_untracked(!options.no_untracked);
+	diff_opts.recurse_untracked_dirs(!options.no_untracked);
+	repo.diff_tree_to_workdir_with_index(first, Some(&mut diff_opts))
+}
+
+// For '--watch', which creates the default diff (head commit with the work directory) again on every change.
+#[cfg(feature = "notify")]
+pub fn create_workdir_diff<'a>(repo: &'a Repository, options: &Options) -> Diff<'a> {
+	let mut diff = diff_to_workdir(repo, head_tree(repo).as_ref(), options).unwrap();
+	find_renames(&mut diff, options);
+	diff
+}
+
+// Resolve arguments, anything Git understands as a revision works (hashes, branches, tags, 'HEAD~2'):
+fn resolve_revision<'a>(repo: &'a Repository, revision_text: &str) -> Result<Tree<'a>, String> {
+	repo.revparse_single(revision_text)
+		.and_then(|object| object.peel_to_tree())
+		.map_err(|error| format!("Did not find OR could not load commit/branch/tag: {revision_text}\nDetails (by gitlib2): {error}"))
+}
+
+// Resolves both (optional) revisions. If any fails, the error lists all failing revisions, not only the first one.
+fn resolve_revision_pair<'a>(repo: &'a Repository, first: Option<&str>, second: Option<&str>) -> Result<(Option<Tree<'a>>, Option<Tree<'a>>), String> {
+	let resolve = |revision_text: Option<&str>| revision_text.map(|text| resolve_revision(repo, text)).transpose();
+	match (resolve(first), resolve(second)) {
+		(Ok(first), Ok(second)) => Ok((first, second)),
+		(Err(error), Ok(_)) | (Ok(_), Err(error)) => Err(error),
+		(Err(error_first), Err(error_second)) => Err(format!("{error_first}\n{error_second}")),
+	}
+}
+
+// Let Git pair up moved/copied files. For meta files the GUID is the deciding factor,
+// but this lets the diff agree with what 'git status' reports. Untracked files are included, as moves in the work directory are untracked until staged.
+fn find_renames(diff: &mut Diff, options: &Options) {
+	let mut find_opts = DiffFindOptions::new();
+	find_opts.renames(true);
+	if let Some(threshold) = options.rename_threshold {
+		find_opts.rename_threshold(threshold);
 	}
+	find_opts.copies(true);
+	find_opts.for_untracked(true);
+	diff.find_similar(Some(&mut find_opts)).unwrap();
+}
+
+// For '--batch', where the commits do not come from the arguments.
+pub fn create_diff_between<'a>(repo: &'a Repository, options: &Options, from: &str, to: &str) -> Result<(Diff<'a>, DiffTarget<'a>), String> {
+	let (Some(from), Some(to)) = resolve_revision_pair(repo, Some(from), Some(to))? else {
+		unreachable!("Both revisions are provided.");
+	};
+	let mut diff = repo.diff_tree_to_tree(Some(&from), Some(&to), None).unwrap();
+	find_renames(&mut diff, options);
+	Ok((diff, DiffTarget::Tree(to)))
+}
+
+// For '--recurse-submodules': A submodule whose commit changed (or whose work directory has changes) in the diff of the outer repository.
+pub struct SubmoduleChange {
+	pub path: PathBuf,
+	// Zero on the side where the submodule does not exist.
+	pub old_commit: Oid,
+	pub new_commit: Oid,
+}
+
+// Submodules show up in the diff as single entries, with the commit they point at instead of a blob.
+pub fn changed_submodules(diff: &Diff) -> Vec<SubmoduleChange> {
+	diff.deltas()
+		.filter(|delta| delta.old_file().mode() == FileMode::Commit || delta.new_file().mode() == FileMode::Commit)
+		.filter_map(|delta| Some(SubmoduleChange {
+			path: delta.new_file().path().or(delta.old_file().path())?.to_path_buf(),
+			old_commit: delta.old_file().id(),
+			new_commit: delta.new_file().id(),
+		}))
+		.collect()
+}
+
+// A submodule is opened from its folder in the work directory. Uninitialized submodules only have an empty folder there.
+pub fn open_submodule(repo: &Repository, change: &SubmoduleChange) -> Result<Repository, String> {
+	let workdir = repo.workdir().ok_or("the repository has no work directory to find it in")?;
+	Repository::open(workdir.join(&change.path))
+		.map_err(|error| format!("it is not initialized or could not be opened (run 'git submodule update --init')\nDetails (by gitlib2): {error}"))
+}
+
+// Diffs the submodule between the commits of the change, or against its work directory - matching the diff of the outer repository.
+pub fn create_submodule_diff<'a>(submodule_repo: &'a Repository, options: &Options, change: &SubmoduleChange, target: &DiffTarget) -> Result<Diff<'a>, String> {
+	let tree_of = |commit: Oid| -> Result<Option<Tree<'a>>, String> {
+		if commit.is_zero() {
+			return Ok(None);
+		}
+		submodule_repo.find_commit(commit)
+			.and_then(|commit| commit.tree())
+			.map(Some)
+			.map_err(|error| format!("commit {commit} is not available in it (run 'git submodule update')\nDetails (by gitlib2): {error}"))
+	};
+	let old_tree = tree_of(change.old_commit)?;
+	let mut diff = match target {
+		DiffTarget::WorkDirectory => diff_to_workdir(submodule_repo, old_tree.as_ref(), options),
+		DiffTarget::Index | DiffTarget::Tree(_) => submodule_repo.diff_tree_to_tree(old_tree.as_ref(), tree_of(change.new_commit)?.as_ref(), None),
+	}.map_err(|error| format!("could not create its diff\nDetails (by gitlib2): {error}"))?;
+	find_renames(&mut diff, options);
+	Ok(diff)
 }
@@ -0,0 +1,232 @@
+use crate::data::uuid::{MetaBlobError, MetaInfo, Uuid, DEFAULT_GUID_KEY};
+use git2::{ErrorCode, Oid, Repository};
+use std::collections::HashMap;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+// Starting a thread and opening the repository again has a cost, only worth it for bigger diffs.
+const MIN_BLOBS_PER_THREAD: usize = 64;
+// How many blobs are parsed between two progress reports.
+const PROGRESS_INTERVAL: usize = 100;
+
+// Blobs are immutable - the same Oid always yields the same UUID (and folder flag).
+// Commit-to-commit diffs and modifications reference the same blobs repeatedly, thus remember already parsed ones.
+// The cache is only valid for a single GUID key, see DEFAULT_GUID_KEY.
+pub struct BlobUuidCache {
+	cache: HashMap<Oid, MetaInfo>,
+	guid_key: String,
+}
+
+impl Default for BlobUuidCache {
+	fn default() -> Self {
+		Self::new(DEFAULT_GUID_KEY)
+	}
+}
+
+impl BlobUuidCache {
+	pub fn new(guid_key: &str) -> Self {
+		BlobUuidCache { cache: HashMap::new(), guid_key: guid_key.to_owned() }
+	}
+	
+	pub fn from_blob(&mut self, repo: &Repository, hash: Oid) -> Result<Uuid, MetaBlobError> {
+		self.meta_info_from_blob(repo, hash).map(|info| info.guid)
+	}
+	
+	pub fn meta_info_from_blob(&mut self, repo: &Repository, hash: Oid) -> Result<MetaInfo, MetaBlobError> {
+		if let Some(info) = self.cache.get(&hash) {
+			return Ok(*info);
+		}
+		let info = MetaInfo::from_blob(repo, hash, &self.guid_key)?;
+		self.cache.insert(hash, info);
+		Ok(info)
+	}
+	
+	// Parses all given blobs up front, on multiple threads. Afterward, lookups for these blobs are cache hits. Blobs which do not exist are skipped.
+	// Git objects cannot be shared between threads, thus every worker gets its own handle of the repository.
+	pub fn prefetch(&mut self, repo: &Repository, hashes: impl IntoIterator<Item = Oid>) -> Result<(), MetaBlobError> {
+		self.prefetch_with_progress(repo, hashes, &|_, _| {})
+	}
+	
+	// Same as prefetch(), but reports (parsed, total) every few blobs and once all are done. Called from the worker threads.
+	pub fn prefetch_with_progress(
+		&mut self,
+		repo: &Repository,
+		hashes: impl IntoIterator<Item = Oid>,
+		progress: &(dyn Fn(usize, usize) + Sync),
+	) -> Result<(), MetaBlobError> {
+		let mut missing: Vec<Oid> = hashes.into_iter().filter(|hash| !hash.is_zero() && !self.cache.contains_key(hash)).collect();
+		missing.sort();
+		missing.dedup();
+		let total = missing.len();
+		let parsed = AtomicUsize::new(0);
+		let report_parsed = || {
+			let done = parsed.fetch_add(1, Ordering::Relaxed) + 1;
+			if done.is_multiple_of(PROGRESS_INTERVAL) || done == total {
+				progress(done, total);
+			}
+		};
+		
+		let thread_count = thread::available_parallelism().map_or(1, NonZeroUsize::get).min(missing.len() / MIN_BLOBS_PER_THREAD);
+		let worker_repositories: Option<Vec<Repository>> = if thread_count > 1 {
+			(0..thread_count).map(|_| Repository::open(repo.path()).ok()).collect()
+		} else {
+			None
+		};
+		let Some(worker_repositories) = worker_repositories else {
+			// Not worth it (or the repository could not be opened again), stay on this thread.
+			for hash in missing {
+				match self.from_blob(repo, hash) {
+					Err(error) if !Self::is_not_found(&error) => return Err(error),
+					_ => report_parsed(),
+				}
+			}
+			return Ok(());
+		};
+		
+		let chunk_size = missing.len().div_ceil(thread_count);
+		let guid_key = self.guid_key.as_str();
+		let results: Vec<Result<Vec<(Oid, MetaInfo)>, MetaBlobError>> = thread::scope(|scope| {
+			let workers: Vec<_> = missing.chunks(chunk_size).zip(worker_repositories).map(|(chunk, worker_repo)| {
+				let report_parsed = &report_parsed;
+				scope.spawn(move || {
+					chunk.iter().filter_map(|&hash| {
+						let result = MetaInfo::from_blob(&worker_repo, hash, guid_key);
+						report_parsed();
+						match result {
+							Err(error) if Self::is_not_found(&error) => None,
+							result => Some(result.map(|info| (hash, info))),
+						}
+					}).collect()
+				})
+			}).collect();
+			workers.into_iter().map(|worker| worker.join().expect("Blob parsing thread panicked.")).collect()
+		});
+		for result in results {
+			self.cache.extend(result?);
+		}
+		Ok(())
+	}
+	
+	// Hashes of work directory files are known to libgit2, but not stored as blob. The caller reads these files from disk instead.
+	fn is_not_found(error: &MetaBlobError) -> bool {
+		matches!(error, MetaBlobError::Lookup { error, .. } if error.code() == ErrorCode::NotFound)
+	}
+	
+	pub fn len(&self) -> usize {
+		self.cache.len()
+	}
+	
+	pub fn is_empty(&self) -> bool {
+		self.cache.is_empty()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	use crate::data::uuid::test_helpers::temp_repository;
+	
+	#[test]
+	fn second_lookup_does_not_read_the_repository() {
+		let (path, repo) = temp_repository("cache-hit");
+		let (empty_path, empty_repo) = temp_repository("cache-hit-empty");
+		let hash = repo.blob(b"fileFormatVersion: 2\nguid: 63079bf56d891f040a461867b5dc65cb\n").unwrap();
+		
+		let mut cache = BlobUuidCache::default();
+		let first = cache.from_blob(&repo, hash).unwrap();
+		assert_eq!(cache.len(), 1);
+		// The blob does not exist in the other repository, thus it can only come from the cache.
+		assert!(empty_repo.find_blob(hash).is_err());
+		assert_eq!(cache.from_blob(&empty_repo, hash).unwrap(), first);
+		assert_eq!(cache.len(), 1);
+		
+		drop((repo, empty_repo));
+		std::fs::remove_dir_all(path).unwrap();
+		std::fs::remove_dir_all(empty_path).unwrap();
+	}
+	
+	#[test]
+	fn errors_are_not_cached() {
+		let (path, repo) = temp_repository("cache-error");
+		let hash = repo.blob(b"fileFormatVersion: 2\n").unwrap();
+		
+		let mut cache = BlobUuidCache::default();
+		assert!(matches!(cache.from_blob(&repo, hash), Err(MetaBlobError::MissingGuid { .. })));
+		assert!(cache.is_empty());
+		
+		drop(repo);
+		std::fs::remove_dir_all(path).unwrap();
+	}
+	
+	// Enough blobs to use multiple threads. Every fifth one is a folder.
+	fn write_many_blobs(repo: &Repository, count: usize) -> Vec<Oid> {
+		(0..count).map(|index| {
+			let folder = if index % 5 == 0 { "folderAsset: yes\n" } else { "" };
+			repo.blob(format!("fileFormatVersion: 2\nguid: {index:032x}\n{folder}").as_bytes()).unwrap()
+		}).collect()
+	}
+	
+	#[test]
+	fn prefetch_matches_serial_lookups() {
+		let (path, repo) = temp_repository("cache-prefetch");
+		let hashes = write_many_blobs(&repo, MIN_BLOBS_PER_THREAD * 8 + 3);
+		
+		let mut prefetched = BlobUuidCache::default();
+		let reported = std::sync::Mutex::new(Vec::new());
+		prefetched.prefetch_with_progress(&repo, hashes.iter().copied(), &|done, total| reported.lock().unwrap().push((done, total))).unwrap();
+		assert_eq!(prefetched.len(), hashes.len());
+		let reported = reported.into_inner().unwrap();
+		assert_eq!(reported.iter().map(|(done, _)| *done).max(), Some(hashes.len()));
+		assert!(reported.iter().all(|(_, total)| *total == hashes.len()));
+		
+		let mut serial = BlobUuidCache::default();
+		for &hash in &hashes {
+			let expected = serial.meta_info_from_blob(&repo, hash).unwrap();
+			let info = prefetched.cache[&hash];
+			assert_eq!((info.guid, info.is_folder), (expected.guid, expected.is_folder));
+		}
+		
+		drop(repo);
+		std::fs::remove_dir_all(path).unwrap();
+	}
+	
+	#[test]
+	fn prefetch_skips_missing_blobs_and_reports_broken_ones() {
+		let (path, repo) = temp_repository("cache-prefetch-errors");
+		let mut hashes = write_many_blobs(&repo, MIN_BLOBS_PER_THREAD * 4);
+		// Work directory files have hashes without blobs, these are read from disk later.
+		hashes.push(Oid::hash_object(git2::ObjectType::Blob, b"not stored").unwrap());
+		hashes.push(Oid::zero());
+		let mut cache = BlobUuidCache::default();
+		cache.prefetch(&repo, hashes.iter().copied()).unwrap();
+		assert_eq!(cache.len(), MIN_BLOBS_PER_THREAD * 4);
+		
+		hashes.push(repo.blob(b"fileFormatVersion: 2\nguid: broken\n").unwrap());
+		let mut cache = BlobUuidCache::default();
+		assert!(matches!(cache.prefetch(&repo, hashes), Err(MetaBlobError::MalformedGuid { .. })));
+		
+		drop(repo);
+		std::fs::remove_dir_all(path).unwrap();
+	}
+	
+	#[test]
+	fn custom_guid_key_is_used_for_lookups_and_prefetching() {
+		let (path, repo) = temp_repository("cache-guid-key");
+		let hash = repo.blob(b"fileFormatVersion: 2\nguid: 11111111111111111111111111111111\nuuid: 63079bf56d891f040a461867b5dc65cb\n").unwrap();
+		
+		let mut cache = BlobUuidCache::new("uuid");
+		assert_eq!(cache.from_blob(&repo, hash).unwrap().to_string(), "63079bf56d891f040a461867b5dc65cb");
+		assert_eq!(BlobUuidCache::default().from_blob(&repo, hash).unwrap().to_string(), "11111111111111111111111111111111");
+		
+		// The other blobs only have a 'guid:' line, the worker threads must use the same key.
+		let mut hashes = write_many_blobs(&repo, MIN_BLOBS_PER_THREAD * 4);
+		hashes.push(hash);
+		let mut prefetched = BlobUuidCache::new("uuid");
+		assert!(matches!(prefetched.prefetch(&repo, hashes), Err(MetaBlobError::MissingGuid { .. })));
+		
+		drop(repo);
+		std::fs::remove_dir_all(path).unwrap();
+	}
+}
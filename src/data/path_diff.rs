@@ -0,0 +1,106 @@
+use std::cmp::min;
+use std::path::{Component, Path};
+
+// Splits the path 'a' into the parts it has in common with path 'b' at the start, the differing middle and the parts in common at the end.
+// Parts already matched from the start are not matched again from the end. For example 'A/B' and 'A/B/A/B' only have the 2 starting parts in common.
+pub fn split_common<'a>(a: &'a Path, b: &Path) -> (Vec<Component<'a>>, Vec<Component<'a>>, Vec<Component<'a>>) {
+	let mut parts_a: Vec<Component> = a.components().collect();
+	let parts_b: Vec<Component> = b.components().collect();
+	// Start and end together can match at most all parts of the shorter path.
+	let min_part_count = min(parts_a.len(), parts_b.len());
+	
+	let start_count = parts_a.iter().zip(&parts_b).take_while(|(part_a, part_b)| part_a == part_b).count();
+	let end_count = parts_a.iter().rev().zip(parts_b.iter().rev())
+		.take(min_part_count - start_count)
+		.take_while(|(part_a, part_b)| part_a == part_b)
+		.count();
+	
+	let end = parts_a.split_off(parts_a.len() - end_count);
+	let middle = parts_a.split_off(start_count);
+	(parts_a, middle, end)
+}
+
+// The rest of the path after the prefix. None if the path is not below the prefix, or is the prefix itself (nothing would be left to show).
+pub fn strip_prefix<'a>(path: &'a Path, prefix: &Path) -> Option<&'a Path> {
+	path.strip_prefix(prefix).ok().filter(|rest| rest.components().next().is_some())
+}
+
+// How much two paths have in common, from 0.0 (nothing) to 1.0 (same path). Counts the parts shared at the start and the end, see split_common().
+pub fn similarity(a: &Path, b: &Path) -> f32 {
+	let part_count = a.components().count() + b.components().count();
+	if part_count == 0 {
+		return 1.0;
+	}
+	let (start, _, end) = split_common(a, b);
+	(2 * (start.len() + end.len())) as f32 / part_count as f32
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	use std::path::PathBuf;
+	
+	// The three parts as paths, for readable assertions.
+	fn split(a: &str, b: &str) -> (PathBuf, PathBuf, PathBuf) {
+		let (start, middle, end) = split_common(Path::new(a), Path::new(b));
+		(start.iter().collect(), middle.iter().collect(), end.iter().collect())
+	}
+	
+	fn parts(start: &str, middle: &str, end: &str) -> (PathBuf, PathBuf, PathBuf) {
+		(PathBuf::from(start), PathBuf::from(middle), PathBuf::from(end))
+	}
+	
+	#[test]
+	fn identical_paths_are_all_start() {
+		assert_eq!(split("A/B/x.png", "A/B/x.png"), parts("A/B/x.png", "", ""));
+		assert_eq!(split("x.png", "x.png"), parts("x.png", "", ""));
+	}
+	
+	#[test]
+	fn paths_differing_in_the_first_part() {
+		assert_eq!(split("A/B/x.png", "C/B/x.png"), parts("", "A", "B/x.png"));
+	}
+	
+	#[test]
+	fn paths_differing_in_the_last_part() {
+		assert_eq!(split("A/B/x.png", "A/B/y.png"), parts("A/B", "x.png", ""));
+	}
+	
+	#[test]
+	fn start_and_end_do_not_overlap() {
+		assert_eq!(split("A/B", "A/B/A/B"), parts("A/B", "", ""));
+		assert_eq!(split("A/B/A/B", "A/B"), parts("A/B", "A/B", ""));
+		assert_eq!(split("A/A", "A/A/A"), parts("A/A", "", ""));
+		assert_eq!(split("A/A/A", "A/A"), parts("A/A", "A", ""));
+	}
+	
+	#[test]
+	fn paths_without_common_parts() {
+		assert_eq!(split("A/B/x.png", "C/D/y.png"), parts("", "A/B/x.png", ""));
+		assert_eq!(split("x.png", "Assets/y.png"), parts("", "x.png", ""));
+	}
+	
+	#[test]
+	fn middle_differs_in_length() {
+		// Moved into a deeper folder, and back out of it.
+		assert_eq!(split("Assets/x.png", "Assets/Deep/Er/x.png"), parts("Assets", "", "x.png"));
+		assert_eq!(split("Assets/Deep/Er/x.png", "Assets/x.png"), parts("Assets", "Deep/Er", "x.png"));
+		assert_eq!(split("Assets/A/B/x.png", "Assets/C/x.png"), parts("Assets", "A/B", "x.png"));
+	}
+	
+	#[test]
+	fn strip_prefix_keeps_something() {
+		assert_eq!(strip_prefix(Path::new("Assets/A/x.png"), Path::new("Assets/A")), Some(Path::new("x.png")));
+		assert_eq!(strip_prefix(Path::new("Assets/A"), Path::new("Assets/A")), None);
+		assert_eq!(strip_prefix(Path::new("Assets/AB/x.png"), Path::new("Assets/A")), None);
+	}
+	
+	#[test]
+	fn similarity_range() {
+		assert_eq!(similarity(Path::new("A/x.png"), Path::new("A/x.png")), 1.0);
+		assert_eq!(similarity(Path::new("A/x.png"), Path::new("B/y.png")), 0.0);
+		assert_eq!(similarity(Path::new("A/x.png"), Path::new("B/x.png")), 0.5);
+		assert_eq!(similarity(Path::new(""), Path::new("")), 1.0);
+	}
+}
@@ -1,163 +1,1002 @@
+use crate::data::path_diff;
 use crate::data::uuid::Uuid;
-use crate::data::uuid_storage::UuidStorage;
+use crate::data::uuid_storage::{ChangeKind, UuidStorage};
 use ecc_ansi_lib::ansi;
-use std::cmp::min;
+use std::collections::hash_map::Entry;
 use std::collections::HashMap;
-use std::path::Path;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::io::{self, Write};
+use std::path::{Component, Path, PathBuf};
 
-#[derive(Default)]
+// A path which cannot be added to the tree. It is up to the caller to skip it.
+#[derive(Debug)]
+pub enum TreePathError {
+	// Nothing is left after normalization, for example '..meta' (the meta file of '.') or 'A/...meta'.
+	Empty { path: PathBuf },
+	NotUtf8 { path: PathBuf },
+}
+
+impl Display for TreePathError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		match self {
+			TreePathError::Empty { path } => write!(f, "Path '{}' has no elements left after normalization", path.display()),
+			TreePathError::NotUtf8 { path } => write!(f, "Path '{}' is not valid UTF-8", path.display()),
+		}
+	}
+}
+
+impl Error for TreePathError {}
+
+// Nodes nest once per path element. Paths can be thousands of elements deep (even if only by accident or on purpose),
+// thus nothing in here recurses along the nodes - explicit stacks are used instead. This includes dropping the nodes.
 struct TreeNode {
 	uuid: Option<Uuid>,
+	// When this node got created, relative to the other nodes of the tree. Allows printing in the order Git reported the paths.
+	order: usize,
+	// An unchanged meta file next to changes, only shown for orientation. See PathTreeStorage::add_context().
+	is_context: bool,
 	entries: HashMap<String, TreeNode>,
 }
 
+impl Drop for TreeNode {
+	fn drop(&mut self) {
+		drop_iteratively(self);
+	}
+}
+
+// The tree printing only needs to know the children and whether a node represents a change.
+// This allows printing the single direction trees and the unified tree with the same code.
+trait PrintableNode: Sized {
+	fn children(&self) -> &HashMap<String, Self>;
+	
+	fn children_mut(&mut self) -> &mut HashMap<String, Self>;
+	
+	fn has_change(&self) -> bool;
+	
+	// Context nodes are kept like changes, but neither counted nor described.
+	fn is_context(&self) -> bool;
+	
+	fn order(&self) -> usize;
+	
+	// Amount of change-bearing nodes in the subtree below this node (excluding itself).
+	fn count_changes_below(&self) -> usize {
+		let mut count = 0;
+		let mut stack: Vec<_> = self.children().values().collect();
+		while let Some(node) = stack.pop() {
+			if node.has_change() {
+				count += 1;
+			}
+			stack.extend(node.children().values());
+		}
+		count
+	}
+}
+
+impl PrintableNode for TreeNode {
+	fn children(&self) -> &HashMap<String, Self> {
+		&self.entries
+	}
+	
+	fn children_mut(&mut self) -> &mut HashMap<String, Self> {
+		&mut self.entries
+	}
+	
+	fn has_change(&self) -> bool {
+		self.uuid.is_some()
+	}
+	
+	fn is_context(&self) -> bool {
+		self.is_context
+	}
+	
+	fn order(&self) -> usize {
+		self.order
+	}
+}
+
+// Node of the unified tree, which combines the addition and removal tree.
+struct UnifiedTreeNode {
+	added: Option<Uuid>,
+	removed: Option<Uuid>,
+	order: usize,
+	is_context: bool,
+	entries: HashMap<String, UnifiedTreeNode>,
+}
+
+impl Drop for UnifiedTreeNode {
+	fn drop(&mut self) {
+		drop_iteratively(self);
+	}
+}
+
+impl PrintableNode for UnifiedTreeNode {
+	fn children(&self) -> &HashMap<String, Self> {
+		&self.entries
+	}
+	
+	fn children_mut(&mut self) -> &mut HashMap<String, Self> {
+		&mut self.entries
+	}
+	
+	fn has_change(&self) -> bool {
+		self.added.is_some() || self.removed.is_some()
+	}
+	
+	fn is_context(&self) -> bool {
+		self.is_context
+	}
+	
+	fn order(&self) -> usize {
+		self.order
+	}
+}
+
+// Moves all nodes below this one onto a list, so that each of them gets dropped without children.
+fn drop_iteratively<N: PrintableNode>(node: &mut N) {
+	let mut stack: Vec<N> = node.children_mut().drain().map(|(_, child)| child).collect();
+	while let Some(mut child) = stack.pop() {
+		stack.extend(child.children_mut().drain().map(|(_, grandchild)| grandchild));
+	}
+}
+
+// Calls update on every node, then removes the nodes without change (or context) and without children (bottom-up).
+// Nodes are taken out of their parent while their children are processed, and put back afterward. This avoids recursion.
+fn update_and_prune<N: PrintableNode>(root_entries: &mut HashMap<String, N>, update: impl Fn(&mut N)) {
+	struct Frame<N> {
+		// None for the root entries.
+		node: Option<(String, N)>,
+		pending: Vec<(String, N)>,
+		done: HashMap<String, N>,
+	}
+	let mut stack = vec![Frame { node: None, pending: root_entries.drain().collect(), done: HashMap::new() }];
+	while let Some(frame) = stack.last_mut() {
+		if let Some((name, mut node)) = frame.pending.pop() {
+			update(&mut node);
+			let pending = node.children_mut().drain().collect();
+			stack.push(Frame { node: Some((name, node)), pending, done: HashMap::new() });
+			continue;
+		}
+		let frame = stack.pop().unwrap();
+		let Some((name, mut node)) = frame.node else {
+			*root_entries = frame.done;
+			break;
+		};
+		*node.children_mut() = frame.done;
+		if node.has_change() || node.is_context() || !node.children().is_empty() {
+			stack.last_mut().unwrap().done.insert(name, node);
+		}
+	}
+}
+
+// Changes in a subtree, shown next to folders.
+#[derive(Default)]
+#[derive(Copy, Clone)]
+struct ChangeCounts {
+	added: usize,
+	removed: usize,
+	renamed: usize,
+}
+
+impl ChangeCounts {
+	fn of_kind(kind: ChangeKind) -> ChangeCounts {
+		match kind {
+			ChangeKind::Added => ChangeCounts { added: 1, ..ChangeCounts::default() },
+			ChangeKind::Removed => ChangeCounts { removed: 1, ..ChangeCounts::default() },
+			ChangeKind::Renamed => ChangeCounts { renamed: 1, ..ChangeCounts::default() },
+			ChangeKind::Unchanged | ChangeKind::Split => ChangeCounts::default(),
+		}
+	}
+	
+	fn add(&mut self, other: ChangeCounts) {
+		self.added += other.added;
+		self.removed += other.removed;
+		self.renamed += other.renamed;
+	}
+	
+	fn format(&self) -> String {
+		format!(ansi!(" «gr»(«lg»+{}«gr» «lr»-{}«gr» «lb»~{}«gr»)«»"), self.added, self.removed, self.renamed)
+	}
+}
+
+struct PrintStackEntry<'a, N> {
+	path_element: String,
+	node: &'a N,
+	prefix_main: String,
+	prefix_sub: String,
+	full_path: PathBuf,
+	depth: usize,
+}
+
+#[derive(Default)]
+pub struct TreePrintOptions {
+	// Nodes deeper than this (root entries have depth 0) are not printed, but summarized in a single line.
+	pub max_depth: Option<usize>,
+	// Draw the tree lines with ASCII characters, for terminals/logs without Unicode support.
+	pub ascii: bool,
+	// Print the entries of a folder in the order they got added to the tree, instead of alphabetically.
+	pub keep_insertion_order: bool,
+	// Printed as root node above the top-level entries (with the changes of the whole tree), to make clear what the paths are relative to.
+	pub root_label: Option<String>,
+}
+
+// How the path of a GUID changed. Only the last path element differing is a rename, only the folders differing is a move.
+#[derive(Debug)]
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub enum PathChange {
+	Renamed,
+	Moved,
+	MovedAndRenamed,
+	Unchanged,
+}
+
+impl PathChange {
+	pub fn between(path_a: &Path, path_b: &Path) -> PathChange {
+		match (path_a.file_name() == path_b.file_name(), path_a.parent() == path_b.parent()) {
+			(true, true) => PathChange::Unchanged,
+			(false, true) => PathChange::Renamed,
+			(true, false) => PathChange::Moved,
+			(false, false) => PathChange::MovedAndRenamed,
+		}
+	}
+	
+	fn label(self) -> &'static str {
+		match self {
+			PathChange::Renamed => ansi!(" «lb»(renamed)«»"),
+			PathChange::Moved => ansi!(" «y»(moved)«»"),
+			PathChange::MovedAndRenamed => ansi!(" «lr»(moved+renamed)«»"),
+			PathChange::Unchanged => "",
+		}
+	}
+}
+
+struct TreeGlyphs {
+	branch: &'static str,
+	last_branch: &'static str,
+	line: &'static str,
+}
+
+const UNICODE_GLYPHS: TreeGlyphs = TreeGlyphs { branch: "├─", last_branch: "└─", line: "│ " };
+const ASCII_GLYPHS: TreeGlyphs = TreeGlyphs { branch: "+-", last_branch: "`-", line: "| " };
+
+// Two UUIDs for the same path in the same direction. Only the first one is part of the tree.
+pub struct PathConflict {
+	pub path: PathBuf,
+	pub kept: Uuid,
+	pub ignored: Uuid,
+}
+
 #[derive(Default)]
 pub struct PathTreeStorage {
 	root_entries: HashMap<String, TreeNode>,
+	// Amount of nodes created so far, the order of the next node.
+	node_count: usize,
+	conflicts: Vec<PathConflict>,
 }
 
 impl PathTreeStorage {
-	pub fn add_to_tree(&mut self, path: &Path, uuid: Uuid) {
+	// Builds a whole tree at once, for example from a list of known changes. Paths which got two UUIDs end up in conflicts().
+	pub fn from_entries(entries: impl IntoIterator<Item = (PathBuf, Uuid)>) -> Result<PathTreeStorage, TreePathError> {
+		let mut storage = PathTreeStorage::default();
+		for (path, uuid) in entries {
+			storage.add_to_tree(&path, uuid)?;
+		}
+		Ok(storage)
+	}
+	
+	// On error, the tree stays unchanged.
+	pub fn add_to_tree(&mut self, path: &Path, uuid: Uuid) -> Result<(), TreePathError> {
+		let (current_node, path) = Self::create_node(&mut self.root_entries, &mut self.node_count, path)?;
+		
+		// Finally set the UUID. But confirm, that there is not already a UUID for this path.
+		// A path can only be added or removed once per diff, thus this normally never triggers. If it does, keep the first and remember the conflict.
+		if let Some(previous_entry) = current_node.uuid {
+			self.conflicts.push(PathConflict {
+				path,
+				kept: previous_entry,
+				ignored: uuid,
+			});
+			return Ok(());
+		}
+		current_node.uuid = Some(uuid);
+		Ok(())
+	}
+	
+	// Adds an unchanged meta file, which is printed dimmed for orientation. Paths which are already part of the tree stay as they are.
+	// Call this after retain(), as the context of removed changes would stay otherwise.
+	pub fn add_context(&mut self, path: &Path) -> Result<(), TreePathError> {
+		let node_count_before = self.node_count;
+		let (node, _) = Self::create_node(&mut self.root_entries, &mut self.node_count, path)?;
+		// The node of the path itself is created last. If any node got created, it is new.
+		if self.node_count != node_count_before {
+			node.is_context = true;
+		}
+		Ok(())
+	}
+	
+	// Finds or creates the node of the meta file path, and the normalized path of its asset.
+	fn create_node<'a>(root_entries: &'a mut HashMap<String, TreeNode>, node_count: &mut usize, path: &Path) -> Result<(&'a mut TreeNode, PathBuf), TreePathError> {
 		// Remove the extension (".meta") from the path:
 		let mut path = path.to_path_buf();
 		path.set_extension("");
-		
-		let mut path_iterator = path.iter();
+		let Some(elements) = Self::normalized_elements(&path) else {
+			return Err(TreePathError::NotUtf8 { path });
+		};
+		let normalized_path: PathBuf = elements.iter().collect();
 		
 		// Resolve the very first node. This is an explicit step as root cannot have a UUID.
-		let first_element = path_iterator.next().unwrap(); // Caller did ensure that the path is not empty.
-		let mut current_node = self.root_entries.entry(first_element.to_str().unwrap().to_owned()).or_default();
+		let mut path_iterator = elements.into_iter();
+		let Some(first_element) = path_iterator.next() else {
+			return Err(TreePathError::Empty { path });
+		};
+		let mut new_node = || {
+			*node_count += 1;
+			TreeNode { uuid: None, order: *node_count, is_context: false, entries: HashMap::new() }
+		};
+		let mut current_node = root_entries.entry(first_element.to_owned()).or_insert_with(&mut new_node);
 		
-		// Resolve all other nodes for this path. The current_node will then point towards the folder/file which gets a UUID.
+		// Resolve all other nodes for this path. The current_node will then point towards the folder/file of the path.
 		for element in path_iterator {
-			current_node = current_node.entries.entry(element.to_str().unwrap().to_owned()).or_default();
+			current_node = current_node.entries.entry(element.to_owned()).or_insert_with(&mut new_node);
 		}
+		Ok((current_node, normalized_path))
+	}
+	
+	// Path::iter() only splits on the separator of the current platform. But paths written by Windows tooling may contain backslashes.
+	// Thus, split on both - neither is allowed in Unity asset names anyway.
+	// Purely logical: '.' and empty elements are dropped, '..' removes the previous element. The file system is never asked.
+	// None if the path is not valid UTF-8.
+	fn normalized_elements(path: &Path) -> Option<Vec<&str>> {
+		let mut elements = Vec::new();
+		for element in path.to_str()?.split(['/', '\\']) {
+			match element {
+				"" | "." => {}
+				// There is nothing above the repository root, thus a leading '..' is dropped as well.
+				".." => {
+					elements.pop();
+				}
+				_ => elements.push(element),
+			}
+		}
+		Some(elements)
+	}
+	
+	// Removes the UUIDs for which keep returns false. Folders left without any UUID below them are removed too.
+	pub fn retain(&mut self, keep: impl Fn(Uuid) -> bool) {
+		update_and_prune(&mut self.root_entries, |node| node.uuid = node.uuid.filter(|uuid| keep(*uuid)));
+	}
+	
+	pub fn conflicts(&self) -> &[PathConflict] {
+		&self.conflicts
+	}
+	
+	// Moves everything inside the prefix folder up to the root, for shorter paths. Nodes outside the prefix stay where they are.
+	// Also stays: A node named like an existing root entry (merging them would mix up two different paths).
+	pub fn strip_prefix(&mut self, prefix: &Path) {
+		let Some(elements) = Self::normalized_elements(prefix) else {
+			return;
+		};
+		let Some(prefix_node) = Self::find_node_mut(&mut self.root_entries, &elements) else {
+			return;
+		};
+		let moved_entries = std::mem::take(&mut prefix_node.entries);
+		let mut kept_entries = HashMap::new();
+		for (name, node) in moved_entries {
+			match self.root_entries.entry(name) {
+				Entry::Occupied(existing) => {
+					kept_entries.insert(existing.key().clone(), node);
+				}
+				Entry::Vacant(free) => {
+					free.insert(node);
+				}
+			}
+		}
+		if let Some(prefix_node) = Self::find_node_mut(&mut self.root_entries, &elements) {
+			prefix_node.entries = kept_entries;
+		}
+		// The folders of the prefix are left without content, unless they had other content or a UUID of their own:
+		self.retain(|_| true);
+		for conflict in &mut self.conflicts {
+			if let Some(rest) = path_diff::strip_prefix(&conflict.path, prefix) {
+				conflict.path = rest.to_path_buf();
+			}
+		}
+	}
+	
+	fn find_node_mut<'a>(root_entries: &'a mut HashMap<String, TreeNode>, elements: &[&str]) -> Option<&'a mut TreeNode> {
+		let (first_element, other_elements) = elements.split_first()?;
+		let mut current_node = root_entries.get_mut(*first_element)?;
+		for element in other_elements {
+			current_node = current_node.entries.get_mut(*element)?;
+		}
+		Some(current_node)
+	}
+	
+	pub fn debug_print(&self, uuid_storage: &UuidStorage, is_adding: bool, options: &TreePrintOptions) {
+		self.write_tree(&mut io::stdout().lock(), uuid_storage, is_adding, options).expect("Failed to write to stdout.");
+	}
+	
+	pub fn write_tree(&self, out: &mut dyn Write, uuid_storage: &UuidStorage, is_adding: bool, options: &TreePrintOptions) -> io::Result<()> {
+		let count = |node: &TreeNode| node.uuid.map_or_else(ChangeCounts::default, |uuid| ChangeCounts::of_kind(uuid_storage.lookup.get(&uuid).unwrap().classify().side(is_adding)));
+		Self::write_nodes(out, &self.root_entries, options, count, |node, full_path| {
+			// Construct a suffix fitting details to this folder entry:
+			let Some(uuid) = node.uuid else {
+				// No UUID for this folder, thus no means to add details.
+				return String::new();
+			};
+			let storage_entry = uuid_storage.lookup.get(&uuid).unwrap();
+			// The addition tree only contains entries with added paths and the removal tree only entries with removed paths.
+			// Thus, only renames have to look up the counterpart paths - the path of this node is the other one.
+			let description = match storage_entry.classify().side(is_adding) {
+				ChangeKind::Renamed if is_adding => Self::highlight_counterparts("<=", &storage_entry.removed, full_path),
+				ChangeKind::Renamed => Self::highlight_counterparts("=>", &storage_entry.added, full_path),
+				ChangeKind::Added => format!(ansi!(" «lg»ADDED«» {}"), uuid),
+				ChangeKind::Removed => format!(ansi!(" «lr»REMOVED«» {}"), uuid),
+				ChangeKind::Unchanged | ChangeKind::Split => format!(ansi!(" «gr»UNCHANGED«» {}"), uuid),
+			};
+			Self::with_folder_label(description, storage_entry.is_folder)
+		})
+	}
+	
+	// Prints the addition and removal tree as one tree. Renames are only shown once, at their new path.
+	pub fn write_unified_tree(
+		out: &mut dyn Write, uuid_storage: &UuidStorage,
+		addition_tree: &PathTreeStorage, removal_tree: &PathTreeStorage,
+		options: &TreePrintOptions,
+	) -> io::Result<()> {
+		// Nodes of the removal tree come after the nodes of the addition tree (when keeping the insertion order), thus their order is offset.
+		// Like update_and_prune(), target nodes are taken out of their parent while their children are merged.
+		fn merge(
+			root_entries: &mut HashMap<String, UnifiedTreeNode>, source: &HashMap<String, TreeNode>,
+			uuid_storage: &UuidStorage, is_adding: bool, order_offset: usize,
+		) {
+			struct Frame<'a> {
+				// None for the root entries.
+				name: Option<String>,
+				target: HashMap<String, UnifiedTreeNode>,
+				// The target node of this frame, without its children (these are in 'target').
+				node: Option<UnifiedTreeNode>,
+				pending: Vec<(&'a String, &'a TreeNode)>,
+			}
+			let mut stack = vec![Frame { name: None, target: std::mem::take(root_entries), node: None, pending: source.iter().collect() }];
+			while let Some(frame) = stack.last_mut() {
+				if let Some((path_element, source_node)) = frame.pending.pop() {
+					let mut target_node = frame.target.remove(path_element).unwrap_or_else(|| UnifiedTreeNode {
+						added: None,
+						removed: None,
+						order: order_offset + source_node.order,
+						is_context: false,
+						entries: HashMap::new(),
+					});
+					target_node.is_context |= source_node.is_context;
+					if let Some(uuid) = source_node.uuid {
+						if is_adding {
+							target_node.added = Some(uuid);
+						} else if uuid_storage.lookup.get(&uuid).unwrap().classify() != ChangeKind::Renamed {
+							target_node.removed = Some(uuid);
+						}
+					}
+					let target = std::mem::take(&mut target_node.entries);
+					stack.push(Frame { name: Some(path_element.clone()), target, node: Some(target_node), pending: source_node.entries.iter().collect() });
+					continue;
+				}
+				let frame = stack.pop().unwrap();
+				let (Some(name), Some(mut node)) = (frame.name, frame.node) else {
+					*root_entries = frame.target;
+					break;
+				};
+				node.entries = frame.target;
+				stack.last_mut().unwrap().target.insert(name, node);
+			}
+		}
+		let mut root_entries = HashMap::new();
+		merge(&mut root_entries, &addition_tree.root_entries, uuid_storage, true, 0);
+		merge(&mut root_entries, &removal_tree.root_entries, uuid_storage, false, addition_tree.node_count);
+		// The old paths of renames leave empty folders behind, remove them.
+		update_and_prune(&mut root_entries, |_| {});
 		
-		// Finally set the UUID. But confirm, that there is not already a UUID for this path.
-		// On the other side, the UUID Storage already checks for this issue - thus this should never trigger.
-		if let Some(previous_entry) = current_node.uuid {
-			// TODO: Find a better way to gracefully handle this case. For now assume that developers used their Git responsibly and did not mess up...
-			panic!("For path '{}' two UUIDs got added or removed ({previous_entry} & {uuid})- normally a gUid is supposed to be UNIQUE (to a single path).", path.display())
+		let count = |node: &UnifiedTreeNode| {
+			let mut counts = ChangeCounts::default();
+			// Removals of renames had not been merged into the tree, thus every removal is a pure removal.
+			if node.removed.is_some() {
+				counts.add(ChangeCounts::of_kind(ChangeKind::Removed));
+			}
+			if let Some(uuid) = node.added {
+				counts.add(ChangeCounts::of_kind(uuid_storage.lookup.get(&uuid).unwrap().classify().side(true)));
+			}
+			counts
+		};
+		Self::write_nodes(out, &root_entries, options, count, |node, full_path| {
+			let mut suffix = String::new();
+			// A path can have a removed and an added UUID at the same time - when the asset got replaced.
+			if let Some(uuid) = node.removed {
+				let storage_entry = uuid_storage.lookup.get(&uuid).unwrap();
+				suffix.push_str(&Self::with_folder_label(format!(ansi!(" «lr»REMOVED«» {}"), uuid), storage_entry.is_folder));
+			}
+			if let Some(uuid) = node.added {
+				let storage_entry = uuid_storage.lookup.get(&uuid).unwrap();
+				let description = match storage_entry.classify() {
+					ChangeKind::Renamed => format!(ansi!(" «lb»RENAMED«»{}"), Self::highlight_counterparts("<=", &storage_entry.removed, full_path)),
+					ChangeKind::Unchanged => format!(ansi!(" «gr»UNCHANGED«» {}"), uuid),
+					_ => format!(ansi!(" «lg»ADDED«» {}"), uuid),
+				};
+				suffix.push_str(&Self::with_folder_label(description, storage_entry.is_folder));
+			}
+			suffix
+		})
+	}
+	
+	// Folder assets stand out, as their changes affect everything inside.
+	fn with_folder_label(description: String, is_folder: bool) -> String {
+		if is_folder {
+			description + ansi!(" «y»[folder]«»")
+		} else {
+			description
 		}
-		current_node.uuid = Some(uuid);
 	}
 	
-	pub fn debug_print(&self, uuid_storage: &UuidStorage, is_adding: bool) {
-		fn add_flipped<'a>(stack: &mut Vec<(String, &'a TreeNode, String, String)>, map: &'a HashMap<String, TreeNode>, prefix: String){
+	// Normally there is only a single counterpart path, but if a GUID got used multiple times, all are listed.
+	fn highlight_counterparts(arrow: &str, counterpart_paths: &[PathBuf], full_path: &Path) -> String {
+		let highlighted: Vec<_> = counterpart_paths.iter()
+			.map(|counterpart_path| {
+				let (highlighted_path, change) = Self::highlight_path_change(counterpart_path, full_path);
+				format!("'{highlighted_path}'{}", change.label())
+			})
+			.collect();
+		format!(" {arrow} {}", highlighted.join(", "))
+	}
+	
+	fn write_nodes<N: PrintableNode>(
+		out: &mut dyn Write, root_entries: &HashMap<String, N>, options: &TreePrintOptions,
+		count: impl Fn(&N) -> ChangeCounts,
+		describe: impl Fn(&N, &Path) -> String,
+	) -> io::Result<()> {
+		fn count_below<N: PrintableNode>(node: &N, count: &impl Fn(&N) -> ChangeCounts) -> ChangeCounts {
+			let mut counts = ChangeCounts::default();
+			let mut stack: Vec<_> = node.children().values().collect();
+			while let Some(child) = stack.pop() {
+				counts.add(count(child));
+				stack.extend(child.children().values());
+			}
+			counts
+		}
+		fn add_flipped<'a, N: PrintableNode>(
+			stack: &mut Vec<PrintStackEntry<'a, N>>, glyphs: &TreeGlyphs, keep_insertion_order: bool,
+			map: &'a HashMap<String, N>, prefix: String, parent_path: &Path, depth: usize,
+		) {
 			let mut list : Vec<_> = map.iter().collect();
 			// HashMaps are ordered with a random seed - sort to ensure consistent output order.
-			list.sort_by_key(|(path, _)| *path);
+			if keep_insertion_order {
+				list.sort_by_key(|(_, node)| node.order());
+			} else {
+				list.sort_by_key(|(path, _)| *path);
+			}
 			
 			// Collect all folders. Given that the folders had been sorted before (gitlib2 ordering), they are reversely added.
 			// This ensures that the first one gets added on the stack last - so that it gets popped first.
-			stack.extend(list.into_iter().enumerate().rev().map(|(index, (path, node))| (
-				path.to_owned(),
+			stack.extend(list.into_iter().enumerate().rev().map(|(index, (path, node))| PrintStackEntry {
+				path_element: path.to_owned(),
 				node,
 				// Tree-building magic. There is a main-prefix for the first child-node line.
 				// And a sub-prefix to prefix all lines of grand-child-nodes.
-				if index == map.len() - 1 { format!("{prefix}└─") } else { format!("{prefix}├─") },
-				if index == map.len() - 1 { format!("{prefix}  ") } else { format!("{prefix}│ ") },
-			)));
+				prefix_main: if index == map.len() - 1 { format!("{prefix}{}", glyphs.last_branch) } else { format!("{prefix}{}", glyphs.branch) },
+				prefix_sub: if index == map.len() - 1 { format!("{prefix}  ") } else { format!("{prefix}{}", glyphs.line) },
+				full_path: parent_path.join(path),
+				depth,
+			}));
 		}
 		
+		let glyphs = if options.ascii { &ASCII_GLYPHS } else { &UNICODE_GLYPHS };
+		// The top-level entries are drawn with branch glyphs anyway, thus they line up below a root line without extra prefix.
+		if let Some(root_label) = options.root_label.as_ref().filter(|_| !root_entries.is_empty()) {
+			let mut counts = ChangeCounts::default();
+			for node in root_entries.values() {
+				counts.add(count(node));
+				counts.add(count_below(node, &count));
+			}
+			writeln!(out, ansi!("«w»{}«»:{}"), root_label, counts.format())?;
+		}
 		let mut stack = Vec::new();
 		// Add root level entries:
-		add_flipped(&mut stack, &self.root_entries, "".to_owned());
+		add_flipped(&mut stack, glyphs, options.keep_insertion_order, root_entries, "".to_owned(), Path::new(""), 0);
 		
-		while let Some((path_element, node, prefix_main, prefix_sub)) = stack.pop() {
-			// Construct a suffix fitting details to this folder entry:
-			let suffix = if let Some(uuid) = node.uuid {
-				let storage_entry = uuid_storage.lookup.get(&uuid).unwrap();
-				// SAFETY: The following code gets added/removed reference - if it is set it also takes the other reference.
-				// This is not an issue - by code design:
-				// When going over the addition tree paths - we know when a UUID exists there must exist a UUID-Addition path entry in the UUID-Storage.
-				// Thus, one only has to check if a removal exists - an addition always exists. The same applies for the removal tree.
-				if is_adding {
-					let optional_primary_path = &storage_entry.removed;
-					if let Some(primary_path) = optional_primary_path {
-						let secondary_path = storage_entry.added.as_ref().unwrap(); // See safety comment.
-						&format!(" <= '{}'", Self::highlight_path_change(primary_path, secondary_path))
-					} else {
-						&format!(ansi!(" «lg»ADDED«» {}"), uuid)
-					}
-				} else {
-					let optional_primary_path = &storage_entry.added;
-					if let Some(primary_path) = optional_primary_path {
-						let secondary_path = storage_entry.removed.as_ref().unwrap(); // See safety comment.
-						&format!(" => '{}'", Self::highlight_path_change(primary_path, secondary_path))
-					} else {
-						&format!(ansi!(" «lr»REMOVED«» {}"), uuid)
-					}
-				}
-			} else {
-				// No UUID for this folder, thus no means to add details.
-				""
-			};
-			println!(ansi!("{}«w»{}«»:{}"), prefix_main, path_element, suffix);
+		while let Some(PrintStackEntry { path_element, node, prefix_main, prefix_sub, full_path, depth }) = stack.pop() {
+			if node.is_context() && node.children().is_empty() {
+				writeln!(out, ansi!("{}«gr»{}«»"), prefix_main, path_element)?;
+				continue;
+			}
+			if node.children().is_empty() {
+				writeln!(out, ansi!("{}«w»{}«»:{}"), prefix_main, path_element, describe(node, &full_path))?;
+				continue;
+			}
+			// Folders get a summary of everything inside:
+			writeln!(out, ansi!("{}«w»{}«»:{}{}"), prefix_main, path_element, describe(node, &full_path), count_below(node, &count).format())?;
+			if options.max_depth.is_some_and(|max_depth| depth >= max_depth) {
+				// Too deep, summarize the whole subtree instead of printing it:
+				writeln!(out, ansi!("{}{}«gr»... ({} more entries)«»"), prefix_sub, glyphs.last_branch, node.count_changes_below())?;
+				continue;
+			}
 			// Add child folders for this folder:
-			add_flipped(&mut stack, &node.entries, prefix_sub);
+			add_flipped(&mut stack, glyphs, options.keep_insertion_order, node.children(), prefix_sub, &full_path, depth + 1);
 		}
+		Ok(())
 	}
 	
-	fn highlight_path_change(main_path: &Path, reference_path: &Path) -> String {
-		// Get the length of the smaller path, to later when looping over paths never run out-of-bounds.
-		let min_part_count = min(
-			main_path.iter().count(),
-			reference_path.iter().count(),
-		);
-		// The idea is to find detect matching start/end paths.
-		
-		// Count matching starting & ending path elements:
-		fn count_same_parts<T: Iterator>(max_iteration: usize, mut iterator_a: T, mut iterator_b: T) -> usize
-			where <T as Iterator>::Item: PartialEq {
-			let mut count = 0;
-			for _ in  0..max_iteration {
-				if iterator_a.next().unwrap() == iterator_b.next().unwrap() {
-					count += 1;
-				} else {
-					break;
-				}
+	// Returns the main path with the parts differing from the reference path highlighted, and what kind of change it is.
+	fn highlight_path_change(main_path: &Path, reference_path: &Path) -> (String, PathChange) {
+		let (start, center, end) = path_diff::split_common(main_path, reference_path);
+		let push_parts = |output: &mut String, color: &str, parts: &[Component]| {
+			for part in parts {
+				output.push_str(color);
+				output.push_str(&part.as_os_str().to_string_lossy());
+				output.push_str(ansi!("«w»/"));
 			}
-			count
-		}
-		let start_index = count_same_parts(min_part_count, &mut main_path.iter(), &mut reference_path.iter());
-		let end_index = count_same_parts(min_part_count, &mut main_path.iter().rev(), &mut reference_path.iter().rev());
-		
-		let center_parts = main_path.iter().count() as isize - start_index as isize - end_index as isize;
-		if center_parts < 0 {
-			unreachable!("Apparently something is wrong with the code to highlight path differences. Managed to ");
-		}
+		};
 		
 		let mut output = String::new();
-		let mut main_iter = main_path.iter();
-		
 		// Print the prefix path parts:
-		for _ in 0..start_index {
-			output.push_str(ansi!("«gr»"));
-			output.push_str(main_iter.next().unwrap().to_str().unwrap());
-			output.push_str(ansi!("«w»/"));
-		}
+		push_parts(&mut output, ansi!("«gr»"), &start);
 		// Print the non-matching center parts in blue:
-		for _ in 0..center_parts {
-			output.push_str(ansi!("«lb»"));
-			output.push_str(main_iter.next().unwrap().to_str().unwrap());
-			output.push_str(ansi!("«w»/"));
-		}
+		push_parts(&mut output, ansi!("«lb»"), &center);
 		// If there is no center part, highlight the separating / between pre/suffix:
-		if center_parts == 0 && !output.is_empty() {
+		if center.is_empty() && !output.is_empty() {
 			output.pop().unwrap();
 			output.push_str(ansi!("«lb»/"));
 		}
 		// Print the suffix path parts:
-		for _ in 0..end_index {
-			output.push_str(ansi!("«gr»"));
-			output.push_str(main_iter.next().unwrap().to_str().unwrap());
-			output.push_str(ansi!("«w»/"));
-		}
-		// Remove the trailing / from the path:
-		output.pop().unwrap();
+		push_parts(&mut output, ansi!("«gr»"), &end);
+		// Remove the trailing / from the path (if there is any path at all):
+		output.pop();
 		output.push_str(ansi!("«»"));
 		
-		output
+		(output, PathChange::between(main_path, reference_path))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	use crate::data::uuid::test_helpers::{uuid, written};
+	use crate::output::color::AnsiStripper;
+	
+	// All paths are added, the GUIDs are numbered in order.
+	fn addition_tree(paths: &[&str]) -> (PathTreeStorage, UuidStorage) {
+		let mut tree = PathTreeStorage::default();
+		let mut storage = UuidStorage::default();
+		for (index, path) in paths.iter().enumerate() {
+			let uuid = uuid(char::from_digit(index as u32 + 1, 16).unwrap());
+			tree.add_to_tree(Path::new(path), uuid).unwrap();
+			storage.added(uuid, PathBuf::from(path));
+		}
+		(tree, storage)
+	}
+	
+	fn written_tree(tree: &PathTreeStorage, storage: &UuidStorage, options: &TreePrintOptions) -> String {
+		written(|out| tree.write_tree(out, storage, true, options))
+	}
+	
+	const DEEP_PATHS: [&str; 3] = ["Assets/A/B/x.png.meta", "Assets/A/B/y.png.meta", "Assets/A/z.png.meta"];
+	
+	#[test]
+	fn count_changes_below_covers_the_whole_subtree() {
+		let (tree, _) = addition_tree(&DEEP_PATHS);
+		let assets = &tree.root_entries["Assets"];
+		assert_eq!(assets.count_changes_below(), 3);
+		assert_eq!(assets.entries["A"].entries["B"].count_changes_below(), 2);
+		assert_eq!(assets.entries["A"].entries["z.png"].count_changes_below(), 0);
+	}
+	
+	#[test]
+	fn tree_is_written_to_a_buffer() {
+		let (tree, storage) = addition_tree(&["A/x.png.meta"]);
+		let mut output = Vec::new();
+		tree.write_tree(&mut output, &storage, true, &TreePrintOptions::default()).unwrap();
+		let expected = [
+			ansi!("└─«w»A«»: «gr»(«lg»+1«gr» «lr»-0«gr» «lb»~0«gr»)«»\n"),
+			ansi!("  └─«w»x.png«»: «lg»ADDED«» 11111111111111111111111111111111\n"),
+		].concat();
+		assert_eq!(output, expected.as_bytes());
+	}
+	
+	#[test]
+	fn max_depth_zero_summarizes_below_the_root_entries() {
+		let (tree, storage) = addition_tree(&DEEP_PATHS);
+		let options = TreePrintOptions { max_depth: Some(0), ..TreePrintOptions::default() };
+		assert_eq!(written_tree(&tree, &storage, &options), "└─Assets: (+3 -0 ~0)\n  └─... (3 more entries)\n");
+	}
+	
+	#[test]
+	fn max_depth_cuts_off_in_the_middle() {
+		// Files above the cutoff are still printed, only the folder 'B' gets summarized.
+		let (tree, storage) = addition_tree(&DEEP_PATHS);
+		let options = TreePrintOptions { max_depth: Some(2), ..TreePrintOptions::default() };
+		assert_eq!(written_tree(&tree, &storage, &options), format!(
+			"└─Assets: (+3 -0 ~0)\n  └─A: (+3 -0 ~0)\n    ├─B: (+2 -0 ~0)\n    │ └─... (2 more entries)\n    └─z.png: ADDED {}\n",
+			uuid('3'),
+		));
+	}
+	
+	#[test]
+	fn without_max_depth_everything_is_printed() {
+		let (tree, storage) = addition_tree(&DEEP_PATHS);
+		assert_eq!(written_tree(&tree, &storage, &TreePrintOptions::default()), format!(
+			"└─Assets: (+3 -0 ~0)\n  └─A: (+3 -0 ~0)\n    ├─B: (+2 -0 ~0)\n    │ ├─x.png: ADDED {}\n    │ └─y.png: ADDED {}\n    └─z.png: ADDED {}\n",
+			uuid('1'), uuid('2'), uuid('3'),
+		));
+	}
+	
+	#[test]
+	fn tree_without_color_has_no_escape_sequences() {
+		let (tree, storage) = addition_tree(&DEEP_PATHS);
+		let mut output = Vec::new();
+		tree.write_tree(&mut crate::output::color::writer(&mut output, false), &storage, true, &TreePrintOptions::default()).unwrap();
+		assert!(!output.is_empty());
+		assert!(!output.contains(&0x1b));
+	}
+	
+	#[test]
+	fn ascii_tree_has_only_ascii_bytes() {
+		let (tree, storage) = addition_tree(&DEEP_PATHS);
+		let options = TreePrintOptions { ascii: true, ..TreePrintOptions::default() };
+		let written = written_tree(&tree, &storage, &options);
+		assert!(written.is_ascii(), "{written}");
+		assert_eq!(written, format!(
+			"`-Assets: (+3 -0 ~0)\n  `-A: (+3 -0 ~0)\n    +-B: (+2 -0 ~0)\n    | +-x.png: ADDED {}\n    | `-y.png: ADDED {}\n    `-z.png: ADDED {}\n",
+			uuid('1'), uuid('2'), uuid('3'),
+		));
+		// The default still uses box-drawing characters.
+		assert!(!written_tree(&tree, &storage, &TreePrintOptions::default()).is_ascii());
+	}
+	
+	fn written_unified_tree(additions: &[(&str, char)], removals: &[(&str, char)]) -> String {
+		let mut storage = UuidStorage::default();
+		let mut addition_tree = PathTreeStorage::default();
+		let mut removal_tree = PathTreeStorage::default();
+		for &(path, digit) in additions {
+			addition_tree.add_to_tree(Path::new(path), uuid(digit)).unwrap();
+			storage.added(uuid(digit), PathBuf::from(path));
+		}
+		for &(path, digit) in removals {
+			removal_tree.add_to_tree(Path::new(path), uuid(digit)).unwrap();
+			storage.removed(uuid(digit), PathBuf::from(path));
+		}
+		written(|out| PathTreeStorage::write_unified_tree(
+			out, &storage, &addition_tree, &removal_tree, &TreePrintOptions::default(),
+		))
+	}
+	
+	#[test]
+	fn unified_tree_shows_a_rename_once() {
+		let written = written_unified_tree(
+			&[("Assets/New/x.png.meta", '1'), ("Assets/y.png.meta", '2')],
+			&[("Assets/Old/x.png.meta", '1'), ("Assets/z.png.meta", '3')],
+		);
+		// Both endpoints are on a single line, the old folder is not part of the tree as it only held the renamed file.
+		assert_eq!(written, format!(
+			"└─Assets: (+1 -1 ~1)\n  ├─New: (+0 -0 ~1)\n  │ └─x.png: RENAMED <= 'Assets/Old/x.png' (moved)\n  ├─y.png: ADDED {}\n  └─z.png: REMOVED {}\n",
+			uuid('2'), uuid('3'),
+		));
+	}
+	
+	#[test]
+	fn unified_tree_shows_a_replaced_asset_on_one_line() {
+		let written = written_unified_tree(&[("Assets/x.png.meta", '1')], &[("Assets/x.png.meta", '2')]);
+		assert_eq!(written, format!(
+			"└─Assets: (+1 -1 ~0)\n  └─x.png: REMOVED {} ADDED {}\n",
+			uuid('2'), uuid('1'),
+		));
+	}
+	
+	#[test]
+	fn second_uuid_for_a_path_is_a_conflict() {
+		let (mut tree, mut storage) = addition_tree(&["Assets/x.png.meta"]);
+		tree.add_to_tree(Path::new("Assets/x.png.meta"), uuid('2')).unwrap();
+		storage.added(uuid('2'), PathBuf::from("Assets/x.png.meta"));
+		
+		let conflicts = tree.conflicts();
+		assert_eq!(conflicts.len(), 1);
+		assert_eq!(conflicts[0].path, PathBuf::from("Assets/x.png"));
+		assert_eq!((conflicts[0].kept, conflicts[0].ignored), (uuid('1'), uuid('2')));
+		// The tree only shows the first one.
+		assert_eq!(written_tree(&tree, &storage, &TreePrintOptions::default()), format!("└─Assets: (+1 -0 ~0)\n  └─x.png: ADDED {}\n", uuid('1')));
+	}
+	
+	#[test]
+	fn path_change_shapes() {
+		let between = |a: &str, b: &str| PathChange::between(Path::new(a), Path::new(b));
+		assert_eq!(between("Assets/A/x.png", "Assets/A/y.png"), PathChange::Renamed);
+		assert_eq!(between("Assets/A/x.png", "Assets/B/x.png"), PathChange::Moved);
+		assert_eq!(between("Assets/A/x.png", "Assets/B/y.png"), PathChange::MovedAndRenamed);
+		assert_eq!(between("Assets/A/x.png", "Assets/A/x.png"), PathChange::Unchanged);
+		// Moving into a deeper folder changes the parent too.
+		assert_eq!(between("Assets/x.png", "Assets/A/B/x.png"), PathChange::Moved);
+	}
+	
+	#[test]
+	fn highlighted_counterpart_has_the_change_label() {
+		let highlighted = |main: &str, reference: &str| {
+			let (path, change) = PathTreeStorage::highlight_path_change(Path::new(main), Path::new(reference));
+			let mut stripped = Vec::new();
+			write!(AnsiStripper::new(&mut stripped), "{path}{}", change.label()).unwrap();
+			String::from_utf8(stripped).unwrap()
+		};
+		assert_eq!(highlighted("Assets/A/x.png", "Assets/A/y.png"), "Assets/A/x.png (renamed)");
+		assert_eq!(highlighted("Assets/A/x.png", "Assets/B/x.png"), "Assets/A/x.png (moved)");
+		assert_eq!(highlighted("Assets/A/x.png", "Assets/B/y.png"), "Assets/A/x.png (moved+renamed)");
+		// Only the differing part is highlighted.
+		let (path, _) = PathTreeStorage::highlight_path_change(Path::new("Assets/A/x.png"), Path::new("Assets/B/x.png"));
+		assert!(path.starts_with(ansi!("«gr»Assets«w»/«lb»A«w»/«gr»x.png")), "{path:?}");
+	}
+	
+	#[test]
+	fn rename_labels_in_the_tree() {
+		let mut storage = UuidStorage::default();
+		let mut tree = PathTreeStorage::default();
+		for (digit, old, new) in [('1', "A/x.png.meta", "A/y.png.meta"), ('2', "A/z.png.meta", "B/z.png.meta"), ('3', "A/w.png.meta", "B/v.png.meta")] {
+			storage.removed(uuid(digit), PathBuf::from(old));
+			storage.added(uuid(digit), PathBuf::from(new));
+			tree.add_to_tree(Path::new(new), uuid(digit)).unwrap();
+		}
+		assert_eq!(written_tree(&tree, &storage, &TreePrintOptions::default()), concat!(
+			"├─A: (+0 -0 ~1)\n│ └─y.png: <= 'A/x.png' (renamed)\n",
+			"└─B: (+0 -0 ~2)\n  ├─v.png: <= 'A/w.png' (moved+renamed)\n  └─z.png: <= 'A/z.png' (moved)\n",
+		));
+	}
+	
+	#[test]
+	fn backslashes_separate_path_elements() {
+		let (tree, storage) = addition_tree(&["Assets\\A\\x.png.meta", "Assets/A\\y.png.meta"]);
+		assert_eq!(written_tree(&tree, &storage, &TreePrintOptions::default()), format!(
+			"└─Assets: (+2 -0 ~0)\n  └─A: (+2 -0 ~0)\n    ├─x.png: ADDED {}\n    └─y.png: ADDED {}\n",
+			uuid('1'), uuid('2'),
+		));
+	}
+	
+	#[test]
+	fn backslash_path_ends_up_at_the_same_node() {
+		let (mut tree, _) = addition_tree(&["Assets/A/x.png.meta"]);
+		tree.add_to_tree(Path::new("Assets\\A\\x.png.meta"), uuid('2')).unwrap();
+		assert_eq!(tree.conflicts().len(), 1);
+		assert_eq!(tree.conflicts()[0].path, PathBuf::from("Assets/A/x.png"));
+	}
+	
+	#[test]
+	fn paths_are_normalized() {
+		let (tree, storage) = addition_tree(&["./Assets//A/../B/./x.png.meta", "../Assets/B/y.png.meta"]);
+		assert_eq!(written_tree(&tree, &storage, &TreePrintOptions::default()), format!(
+			"└─Assets: (+2 -0 ~0)\n  └─B: (+2 -0 ~0)\n    ├─x.png: ADDED {}\n    └─y.png: ADDED {}\n",
+			uuid('1'), uuid('2'),
+		));
+	}
+	
+	#[test]
+	fn folder_counts_aggregate_the_subtree() {
+		let mut storage = UuidStorage::default();
+		let mut addition_tree = PathTreeStorage::default();
+		let mut removal_tree = PathTreeStorage::default();
+		let mut add = |digit: char, path: &str| {
+			storage.added(uuid(digit), PathBuf::from(path));
+			addition_tree.add_to_tree(Path::new(path), uuid(digit)).unwrap();
+		};
+		add('1', "A/B/C/x.png.meta");
+		add('2', "A/B/y.png.meta");
+		add('3', "A/B/C/moved.png.meta");
+		add('4', "A/z.png.meta");
+		let mut remove = |digit: char, path: &str| {
+			storage.removed(uuid(digit), PathBuf::from(path));
+			removal_tree.add_to_tree(Path::new(path), uuid(digit)).unwrap();
+		};
+		remove('3', "A/moved.png.meta");
+		remove('5', "A/B/gone.png.meta");
+		remove('6', "D/gone.png.meta");
+		
+		let added = written_tree(&addition_tree, &storage, &TreePrintOptions::default());
+		let counts: Vec<_> = added.lines().filter(|line| line.contains(": (+")).collect();
+		assert_eq!(counts, vec![
+			"└─A: (+3 -0 ~1)",
+			"  ├─B: (+2 -0 ~1)",
+			"  │ ├─C: (+1 -0 ~1)",
+		]);
+		let mut removed = Vec::new();
+		removal_tree.write_tree(&mut AnsiStripper::new(&mut removed), &storage, false, &TreePrintOptions::default()).unwrap();
+		let removed = String::from_utf8(removed).unwrap();
+		let counts: Vec<_> = removed.lines().filter(|line| line.contains(": (+")).collect();
+		assert_eq!(counts, vec!["├─A: (+0 -1 ~1)", "│ ├─B: (+0 -1 ~0)", "└─D: (+0 -1 ~0)"]);
+	}
+	
+	#[test]
+	fn dot_elements_are_resolved_without_the_file_system() {
+		let (mut tree, storage) = addition_tree(&["Assets/./Foo/../Bar/baz.meta"]);
+		assert_eq!(written_tree(&tree, &storage, &TreePrintOptions::default()), format!(
+			"└─Assets: (+1 -0 ~0)\n  └─Bar: (+1 -0 ~0)\n    └─baz: ADDED {}\n",
+			uuid('1'),
+		));
+		// The same node, thus a second GUID conflicts - and the conflict has the normalized path.
+		tree.add_to_tree(Path::new("Assets/Bar/baz.meta"), uuid('2')).unwrap();
+		assert_eq!(tree.conflicts()[0].path, PathBuf::from("Assets/Bar/baz"));
+	}
+	
+	#[test]
+	fn tree_from_entries_keeps_conflicts() {
+		let entries = vec![
+			(PathBuf::from("Assets/B/y.png.meta"), uuid('2')),
+			(PathBuf::from("Assets/A/x.png.meta"), uuid('1')),
+			(PathBuf::from("Assets/A/x.png.meta"), uuid('3')),
+		];
+		let tree = PathTreeStorage::from_entries(entries.clone()).unwrap();
+		let mut storage = UuidStorage::default();
+		for (path, uuid) in entries {
+			storage.added(uuid, path);
+		}
+		assert_eq!(written_tree(&tree, &storage, &TreePrintOptions::default()), format!(
+			"└─Assets: (+2 -0 ~0)\n  ├─A: (+1 -0 ~0)\n  │ └─x.png: ADDED {}\n  └─B: (+1 -0 ~0)\n    └─y.png: ADDED {}\n",
+			uuid('1'), uuid('2'),
+		));
+		assert_eq!(tree.conflicts().len(), 1);
+		assert_eq!((tree.conflicts()[0].kept, tree.conflicts()[0].ignored), (uuid('1'), uuid('3')));
+	}
+	
+	#[test]
+	fn tree_from_entries_stops_at_an_invalid_path() {
+		let entries = [(PathBuf::from("Assets/x.png.meta"), uuid('1')), (PathBuf::from("..meta"), uuid('2'))];
+		assert!(matches!(PathTreeStorage::from_entries(entries), Err(TreePathError::Empty { .. })));
+	}
+	
+	#[test]
+	fn tree_in_insertion_order() {
+		let (tree, storage) = addition_tree(&["Assets/Zebra/z.png.meta", "Assets/Apple.png.meta", "Assets/Zebra/a.png.meta"]);
+		assert_eq!(written_tree(&tree, &storage, &TreePrintOptions::default()), format!(
+			"└─Assets: (+3 -0 ~0)\n  ├─Apple.png: ADDED {1}\n  └─Zebra: (+2 -0 ~0)\n    ├─a.png: ADDED {2}\n    └─z.png: ADDED {0}\n",
+			uuid('1'), uuid('2'), uuid('3'),
+		));
+		let options = TreePrintOptions { keep_insertion_order: true, ..TreePrintOptions::default() };
+		assert_eq!(written_tree(&tree, &storage, &options), format!(
+			"└─Assets: (+3 -0 ~0)\n  ├─Zebra: (+2 -0 ~0)\n  │ ├─z.png: ADDED {0}\n  │ └─a.png: ADDED {2}\n  └─Apple.png: ADDED {1}\n",
+			uuid('1'), uuid('2'), uuid('3'),
+		));
+	}
+	
+	#[test]
+	fn path_without_elements_is_an_error() {
+		// A meta file named '.meta' belongs to an asset named '.meta', thus that is fine.
+		assert!(PathTreeStorage::default().add_to_tree(Path::new(".meta"), uuid('1')).is_ok());
+		let mut tree = PathTreeStorage::default();
+		for path in ["..meta", "A/...meta", "./..meta"] {
+			assert!(matches!(tree.add_to_tree(Path::new(path), uuid('1')), Err(TreePathError::Empty { .. })), "{path}");
+		}
+	}
+	
+	#[test]
+	fn paths_with_thousands_of_elements() {
+		// Test threads have a small stack, recursing once per element would overflow it.
+		const DEPTH: usize = 5000;
+		let folders = "A/".repeat(DEPTH - 1);
+		let old_path = format!("{folders}x.png.meta");
+		let new_path = format!("{folders}y.png.meta");
+		let unified = written_unified_tree(&[(&new_path, '1'), (&format!("{folders}z.png.meta"), '2')], &[(&old_path, '1')]);
+		assert_eq!(unified.lines().count(), DEPTH + 1);
+		assert!(unified.contains(&format!("y.png: RENAMED <= '{folders}x.png' (renamed)\n")));
+		
+		let (mut tree, storage) = addition_tree(&[&old_path, &new_path]);
+		assert_eq!(written_tree(&tree, &storage, &TreePrintOptions::default()).lines().count(), DEPTH + 1);
+		let options = TreePrintOptions { max_depth: Some(3), ..TreePrintOptions::default() };
+		assert_eq!(written_tree(&tree, &storage, &options), "└─A: (+2 -0 ~0)\n  └─A: (+2 -0 ~0)\n    └─A: (+2 -0 ~0)\n      └─A: (+2 -0 ~0)\n        └─... (2 more entries)\n");
+		tree.strip_prefix(Path::new(&folders[..folders.len() / 2]));
+		// Pruning the whole tree, and dropping it.
+		tree.retain(|_| false);
+		assert_eq!(written_tree(&tree, &storage, &TreePrintOptions::default()), "");
+	}
+	
+	#[test]
+	fn root_label_above_the_top_level_entries() {
+		let (tree, storage) = addition_tree(&["Assets/x.png.meta", "Packages/y.png.meta"]);
+		let options = TreePrintOptions { root_label: Some("Project".to_owned()), ..TreePrintOptions::default() };
+		assert_eq!(written_tree(&tree, &storage, &options), format!(
+			"Project: (+2 -0 ~0)\n├─Assets: (+1 -0 ~0)\n│ └─x.png: ADDED {}\n└─Packages: (+1 -0 ~0)\n  └─y.png: ADDED {}\n",
+			uuid('1'), uuid('2'),
+		));
+		// Nothing to be the root of.
+		assert_eq!(written_tree(&PathTreeStorage::default(), &storage, &options), "");
 	}
 }
@@ -1,7 +1,16 @@
 use git2::{Oid, Repository};
-use std::fmt::{Display, Formatter};
-use std::fs;
-use std::path::Path;
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+// Lines to read from a meta file on disk, before giving up on the GUID being at the top.
+const GUID_SEARCH_LINES: usize = 16;
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+// The top-level key of the line with the GUID, as Unity writes it. Some studios use their own sidecar files, with the identifier under another key.
+pub const DEFAULT_GUID_KEY: &str = "guid";
 
 // Unity Unique Identifier (lel)
 #[derive(Copy, Clone)]
@@ -14,6 +23,167 @@ pub struct Uuid {
 	hash_bytes: [u8; 16],
 }
 
+#[derive(Debug)]
+#[derive(Clone, Eq, PartialEq)]
+pub enum UuidParseError {
+	// Unity GUIDs are always 32 hex digits long.
+	WrongLength { got: usize },
+	// Byte position (in the input) of the first character that is not a hex digit.
+	InvalidHex { position: usize },
+}
+
+impl Display for UuidParseError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		match self {
+			UuidParseError::WrongLength { got } => write!(f, "expected 32 hex digits, got input of length {got}"),
+			UuidParseError::InvalidHex { position } => write!(f, "invalid hex digit at position {position}"),
+		}
+	}
+}
+
+impl Error for UuidParseError {}
+
+#[derive(Debug)]
+pub enum MetaReadError {
+	Io { path: PathBuf, error: io::Error },
+	MissingGuid { path: PathBuf },
+	MalformedGuid { path: PathBuf, text: String, error: UuidParseError },
+}
+
+impl Display for MetaReadError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		match self {
+			MetaReadError::Io { path, error } => write!(f, "Could not read meta file '{}': {error}", path.display()),
+			MetaReadError::MissingGuid { path } => write!(f, "Did not find UUID for path {}", path.display()),
+			MetaReadError::MalformedGuid { path, text, error } => write!(f, "Could not convert UUID '{text}' in file '{}': {error}", path.display()),
+		}
+	}
+}
+
+impl Error for MetaReadError {
+	fn source(&self) -> Option<&(dyn Error + 'static)> {
+		match self {
+			MetaReadError::Io { error, .. } => Some(error),
+			MetaReadError::MissingGuid { .. } => None,
+			MetaReadError::MalformedGuid { error, .. } => Some(error),
+		}
+	}
+}
+
+#[derive(Debug)]
+pub enum MetaBlobError {
+	Lookup { hash: Oid, error: git2::Error },
+	MissingGuid { hash: Oid },
+	MalformedGuid { hash: Oid, text: String, error: UuidParseError },
+}
+
+impl Display for MetaBlobError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		match self {
+			MetaBlobError::Lookup { hash, error } => write!(f, "Could not load blob {hash}: {error}"),
+			MetaBlobError::MissingGuid { hash } => write!(f, "Did not find UUID for blob {hash}"),
+			MetaBlobError::MalformedGuid { hash, text, error } => write!(f, "Could not convert UUID '{text}' in blob {hash}: {error}"),
+		}
+	}
+}
+
+impl Error for MetaBlobError {
+	fn source(&self) -> Option<&(dyn Error + 'static)> {
+		match self {
+			MetaBlobError::Lookup { error, .. } => Some(error),
+			MetaBlobError::MissingGuid { .. } => None,
+			MetaBlobError::MalformedGuid { error, .. } => Some(error),
+		}
+	}
+}
+
+#[derive(Debug)]
+#[derive(Clone, Eq, PartialEq)]
+pub enum MetaContentError {
+	MissingGuid,
+	MalformedGuid { text: String, error: UuidParseError },
+}
+
+impl Display for MetaContentError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		match self {
+			MetaContentError::MissingGuid => write!(f, "Did not find UUID in meta content"),
+			MetaContentError::MalformedGuid { text, error } => write!(f, "Could not convert UUID '{text}': {error}"),
+		}
+	}
+}
+
+impl Error for MetaContentError {
+	fn source(&self) -> Option<&(dyn Error + 'static)> {
+		match self {
+			MetaContentError::MissingGuid => None,
+			MetaContentError::MalformedGuid { error, .. } => Some(error),
+		}
+	}
+}
+
+// Everything this tool needs to know from a meta file.
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub struct MetaInfo {
+	pub guid: Uuid,
+	// Unity writes 'folderAsset: yes' into the meta files of folders.
+	pub is_folder: bool,
+}
+
+impl MetaInfo {
+	pub fn from_content(text: &str, guid_key: &str) -> Result<MetaInfo, MetaContentError> {
+		Self::from_bytes(text.as_bytes(), guid_key)
+	}
+	
+	// Does not require the content to be valid UTF-8, see Uuid::meta_lines().
+	pub fn from_bytes(content: &[u8], guid_key: &str) -> Result<MetaInfo, MetaContentError> {
+		let uuid_text = Uuid::from_meta_content(content, guid_key).ok_or(MetaContentError::MissingGuid)?;
+		let guid = uuid_text.parse().map_err(|error| MetaContentError::MalformedGuid { text: uuid_text, error })?;
+		// Like 'guid:', this is a top-level key. Thus, it is never indented.
+		let is_folder = Uuid::meta_lines(content).any(|line| line.strip_prefix(b"folderAsset:").is_some_and(|value| value.trim_ascii() == b"yes"));
+		Ok(MetaInfo { guid, is_folder })
+	}
+	
+	pub fn from_blob(repo: &Repository, hash: Oid, guid_key: &str) -> Result<MetaInfo, MetaBlobError> {
+		let blob = repo.find_blob(hash).map_err(|error| MetaBlobError::Lookup { hash, error })?;
+		Self::from_bytes(blob.content(), guid_key).map_err(|error| match error {
+			MetaContentError::MissingGuid => MetaBlobError::MissingGuid { hash },
+			MetaContentError::MalformedGuid { text, error } => MetaBlobError::MalformedGuid { hash, text, error },
+		})
+	}
+}
+
+// Unity writes lowercase hex, but hand-edited or generated meta files may use uppercase - or even mix both within one GUID.
+// Only the bytes are stored, thus all spellings of a GUID compare equal and are displayed in lowercase.
+impl FromStr for Uuid {
+	type Err = UuidParseError;
+	
+	fn from_str(input: &str) -> Result<Self, Self::Err> {
+		if input.len() != 32 {
+			return Err(UuidParseError::WrongLength { got: input.len() });
+		}
+		// Decode on the byte level, as slicing the string could split a multibyte character and
+		// u8::from_str_radix would also accept a leading '+' sign in place of a hex digit.
+		let input = input.as_bytes();
+		let digit = |position: usize| Self::hex_digit_value(input[position]).ok_or(UuidParseError::InvalidHex { position });
+		let mut bytes = [0u8; 16];
+		for (index, item) in bytes.iter_mut().enumerate() {
+			let input_index = index << 1;
+			*item = digit(input_index)? << 4 | digit(input_index + 1)?;
+		}
+		Ok(Self::from_bytes(bytes))
+	}
+}
+
+impl TryFrom<&str> for Uuid {
+	type Error = UuidParseError;
+	
+	fn try_from(input: &str) -> Result<Self, Self::Error> {
+		input.parse()
+	}
+}
+
+// Always lowercase (like Unity writes it), no matter how the GUID was spelled in the meta file. See FromStr.
 impl Display for Uuid {
 	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
 		let hex : String = self.hash_bytes.iter()
@@ -23,44 +193,473 @@ impl Display for Uuid {
 	}
 }
 
+// The raw bytes would be unreadable in panics and error messages. Show the same hex form as Display.
+impl Debug for Uuid {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		write!(f, "Uuid({self})")
+	}
+}
+
+// Serialized as the compact hex form, the same way Unity writes it into meta files.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Uuid {
+	fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.collect_str(self)
+	}
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Uuid {
+	fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		let text = String::deserialize(deserializer)?;
+		text.parse().map_err(serde::de::Error::custom)
+	}
+}
+
 impl Uuid {
-	fn from(input: &str) -> Option<Self> {
-		if input.len() != 32 {
-			return None;
+	pub const fn from_bytes(bytes: [u8; 16]) -> Uuid {
+		Uuid {
+			hash_bytes: bytes,
 		}
-		let mut bytes = [0u8; 16];
-		for (index, item) in bytes.iter_mut().enumerate() {
-			let input_index = index << 1;
-			*item = match u8::from_str_radix(&input[input_index..=(input_index + 1)], 16) {
-				Ok(v) => v,
-				Err(_) => return None,
+	}
+	
+	pub fn as_bytes(&self) -> &[u8; 16] {
+		&self.hash_bytes
+	}
+	
+	// Unity writes an all-zero GUID for placeholder or broken assets. It does not identify anything.
+	pub fn is_nil(&self) -> bool {
+		self.hash_bytes == [0; 16]
+	}
+	
+	// The 8-4-4-4-12 grouped form, as used by most non-Unity tooling. Display keeps the compact form, as written by Unity.
+	pub fn to_hyphenated(&self) -> String {
+		let hex = self.to_string();
+		format!("{}-{}-{}-{}-{}", &hex[0..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..32])
+	}
+	
+	pub fn from_disk(path: &Path, guid_key: &str) -> Result<Uuid, MetaReadError> {
+		let io_error = |error| MetaReadError::Io { path: path.to_path_buf(), error };
+		let mut reader = BufReader::new(File::open(path).map_err(io_error)?);
+		let uuid_text = Self::guid_text_from_reader(&mut reader, guid_key).map_err(io_error)?;
+		let uuid_text = uuid_text.ok_or_else(|| MetaReadError::MissingGuid { path: path.to_path_buf() })?;
+		uuid_text.parse().map_err(|error| MetaReadError::MalformedGuid { path: path.to_path_buf(), text: uuid_text, error })
+	}
+	
+	// Unity writes the GUID into the second line, but importer settings after it can be huge. Thus, stop reading once found.
+	fn guid_text_from_reader(reader: &mut impl BufRead, guid_key: &str) -> io::Result<Option<String>> {
+		let mut line = Vec::new();
+		let mut uuid_text = None;
+		for line_index in 0..GUID_SEARCH_LINES {
+			line.clear();
+			if reader.read_until(b'\n', &mut line)? == 0 {
+				break; // End of file.
+			}
+			let content = if line_index == 0 { line.strip_prefix(UTF8_BOM).unwrap_or(&line) } else { &line };
+			if let Some(found) = Self::guid_from_line(content, guid_key) {
+				uuid_text = Some(found);
+				break;
 			}
 		}
-		Some(Self {
-			hash_bytes: bytes,
-		})
+		// Some other tool might have put the GUID further down, fall back to scanning the remaining file.
+		if uuid_text.is_none() {
+			let mut rest = Vec::new();
+			reader.read_to_end(&mut rest)?;
+			uuid_text = Self::from_meta_content(&rest, guid_key);
+		}
+		Ok(uuid_text)
 	}
 	
-	pub fn from_disk_or_panic(path: &Path) -> Uuid {
-		let text = fs::read_to_string(path).unwrap();
-		let uuid_text = Self::from_meta_content(&text).unwrap_or_else(|| panic!("Did not find UUID for path {}", path.display()));
-		Uuid::from(uuid_text).unwrap_or_else(|| panic!("Could not convert UUID '{uuid_text}' in file '{}'", path.display()))
+	pub fn from_disk_or_panic(path: &Path, guid_key: &str) -> Uuid {
+		Self::from_disk(path, guid_key).unwrap_or_else(|error| panic!("{error}"))
 	}
 	
-	pub fn from_blob_or_panic(repo: &Repository, hash: Oid) -> Uuid {
-		let blob = repo.find_blob(hash).unwrap();
-		let text = String::from_utf8(blob.content().to_owned()).unwrap();
-		let uuid_text = Self::from_meta_content(&text).unwrap_or_else(|| panic!("Did not find UUID for blob {hash}"));
-		Uuid::from(uuid_text).unwrap_or_else(|| panic!("Could not convert UUID '{uuid_text}' in blob {hash}"))
+	pub fn from_blob(repo: &Repository, hash: Oid, guid_key: &str) -> Result<Uuid, MetaBlobError> {
+		let blob = repo.find_blob(hash).map_err(|error| MetaBlobError::Lookup { hash, error })?;
+		let uuid_text = Self::from_meta_content(blob.content(), guid_key).ok_or(MetaBlobError::MissingGuid { hash })?;
+		uuid_text.parse().map_err(|error| MetaBlobError::MalformedGuid { hash, text: uuid_text, error })
 	}
 	
-	fn from_meta_content(text: &str) -> Option<&str> {
-		for line in text.lines() {
-			// Technically Unity only ever puts one space into this line (after the colon), but let the code handle a few more spaces:
-			if let Some(uid) = line.strip_prefix("guid:") {
-				return Some(uid.trim());
+	pub fn from_blob_or_panic(repo: &Repository, hash: Oid, guid_key: &str) -> Uuid {
+		Self::from_blob(repo, hash, guid_key).unwrap_or_else(|error| panic!("{error}"))
+	}
+	
+	// Each case is decoded on its own, so that a GUID mixing both cases parses to the same bytes as its lowercase form.
+	fn hex_digit_value(byte: u8) -> Option<u8> {
+		match byte {
+			b'0'..=b'9' => Some(byte - b'0'),
+			b'a'..=b'f' => Some(byte - b'a' + 10),
+			b'A'..=b'F' => Some(byte - b'A' + 10),
+			_ => None,
+		}
+	}
+	
+	// Meta files are YAML and thus UTF-8. But an old blob might have been saved as Windows-1252 or Latin-1 by some editor.
+	// The keys of interest are pure ASCII, thus lines are searched on the byte level - the rest of the file may contain anything.
+	fn meta_lines(content: &[u8]) -> impl Iterator<Item = &[u8]> {
+		// Files saved by some Windows editors start with a UTF-8 BOM, which would otherwise prefix the first line.
+		let content = content.strip_prefix(UTF8_BOM).unwrap_or(content);
+		content.split(|byte| *byte == b'\n')
+	}
+	
+	// The value of the first line starting with the key (followed by a colon), see DEFAULT_GUID_KEY.
+	fn from_meta_content(content: &[u8], key: &str) -> Option<String> {
+		Self::meta_lines(content).find_map(|line| Self::guid_from_line(line, key))
+	}
+	
+	fn guid_from_line(line: &[u8], key: &str) -> Option<String> {
+		// Lines may still end with '\r' (Windows line endings), or with '\n' when coming from read_until().
+		let line = line.strip_suffix(b"\n").unwrap_or(line);
+		let line = line.strip_suffix(b"\r").unwrap_or(line);
+		// Technically Unity only ever puts one space into this line (after the colon), but let the code handle a few more spaces:
+		// A broken value is kept (lossy), to show it in the error message.
+		line.strip_prefix(key.as_bytes()).and_then(|rest| rest.strip_prefix(b":")).map(|uid| Self::unquote(String::from_utf8_lossy(uid).trim()).to_owned())
+	}
+	
+	// Some tools write the value as a YAML string, surrounded by matching quotes (which again could be padded).
+	fn unquote(value: &str) -> &str {
+		for quote in ['"', '\''] {
+			if let Some(inner) = value.strip_prefix(quote).and_then(|value| value.strip_suffix(quote)) {
+				return inner.trim();
+			}
+		}
+		value
+	}
+}
+
+#[cfg(test)]
+pub(crate) mod test_helpers {
+	use super::Uuid;
+	use crate::output::color::AnsiStripper;
+	use git2::Repository;
+	use std::io::{self, Write};
+	use std::path::PathBuf;
+	
+	// GUIDs are easier to recognize in the output, when they are all the same digit.
+	pub(crate) fn uuid(digit: char) -> Uuid {
+		std::iter::repeat_n(digit, 32).collect::<String>().parse().unwrap()
+	}
+	
+	// A throwaway bare repository to store blobs in. Deleted again by the caller.
+	pub(crate) fn temp_repository(name: &str) -> (PathBuf, Repository) {
+		let path = std::env::temp_dir().join(format!("unity-meta-diff-{name}-{}", std::process::id()));
+		let _ = std::fs::remove_dir_all(&path);
+		let repo = Repository::init_bare(&path).unwrap();
+		(path, repo)
+	}
+	
+	// Everything the function writes, with the color codes removed.
+	pub(crate) fn written(write: impl FnOnce(&mut dyn Write) -> io::Result<()>) -> String {
+		let mut output = Vec::new();
+		write(&mut AnsiStripper::new(&mut output)).unwrap();
+		String::from_utf8(output).unwrap()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::test_helpers::temp_repository;
+	
+	// Real GUIDs from Unity projects.
+	const GUIDS: [&str; 4] = [
+		"63079bf56d891f040a461867b5dc65cb",
+		"0000000000000000e000000000000000",
+		"9fc0d4010bbf28b4594072e72b8655ab",
+		"fe6f3c3b1a5e4b2a8c1d7f0e9a8b7c6d",
+	];
+	
+	#[test]
+	fn round_trip_through_display() {
+		for guid in GUIDS {
+			let uuid: Uuid = guid.parse().unwrap();
+			assert_eq!(uuid.to_string(), guid);
+		}
+	}
+	
+	#[test]
+	fn decodes_both_nibbles_of_each_byte() {
+		let uuid: Uuid = "0123456789abcdef0123456789abcdef".parse().unwrap();
+		assert_eq!(uuid.as_bytes(), &[0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef, 0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef]);
+	}
+	
+	// Regression: Only the even-positioned digits used to be decoded, thus these two collided.
+	#[test]
+	fn guids_differing_in_odd_nibbles_are_distinct() {
+		let first: Uuid = "10101010101010101010101010101010".parse().unwrap();
+		let second: Uuid = "1f1f1f1f1f1f1f1f1f1f1f1f1f1f1f1f".parse().unwrap();
+		assert_ne!(first, second);
+		assert_ne!(first.as_bytes(), second.as_bytes());
+	}
+	
+	#[test]
+	fn parse_rejects_wrong_length() {
+		assert_eq!("63079bf56d891f040a461867b5dc65c".parse::<Uuid>(), Err(UuidParseError::WrongLength { got: 31 }));
+		assert_eq!("63079bf56d891f040a461867b5dc65cb0".parse::<Uuid>(), Err(UuidParseError::WrongLength { got: 33 }));
+		assert_eq!("".parse::<Uuid>(), Err(UuidParseError::WrongLength { got: 0 }));
+	}
+	
+	#[test]
+	fn parse_rejects_non_hex() {
+		assert_eq!("g3079bf56d891f040a461867b5dc65cb".parse::<Uuid>(), Err(UuidParseError::InvalidHex { position: 0 }));
+		assert_eq!("63079bf56d891f040a461867b5dc65cz".parse::<Uuid>(), Err(UuidParseError::InvalidHex { position: 31 }));
+		// A sign is no hex digit, even though u8::from_str_radix would accept it.
+		assert_eq!("+3079bf56d891f040a461867b5dc65cb".parse::<Uuid>(), Err(UuidParseError::InvalidHex { position: 0 }));
+	}
+	
+	#[test]
+	fn try_from_matches_parse() {
+		let guid = "63079bf56d891f040a461867b5dc65cb";
+		assert_eq!(Uuid::try_from(guid), guid.parse::<Uuid>());
+		assert!(Uuid::try_from("63079bf5").is_err());
+	}
+	
+	// A meta file in the temp folder, named after the test (they run in parallel).
+	fn temp_meta_file(name: &str, content: &[u8]) -> PathBuf {
+		let path = std::env::temp_dir().join(format!("unity-meta-diff-{name}-{}.meta", std::process::id()));
+		std::fs::write(&path, content).unwrap();
+		path
+	}
+	
+	#[test]
+	fn from_disk_reads_the_guid() {
+		let path = temp_meta_file("from-disk", b"fileFormatVersion: 2\nguid: 63079bf56d891f040a461867b5dc65cb\n");
+		let uuid = Uuid::from_disk(&path, DEFAULT_GUID_KEY);
+		std::fs::remove_file(&path).unwrap();
+		assert_eq!(uuid.unwrap().to_string(), "63079bf56d891f040a461867b5dc65cb");
+	}
+	
+	#[test]
+	fn from_disk_reports_missing_file_with_path() {
+		let path = std::env::temp_dir().join("unity-meta-diff-does-not-exist.meta");
+		match Uuid::from_disk(&path, DEFAULT_GUID_KEY) {
+			Err(MetaReadError::Io { path: error_path, error }) => {
+				assert_eq!(error_path, path);
+				assert_eq!(error.kind(), io::ErrorKind::NotFound);
+			}
+			other => panic!("Expected an IO error, got {other:?}"),
+		}
+	}
+	
+	#[test]
+	fn from_disk_reports_missing_guid_with_path() {
+		let path = temp_meta_file("missing-guid", b"fileFormatVersion: 2\nfolderAsset: yes\n");
+		let result = Uuid::from_disk(&path, DEFAULT_GUID_KEY);
+		std::fs::remove_file(&path).unwrap();
+		assert!(matches!(result, Err(MetaReadError::MissingGuid { path: error_path }) if error_path == path));
+	}
+	
+	#[test]
+	fn from_disk_reports_malformed_guid_with_path() {
+		let path = temp_meta_file("malformed-guid", b"fileFormatVersion: 2\nguid: 63079bf5\n");
+		let result = Uuid::from_disk(&path, DEFAULT_GUID_KEY);
+		std::fs::remove_file(&path).unwrap();
+		match result {
+			Err(MetaReadError::MalformedGuid { path: error_path, text, error }) => {
+				assert_eq!(error_path, path);
+				assert_eq!(text, "63079bf5");
+				assert_eq!(error, UuidParseError::WrongLength { got: 8 });
 			}
+			other => panic!("Expected a malformed GUID error, got {other:?}"),
 		}
-		None
+	}
+	
+	#[test]
+	fn from_blob_reads_the_guid_and_reports_errors_with_hash() {
+		let (path, repo) = temp_repository("from-blob");
+		// Old blobs may not be UTF-8, only the GUID line matters.
+		let valid = repo.blob(b"fileFormatVersion: 2\nguid: 63079bf56d891f040a461867b5dc65cb\nuserData: caf\xE9\n").unwrap();
+		let missing = repo.blob(b"fileFormatVersion: 2\n").unwrap();
+		let malformed = repo.blob(b"fileFormatVersion: 2\nguid: xyz\n").unwrap();
+		let unknown = Oid::from_str("0123456789012345678901234567890123456789").unwrap();
+		
+		assert_eq!(Uuid::from_blob(&repo, valid, DEFAULT_GUID_KEY).unwrap().to_string(), "63079bf56d891f040a461867b5dc65cb");
+		assert!(matches!(Uuid::from_blob(&repo, missing, DEFAULT_GUID_KEY), Err(MetaBlobError::MissingGuid { hash }) if hash == missing));
+		assert!(matches!(Uuid::from_blob(&repo, malformed, DEFAULT_GUID_KEY), Err(MetaBlobError::MalformedGuid { hash, text, .. }) if hash == malformed && text == "xyz"));
+		assert!(matches!(Uuid::from_blob(&repo, unknown, DEFAULT_GUID_KEY), Err(MetaBlobError::Lookup { hash, .. }) if hash == unknown));
+		
+		drop(repo);
+		std::fs::remove_dir_all(path).unwrap();
+	}
+	
+	fn guid_of(content: &[u8]) -> Option<String> {
+		Uuid::from_meta_content(content, DEFAULT_GUID_KEY)
+	}
+	
+	#[test]
+	fn meta_content_with_bom() {
+		assert_eq!(guid_of(b"\xEF\xBB\xBFguid: 63079bf56d891f040a461867b5dc65cb\n").as_deref(), Some("63079bf56d891f040a461867b5dc65cb"));
+		assert_eq!(guid_of(b"\xEF\xBB\xBFfileFormatVersion: 2\nguid: 63079bf56d891f040a461867b5dc65cb\n").as_deref(), Some("63079bf56d891f040a461867b5dc65cb"));
+	}
+	
+	#[test]
+	fn meta_content_with_crlf() {
+		assert_eq!(guid_of(b"fileFormatVersion: 2\r\nguid: 63079bf56d891f040a461867b5dc65cb\r\nuserData: \r\n").as_deref(), Some("63079bf56d891f040a461867b5dc65cb"));
+	}
+	
+	#[test]
+	fn meta_content_with_bom_and_crlf() {
+		let content = b"\xEF\xBB\xBFguid: 63079bf56d891f040a461867b5dc65cb\r\nfolderAsset: yes\r\n";
+		assert_eq!(guid_of(content).as_deref(), Some("63079bf56d891f040a461867b5dc65cb"));
+		let info = MetaInfo::from_bytes(content, DEFAULT_GUID_KEY).unwrap();
+		assert_eq!(info.guid.to_string(), "63079bf56d891f040a461867b5dc65cb");
+		assert!(info.is_folder);
+	}
+	
+	#[test]
+	fn meta_file_with_bom_and_crlf() {
+		let path = temp_meta_file("bom-crlf", b"\xEF\xBB\xBFguid: 63079bf56d891f040a461867b5dc65cb\r\n");
+		let uuid = Uuid::from_disk(&path, DEFAULT_GUID_KEY);
+		std::fs::remove_file(&path).unwrap();
+		assert_eq!(uuid.unwrap().to_string(), "63079bf56d891f040a461867b5dc65cb");
+	}
+	
+	#[test]
+	fn quoted_guid_values() {
+		assert_eq!(guid_of(b"guid: \"63079bf56d891f040a461867b5dc65cb\"\n").as_deref(), Some("63079bf56d891f040a461867b5dc65cb"));
+		assert_eq!(guid_of(b"guid: '63079bf56d891f040a461867b5dc65cb'\n").as_deref(), Some("63079bf56d891f040a461867b5dc65cb"));
+		assert_eq!(guid_of(b"guid: \" 63079bf56d891f040a461867b5dc65cb \"\n").as_deref(), Some("63079bf56d891f040a461867b5dc65cb"));
+		// Mismatched quotes are no quoting, the value then fails to parse.
+		assert_eq!(guid_of(b"guid: \"63079bf56d891f040a461867b5dc65cb'\n").as_deref(), Some("\"63079bf56d891f040a461867b5dc65cb'"));
+	}
+	
+	#[test]
+	fn whitespace_padded_guid_values() {
+		assert_eq!(guid_of(b"guid:\t63079bf56d891f040a461867b5dc65cb\n").as_deref(), Some("63079bf56d891f040a461867b5dc65cb"));
+		assert_eq!(guid_of(b"guid:    63079bf56d891f040a461867b5dc65cb   \n").as_deref(), Some("63079bf56d891f040a461867b5dc65cb"));
+		assert_eq!(guid_of(b"guid:63079bf56d891f040a461867b5dc65cb\n").as_deref(), Some("63079bf56d891f040a461867b5dc65cb"));
+	}
+	
+	#[test]
+	fn hyphenated_form() {
+		let uuid: Uuid = "63079bf56d891f040a461867b5dc65cb".parse().unwrap();
+		assert_eq!(uuid.to_hyphenated(), "63079bf5-6d89-1f04-0a46-1867b5dc65cb");
+		assert_eq!(uuid.to_string(), "63079bf56d891f040a461867b5dc65cb");
+	}
+	
+	#[test]
+	fn bytes_round_trip() {
+		let bytes = [0x63, 0x07, 0x9b, 0xf5, 0x6d, 0x89, 0x1f, 0x04, 0x0a, 0x46, 0x18, 0x67, 0xb5, 0xdc, 0x65, 0xcb];
+		const UUID: Uuid = Uuid::from_bytes([0xff; 16]);
+		assert_eq!(Uuid::from_bytes(bytes).as_bytes(), &bytes);
+		assert_eq!(Uuid::from_bytes(bytes).to_string(), "63079bf56d891f040a461867b5dc65cb");
+		assert_eq!(UUID.as_bytes(), &[0xff; 16]);
+	}
+	
+	#[test]
+	fn nil_guid() {
+		assert!(Uuid::from_bytes([0; 16]).is_nil());
+		assert!("00000000000000000000000000000000".parse::<Uuid>().unwrap().is_nil());
+		assert!(!"00000000000000000000000000000001".parse::<Uuid>().unwrap().is_nil());
+	}
+	
+	#[test]
+	fn reading_stops_after_the_guid_line() {
+		let mut content = b"fileFormatVersion: 2\nguid: 63079bf56d891f040a461867b5dc65cb\n".to_vec();
+		let guid_end = content.len() as u64;
+		content.extend(b"  huge importer settings\n".repeat(100_000));
+		let mut reader = io::Cursor::new(content);
+		let text = Uuid::guid_text_from_reader(&mut reader, DEFAULT_GUID_KEY).unwrap();
+		assert_eq!(text.as_deref(), Some("63079bf56d891f040a461867b5dc65cb"));
+		assert_eq!(reader.position(), guid_end);
+	}
+	
+	#[test]
+	fn guid_after_the_search_window_is_found_too() {
+		let mut content = b"fileFormatVersion: 2\n".to_vec();
+		content.extend(b"other: value\n".repeat(GUID_SEARCH_LINES * 2));
+		content.extend(b"guid: 63079bf56d891f040a461867b5dc65cb\n");
+		let text = Uuid::guid_text_from_reader(&mut io::Cursor::new(content), DEFAULT_GUID_KEY).unwrap();
+		assert_eq!(text.as_deref(), Some("63079bf56d891f040a461867b5dc65cb"));
+	}
+	
+	#[test]
+	fn meta_info_of_folder_and_file() {
+		let folder = MetaInfo::from_content("fileFormatVersion: 2\nguid: 63079bf56d891f040a461867b5dc65cb\nfolderAsset: yes\nDefaultImporter:\n  userData: \n", DEFAULT_GUID_KEY).unwrap();
+		assert_eq!(folder.guid.to_string(), "63079bf56d891f040a461867b5dc65cb");
+		assert!(folder.is_folder);
+		let file = MetaInfo::from_content("fileFormatVersion: 2\nguid: 63079bf56d891f040a461867b5dc65cb\nTextureImporter:\n  mipmaps: 1\n", DEFAULT_GUID_KEY).unwrap();
+		assert!(!file.is_folder);
+		// Only the GUID is needed by the GUID-only parser.
+		assert_eq!(Uuid::from_meta_content(b"guid: 63079bf56d891f040a461867b5dc65cb\nfolderAsset: yes\n", "guid").as_deref(), Some("63079bf56d891f040a461867b5dc65cb"));
+	}
+	
+	#[test]
+	fn folder_flag_needs_a_top_level_yes() {
+		let is_folder = |extra: &str| MetaInfo::from_content(&format!("guid: 63079bf56d891f040a461867b5dc65cb\n{extra}"), DEFAULT_GUID_KEY).unwrap().is_folder;
+		assert!(is_folder("folderAsset:   yes  \n"));
+		assert!(!is_folder("folderAsset: no\n"));
+		assert!(!is_folder("Importer:\n  folderAsset: yes\n"));
+	}
+	
+	#[test]
+	fn meta_info_errors() {
+		assert!(matches!(MetaInfo::from_content("folderAsset: yes\n", DEFAULT_GUID_KEY), Err(MetaContentError::MissingGuid)));
+		assert!(matches!(MetaInfo::from_content("guid: xyz\n", DEFAULT_GUID_KEY), Err(MetaContentError::MalformedGuid { text, .. }) if text == "xyz"));
+	}
+	
+	#[test]
+	fn windows_1252_content_around_the_guid() {
+		// 'café' and '©' in Windows-1252, as older editors saved them.
+		let content = b"fileFormatVersion: 2\n# \xA9 caf\xE9\nguid: 63079bf56d891f040a461867b5dc65cb\nuserData: caf\xE9\nfolderAsset: yes\n";
+		assert_eq!(guid_of(content).as_deref(), Some("63079bf56d891f040a461867b5dc65cb"));
+		assert!(MetaInfo::from_bytes(content, DEFAULT_GUID_KEY).unwrap().is_folder);
+		let path = temp_meta_file("latin-1", content);
+		let uuid = Uuid::from_disk(&path, DEFAULT_GUID_KEY);
+		std::fs::remove_file(&path).unwrap();
+		assert_eq!(uuid.unwrap().to_string(), "63079bf56d891f040a461867b5dc65cb");
+	}
+	
+	#[test]
+	fn debug_shows_the_hex_form() {
+		let uuid: Uuid = "63079bf56d891f040a461867b5dc65cb".parse().unwrap();
+		assert_eq!(format!("{uuid:?}"), "Uuid(63079bf56d891f040a461867b5dc65cb)");
+		assert_eq!(format!("{:?}", Some(uuid)), "Some(Uuid(63079bf56d891f040a461867b5dc65cb))");
+	}
+	
+	#[test]
+	fn custom_guid_key() {
+		let content = b"fileFormatVersion: 2\nguid: 11111111111111111111111111111111\nuuid:  63079bf56d891f040a461867b5dc65cb \n";
+		assert_eq!(Uuid::from_meta_content(content, "uuid").as_deref(), Some("63079bf56d891f040a461867b5dc65cb"));
+		assert_eq!(guid_of(content).as_deref(), Some("11111111111111111111111111111111"));
+		// The key must be followed by the colon, a longer key is another key.
+		assert_eq!(Uuid::from_meta_content(b"uuids: 63079bf56d891f040a461867b5dc65cb\n", "uuid"), None);
+		// The key is passed per call, thus both formats can be read side by side.
+		let text = std::str::from_utf8(content).unwrap();
+		assert_eq!(MetaInfo::from_content(text, "uuid").unwrap().guid.to_string(), "63079bf56d891f040a461867b5dc65cb");
+		assert_eq!(MetaInfo::from_content(text, DEFAULT_GUID_KEY).unwrap().guid.to_string(), "11111111111111111111111111111111");
+	}
+	
+	#[test]
+	fn uppercase_and_mixed_case_equal_lowercase() {
+		let lowercase: Uuid = "63079bf56d891f040a461867b5dc65cb".parse().unwrap();
+		for spelling in ["63079BF56D891F040A461867B5DC65CB", "63079Bf56d891F040a461867b5Dc65cB"] {
+			let uuid: Uuid = spelling.parse().unwrap();
+			assert_eq!(uuid, lowercase, "{spelling}");
+			assert_eq!(uuid.to_string(), "63079bf56d891f040a461867b5dc65cb", "{spelling}");
+		}
+		// Letters past F stay invalid in either case.
+		assert_eq!("63079bf56d891f040a461867b5dc65cG".parse::<Uuid>(), Err(UuidParseError::InvalidHex { position: 31 }));
+	}
+}
+
+// Only with '--features serde'.
+#[cfg(all(test, feature = "serde"))]
+mod serde_tests {
+	use super::*;
+	
+	#[test]
+	fn round_trip() {
+		let uuid: Uuid = "0123456789abcdef0123456789abcdef".parse().unwrap();
+		let json = serde_json::to_string(&uuid).unwrap();
+		assert_eq!(json, "\"0123456789abcdef0123456789abcdef\"");
+		assert_eq!(serde_json::from_str::<Uuid>(&json).unwrap(), uuid);
+	}
+	
+	#[test]
+	fn rejects_malformed_guid() {
+		assert!(serde_json::from_str::<Uuid>("\"0123456789abcdef\"").is_err());
+		assert!(serde_json::from_str::<Uuid>("\"0123456789abcdef0123456789abcdeg\"").is_err());
+		assert!(serde_json::from_str::<Uuid>("42").is_err());
 	}
 }
@@ -1,56 +1,641 @@
+use crate::data::path_diff;
 use crate::data::uuid::Uuid;
 use ecc_ansi_lib::ansi;
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
 
 #[derive(Default)]
 #[derive(Clone)]
 pub struct UuidStorageEntry {
-	pub added: Option<PathBuf>,
-	pub removed: Option<PathBuf>,
+	// Normally there is at most one path per direction. More than one means the same GUID is used by multiple files.
+	pub added: Vec<PathBuf>,
+	pub removed: Vec<PathBuf>,
+	// Meta files which got edited (for example importer settings), while keeping their GUID.
+	pub modified: Vec<PathBuf>,
+	// Unchanged files which an added path got copied from. They still use the GUID, thus the copy duplicates it.
+	pub copied_from: Vec<PathBuf>,
+	// The GUID belongs to a folder asset. Moving it means, that everything inside moved too.
+	pub is_folder: bool,
+	// The user rejected the rename, the removed and added paths are reported as unrelated removal and addition.
+	pub is_split: bool,
+	// When this entry got created, relative to the other entries.
+	pub order: usize,
+}
+
+#[derive(Debug)]
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub enum ChangeKind {
+	Added,
+	Removed,
+	// The GUID got removed from one path and added to another path.
+	Renamed,
+	// Neither added nor removed, or removed and added again at the very same path.
+	Unchanged,
+	// A rename which is reported as removal and addition instead, see UuidStorageEntry::is_split.
+	Split,
+}
+
+impl ChangeKind {
+	// How the change looks like from one side of the diff. A split rename is an addition on one side and a removal on the other.
+	pub fn side(self, is_adding: bool) -> ChangeKind {
+		match self {
+			ChangeKind::Split if is_adding => ChangeKind::Added,
+			ChangeKind::Split => ChangeKind::Removed,
+			kind => kind,
+		}
+	}
+}
+
+impl UuidStorageEntry {
+	pub fn classify(&self) -> ChangeKind {
+		match (self.added.is_empty(), self.removed.is_empty()) {
+			(false, true) => ChangeKind::Added,
+			(true, false) => ChangeKind::Removed,
+			(false, false) if self.added != self.removed && self.is_split => ChangeKind::Split,
+			(false, false) if self.added != self.removed => ChangeKind::Renamed,
+			_ => ChangeKind::Unchanged,
+		}
+	}
+	
+	pub fn has_duplicates(&self) -> bool {
+		self.added_with_copy_sources().len() > 1 || self.removed.len() > 1
+	}
+	
+	// The paths which use the GUID after the change, besides the ones which got removed.
+	fn added_with_copy_sources(&self) -> Vec<PathBuf> {
+		if self.added.is_empty() {
+			// Nothing got copied anymore - for example after inverting the change.
+			return Vec::new();
+		}
+		self.added.iter().chain(&self.copied_from).cloned().collect()
+	}
+}
+
+#[derive(Debug, Default)]
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub struct ChangeSummary {
+	pub added: usize,
+	pub removed: usize,
+	pub renamed: usize,
+	pub modified: usize,
+	// GUIDs used by more than one added or removed path.
+	pub conflicts: usize,
+}
+
+impl Display for ChangeSummary {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		write!(
+			f, "{} added, {} removed, {} renamed, {} modified, {} GUID conflicts",
+			self.added, self.removed, self.renamed, self.modified, self.conflicts,
+		)
+	}
+}
+
+impl ChangeSummary {
+	// Whether any GUID got added, removed or moved. Modifications keep the GUID and do not count.
+	pub fn has_guid_changes(&self) -> bool {
+		self.added != 0 || self.removed != 0 || self.renamed != 0
+	}
+}
+
+// Multiple files being added (or removed) with the same GUID. Unity requires GUIDs to be unique - this breaks references.
+pub struct GuidCollision {
+	pub uuid: Uuid,
+	pub is_adding: bool,
+	pub paths: Vec<PathBuf>,
+}
+
+// Renames which all moved their common end (for example 'A/x.png') from the same old folder to the same new folder.
+pub struct MoveGroup {
+	pub old_prefix: PathBuf,
+	pub new_prefix: PathBuf,
+	// The rest of the paths, which is the same before and after. Empty if the last path element changed (a plain rename).
+	pub moves: Vec<(Uuid, PathBuf)>,
+}
+
+// One reported change of a GUID, see UuidStorage::records(). Added and removed paths only exist on one side, renamed ones on both.
+pub struct ChangeRecord<'a> {
+	// Only Added, Removed or Renamed.
+	pub kind: ChangeKind,
+	pub uuid: Uuid,
+	pub old_path: Option<&'a Path>,
+	pub new_path: Option<&'a Path>,
 }
 
 #[derive(Default)]
 pub struct UuidStorage {
-	pub lookup: HashMap<Uuid, UuidStorageEntry>
+	// Ordered by UUID, to ensure consistent output order.
+	pub lookup: BTreeMap<Uuid, UuidStorageEntry>,
+	// Iterate in the order the UUIDs got added (as Git reported them), instead of by UUID.
+	pub keep_insertion_order: bool,
 }
 
 impl UuidStorage {
 	fn get_or_create_node(&mut self, uuid: Uuid) -> &mut UuidStorageEntry {
-		self.lookup.entry(uuid).or_default()
+		let order = self.lookup.len();
+		self.lookup.entry(uuid).or_insert_with(|| UuidStorageEntry { order, ..UuidStorageEntry::default() })
 	}
 	
-	pub fn added(&mut self, uuid: Uuid, path: PathBuf) -> Option<&PathBuf> {
-		Self::set(&mut self.get_or_create_node(uuid).added, path)
+	// If the UUID was already added via another path, both are kept and reported as collision.
+	pub fn added(&mut self, uuid: Uuid, path: PathBuf) {
+		Self::push(&mut self.get_or_create_node(uuid).added, path);
+	}
+	
+	// If the UUID was already removed via another path, both are kept and reported as collision.
+	pub fn removed(&mut self, uuid: Uuid, path: PathBuf) {
+		Self::push(&mut self.get_or_create_node(uuid).removed, path);
+	}
+	
+	pub fn modified(&mut self, uuid: Uuid, path: PathBuf) {
+		Self::push(&mut self.get_or_create_node(uuid).modified, path);
+	}
+	
+	// Every added path becomes a removed path and the other way around. Modifications stay as they are.
+	pub fn invert(&mut self) {
+		for entry in self.lookup.values_mut() {
+			std::mem::swap(&mut entry.added, &mut entry.removed);
+		}
 	}
 	
-	pub fn removed(&mut self, uuid: Uuid, path: PathBuf) -> Option<&PathBuf> {
-		Self::set(&mut self.get_or_create_node(uuid).removed, path)
+	pub fn retain(&mut self, keep: impl FnMut(&Uuid, &mut UuidStorageEntry) -> bool) {
+		self.lookup.retain(keep);
 	}
 	
-	fn set(option: &mut Option<PathBuf>, mut path: PathBuf) -> Option<&PathBuf> {
+	// The source of a copied file, which keeps its GUID. Together with the added copy, it is reported as collision.
+	pub fn copied_from(&mut self, uuid: Uuid, path: PathBuf) {
+		Self::push(&mut self.get_or_create_node(uuid).copied_from, path);
+	}
+	
+	pub fn mark_folder(&mut self, uuid: Uuid) {
+		self.get_or_create_node(uuid).is_folder = true;
+	}
+	
+	// Shortens all paths below the prefix, see path_diff::strip_prefix(). Other paths stay unchanged.
+	pub fn strip_prefix(&mut self, prefix: &Path) {
+		for entry in self.lookup.values_mut() {
+			for path in entry.added.iter_mut().chain(&mut entry.removed).chain(&mut entry.modified).chain(&mut entry.copied_from) {
+				if let Some(rest) = path_diff::strip_prefix(path, prefix) {
+					*path = rest.to_path_buf();
+				}
+			}
+		}
+	}
+	
+	// Whether the meta file path got already added (or removed) with the UUID.
+	pub fn contains(&self, uuid: Uuid, is_adding: bool, path: &Path) -> bool {
+		let path = path.with_extension("");
+		self.lookup.get(&uuid).is_some_and(|entry| if is_adding { entry.added.contains(&path) } else { entry.removed.contains(&path) })
+	}
+	
+	fn push(list: &mut Vec<PathBuf>, mut path: PathBuf) {
 		path.set_extension("");
-		if option.is_none() {
-			*option = Some(path);
-			None
-		} else {
-			option.as_ref()
+		list.push(path);
+	}
+	
+	pub fn iter(&self) -> impl Iterator<Item = (&Uuid, &UuidStorageEntry)> {
+		let mut entries: Vec<_> = self.lookup.iter().collect();
+		if self.keep_insertion_order {
+			entries.sort_by_key(|(_, entry)| entry.order);
+		}
+		entries.into_iter()
+	}
+	
+	// The changes as flat records, for the machine-readable formats. Ordered like iter().
+	// Split renames turn into an addition and a removal. A GUID shared by multiple files results in one record per path (or path pair for renames).
+	pub fn records(&self) -> impl Iterator<Item = ChangeRecord<'_>> {
+		self.iter().flat_map(|(&uuid, entry)| {
+			let record = |kind, old_path, new_path| ChangeRecord { kind, uuid, old_path, new_path };
+			let records: Vec<_> = match entry.classify() {
+				ChangeKind::Added | ChangeKind::Removed | ChangeKind::Split => {
+					let added = entry.added.iter().map(|path| record(ChangeKind::Added, None, Some(path.as_path())));
+					let removed = entry.removed.iter().map(|path| record(ChangeKind::Removed, Some(path.as_path()), None));
+					added.chain(removed).collect()
+				}
+				ChangeKind::Renamed => entry.removed.iter()
+					.flat_map(|old_path| entry.added.iter().map(move |new_path| record(ChangeKind::Renamed, Some(old_path.as_path()), Some(new_path.as_path()))))
+					.collect(),
+				ChangeKind::Unchanged => Vec::new(),
+			};
+			records
+		})
+	}
+	
+	pub fn renames(&self) -> impl Iterator<Item = (&Uuid, &UuidStorageEntry)> {
+		self.of_kind(ChangeKind::Renamed)
+	}
+	
+	// Includes the added side of split renames.
+	pub fn pure_additions(&self) -> impl Iterator<Item = (&Uuid, &UuidStorageEntry)> {
+		self.iter().filter(|(_, entry)| entry.classify().side(true) == ChangeKind::Added)
+	}
+	
+	// Includes the removed side of split renames.
+	pub fn pure_removals(&self) -> impl Iterator<Item = (&Uuid, &UuidStorageEntry)> {
+		self.iter().filter(|(_, entry)| entry.classify().side(false) == ChangeKind::Removed)
+	}
+	
+	fn of_kind(&self, kind: ChangeKind) -> impl Iterator<Item = (&Uuid, &UuidStorageEntry)> {
+		self.iter().filter(move |(_, entry)| entry.classify() == kind)
+	}
+	
+	pub fn summary(&self) -> ChangeSummary {
+		let mut summary = ChangeSummary::default();
+		for (_, entry) in self.iter() {
+			match entry.classify() {
+				ChangeKind::Added => summary.added += 1,
+				ChangeKind::Removed => summary.removed += 1,
+				ChangeKind::Renamed => summary.renamed += 1,
+				ChangeKind::Split => {
+					summary.added += 1;
+					summary.removed += 1;
+				}
+				ChangeKind::Unchanged => {}
+			}
+			summary.modified += entry.modified.len();
+			if entry.has_duplicates() {
+				summary.conflicts += 1;
+			}
+		}
+		summary
+	}
+	
+	// Amount of added, removed or renamed GUIDs as (folder assets, file assets). Many files and few folders means the files moved one by one.
+	pub fn count_changed_assets(&self) -> (usize, usize) {
+		let changed = || self.lookup.values().filter(|entry| entry.classify() != ChangeKind::Unchanged);
+		let folders = changed().filter(|entry| entry.is_folder).count();
+		(folders, changed().count() - folders)
+	}
+	
+	// Same counting as summary(), but per asset type. Renames count for the extension of the new path, split renames for both.
+	// Ordered by the amount of changes (most first), then by extension. The GUID conflicts are not counted.
+	pub fn summary_by_extension(&self) -> Vec<(String, ChangeSummary)> {
+		// Unity does not care about the case of extensions. Folders can have a dot in their name, that is no extension though.
+		fn tally<'a>(tallies: &'a mut BTreeMap<String, ChangeSummary>, path: &Path, is_folder: bool) -> &'a mut ChangeSummary {
+			let key = match path.extension() {
+				_ if is_folder => "(folder)".to_owned(),
+				Some(extension) => format!(".{}", extension.to_string_lossy().to_lowercase()),
+				None => "(none)".to_owned(),
+			};
+			tallies.entry(key).or_default()
+		}
+		let mut tallies = BTreeMap::new();
+		for (_, entry) in self.iter() {
+			match entry.classify() {
+				ChangeKind::Added => tally(&mut tallies, &entry.added[0], entry.is_folder).added += 1,
+				ChangeKind::Removed => tally(&mut tallies, &entry.removed[0], entry.is_folder).removed += 1,
+				ChangeKind::Renamed => tally(&mut tallies, &entry.added[0], entry.is_folder).renamed += 1,
+				ChangeKind::Split => {
+					tally(&mut tallies, &entry.added[0], entry.is_folder).added += 1;
+					tally(&mut tallies, &entry.removed[0], entry.is_folder).removed += 1;
+				}
+				ChangeKind::Unchanged => {}
+			}
+			for path in &entry.modified {
+				tally(&mut tallies, path, entry.is_folder).modified += 1;
+			}
 		}
+		let mut tallies: Vec<_> = tallies.into_iter().collect();
+		// Stable sort, thus equal amounts stay ordered by extension.
+		tallies.sort_by_key(|(_, summary)| std::cmp::Reverse(summary.added + summary.removed + summary.renamed + summary.modified));
+		tallies
+	}
+	
+	// Clusters the renames by how their paths changed: 'Old/A/x.png' => 'New/A/x.png' moved 'A/x.png' from 'Old' to 'New'.
+	// Renames which changed the last path element stay alone. With GUID conflicts, only the first old and new path are used.
+	// Ordered by the amount of moves (most first), then by old and new prefix.
+	pub fn move_groups(&self) -> Vec<MoveGroup> {
+		let mut groups: BTreeMap<(PathBuf, PathBuf), Vec<(Uuid, PathBuf)>> = BTreeMap::new();
+		for (uuid, entry) in self.renames() {
+			let (old_path, new_path) = (&entry.removed[0], &entry.added[0]);
+			let (_, _, end) = path_diff::split_common(old_path, new_path);
+			let prefix = |path: &Path| -> PathBuf {
+				let components: Vec<_> = path.components().collect();
+				components[..components.len() - end.len()].iter().collect()
+			};
+			let end: PathBuf = end.iter().collect();
+			if end.as_os_str().is_empty() {
+				// The whole path is the prefix, thus no other rename can share it.
+				groups.entry((old_path.clone(), new_path.clone())).or_default().push((*uuid, end));
+			} else {
+				groups.entry((prefix(old_path), prefix(new_path))).or_default().push((*uuid, end));
+			}
+		}
+		let mut groups: Vec<_> = groups.into_iter()
+			.map(|((old_prefix, new_prefix), moves)| MoveGroup { old_prefix, new_prefix, moves })
+			.collect();
+		// Stable sort, thus equal amounts stay ordered by prefix.
+		groups.sort_by_key(|group| std::cmp::Reverse(group.moves.len()));
+		groups
+	}
+	
+	pub fn collisions(&self) -> Vec<GuidCollision> {
+		let mut collisions = Vec::new();
+		for (uuid, entry) in self.iter() {
+			if entry.removed.len() > 1 {
+				collisions.push(GuidCollision { uuid: *uuid, is_adding: false, paths: entry.removed.clone() });
+			}
+			let added = entry.added_with_copy_sources();
+			if added.len() > 1 {
+				collisions.push(GuidCollision { uuid: *uuid, is_adding: true, paths: added });
+			}
+		}
+		collisions
+	}
+	
+	pub fn print_collisions(&self) {
+		self.write_collisions(&mut io::stdout().lock()).expect("Failed to write to stdout.");
+	}
+	
+	pub fn write_collisions(&self, out: &mut dyn Write) -> io::Result<()> {
+		for collision in self.collisions() {
+			if collision.is_adding {
+				writeln!(out, ansi!("  {} is «lg»added«» by {} files:"), collision.uuid, collision.paths.len())?;
+			} else {
+				writeln!(out, ansi!("  {} is «lr»removed«» by {} files:"), collision.uuid, collision.paths.len())?;
+			}
+			for path in collision.paths.iter() {
+				writeln!(out, ansi!("    - «w»{}«»"), path.display())?;
+			}
+		}
+		Ok(())
+	}
+	
+	pub fn write_by_extension(&self, out: &mut dyn Write) -> io::Result<()> {
+		writeln!(out, "{:<12} {:>7} {:>7} {:>7} {:>8}", "Extension", "Added", "Removed", "Renamed", "Modified")?;
+		for (extension, summary) in self.summary_by_extension() {
+			writeln!(
+				out, ansi!("«w»{:<12}«» «lg»{:>7}«» «lr»{:>7}«» «lb»{:>7}«» «y»{:>8}«»"),
+				extension, summary.added, summary.removed, summary.renamed, summary.modified,
+			)?;
+		}
+		Ok(())
+	}
+	
+	// One line per move group, with 'list_moves' followed by the moved paths below the old prefix.
+	pub fn write_move_groups(&self, out: &mut dyn Write, list_moves: bool) -> io::Result<()> {
+		// The repository root has no name, './' still shows that everything got moved into (or out of) a folder.
+		let display_prefix = |prefix: &Path| if prefix.as_os_str().is_empty() { "./".to_owned() } else { format!("{}/", prefix.display()) };
+		for group in self.move_groups() {
+			if group.moves[0].1.as_os_str().is_empty() {
+				writeln!(out, ansi!("  1 asset renamed from «lr»{}«» to «lg»{}«»"), group.old_prefix.display(), group.new_prefix.display())?;
+				continue;
+			}
+			let noun = if group.moves.len() == 1 { "asset" } else { "assets" };
+			writeln!(
+				out, ansi!("  {} {} moved from «lr»{}«» to «lg»{}«»"),
+				group.moves.len(), noun, display_prefix(&group.old_prefix), display_prefix(&group.new_prefix),
+			)?;
+			if list_moves {
+				for (uuid, path) in &group.moves {
+					writeln!(out, ansi!("    - «w»{}«» {}"), path.display(), uuid)?;
+				}
+			}
+		}
+		Ok(())
 	}
 	
 	pub fn debug_print(&self) {
-		let mut list: Vec<_> = self.lookup.iter().collect();
-		// HashMaps are ordered with a random seed - sort to ensure consistent output order.
-		list.sort_by_key(|item| item.0);
-		
-		for (uuid, storage) in list.into_iter() {
-			println!("{uuid}:");
+		self.debug_write(&mut io::stdout().lock()).expect("Failed to write to stdout.");
+	}
+	
+	pub fn debug_write(&self, out: &mut dyn Write) -> io::Result<()> {
+		for (uuid, storage) in self.iter() {
+			writeln!(out, "{uuid}:")?;
 			for removed in storage.removed.iter() {
-				println!(ansi!("  «lr»{}«»"), removed.display());
+				writeln!(out, ansi!("  «lr»{}«»"), removed.display())?;
 			}
 			for added in storage.added.iter() {
-				println!(ansi!("  «lg»{}«»"), added.display());
+				writeln!(out, ansi!("  «lg»{}«»"), added.display())?;
 			}
 		}
+		Ok(())
+	}
+	
+	pub fn print_modified(&self) {
+		self.write_modified(&mut io::stdout().lock()).expect("Failed to write to stdout.");
+	}
+	
+	pub fn write_modified(&self, out: &mut dyn Write) -> io::Result<()> {
+		let mut list: Vec<_> = self.iter()
+			.flat_map(|(uuid, storage)| storage.modified.iter().map(move |path| (path, uuid)))
+			.collect();
+		if !self.keep_insertion_order {
+			list.sort();
+		}
+		
+		for (path, uuid) in list.into_iter() {
+			writeln!(out, ansi!("  «w»{}«»: «y»MODIFIED«» {}"), path.display(), uuid)?;
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	use crate::data::uuid::test_helpers::{uuid, written};
+	
+	#[test]
+	fn all_paths_of_a_guid_are_kept() {
+		let mut storage = UuidStorage::default();
+		storage.added(uuid('1'), PathBuf::from("A/x.png.meta"));
+		storage.added(uuid('1'), PathBuf::from("B/y.png.meta"));
+		storage.removed(uuid('2'), PathBuf::from("C/z.png.meta"));
+		let entry = &storage.lookup[&uuid('1')];
+		assert_eq!(entry.added, vec![PathBuf::from("A/x.png"), PathBuf::from("B/y.png")]);
+		assert!(entry.has_duplicates());
+		assert!(!storage.lookup[&uuid('2')].has_duplicates());
+		let output = written(|out| storage.debug_write(out));
+		assert_eq!(output, format!("{}:\n  A/x.png\n  B/y.png\n{}:\n  C/z.png\n", uuid('1'), uuid('2')));
+	}
+	
+	#[test]
+	fn modified_with_same_guid() {
+		let mut storage = UuidStorage::default();
+		storage.modified(uuid('1'), PathBuf::from("A/x.png.meta"));
+		let entry = &storage.lookup[&uuid('1')];
+		assert_eq!(entry.modified, vec![PathBuf::from("A/x.png")]);
+		assert_eq!(entry.classify(), ChangeKind::Unchanged);
+		assert_eq!(storage.summary(), ChangeSummary { modified: 1, ..ChangeSummary::default() });
+		assert_eq!(written(|out| storage.write_modified(out)), format!("  A/x.png: MODIFIED {}\n", uuid('1')));
+	}
+	
+	// One GUID of each kind: '1' added, '2' removed, '3' and '4' renamed, '5' only modified.
+	fn mixed_storage() -> UuidStorage {
+		let mut storage = UuidStorage::default();
+		storage.added(uuid('1'), PathBuf::from("New/a.png.meta"));
+		storage.removed(uuid('2'), PathBuf::from("Old/b.png.meta"));
+		storage.removed(uuid('3'), PathBuf::from("Old/c.png.meta"));
+		storage.added(uuid('3'), PathBuf::from("New/c.png.meta"));
+		storage.removed(uuid('4'), PathBuf::from("Old/d.png.meta"));
+		storage.added(uuid('4'), PathBuf::from("New/d.png.meta"));
+		storage.lookup.get_mut(&uuid('4')).unwrap().is_split = true;
+		storage.modified(uuid('5'), PathBuf::from("Old/e.png.meta"));
+		storage
+	}
+	
+	// The digits of the GUIDs (see uuid()), in iteration order.
+	fn uuids<'a>(entries: impl Iterator<Item = (&'a Uuid, &'a UuidStorageEntry)>) -> Vec<Uuid> {
+		entries.map(|(uuid, _)| *uuid).collect()
+	}
+	
+	#[test]
+	fn iterators_classify_the_entries() {
+		let storage = mixed_storage();
+		assert_eq!(uuids(storage.iter()), vec![uuid('1'), uuid('2'), uuid('3'), uuid('4'), uuid('5')]);
+		assert_eq!(uuids(storage.renames()), vec![uuid('3')]);
+		// Split renames count on both sides.
+		assert_eq!(uuids(storage.pure_additions()), vec![uuid('1'), uuid('4')]);
+		assert_eq!(uuids(storage.pure_removals()), vec![uuid('2'), uuid('4')]);
+	}
+	
+	fn entry(added: &[&str], removed: &[&str]) -> UuidStorageEntry {
+		UuidStorageEntry {
+			added: added.iter().map(PathBuf::from).collect(),
+			removed: removed.iter().map(PathBuf::from).collect(),
+			..UuidStorageEntry::default()
+		}
+	}
+	
+	#[test]
+	fn classify_all_kinds() {
+		assert_eq!(entry(&["A/x.png"], &[]).classify(), ChangeKind::Added);
+		assert_eq!(entry(&[], &["A/x.png"]).classify(), ChangeKind::Removed);
+		assert_eq!(entry(&["B/x.png"], &["A/x.png"]).classify(), ChangeKind::Renamed);
+		assert_eq!(entry(&[], &[]).classify(), ChangeKind::Unchanged);
+	}
+	
+	#[test]
+	fn classify_same_path_on_both_sides_as_unchanged() {
+		assert_eq!(entry(&["A/x.png"], &["A/x.png"]).classify(), ChangeKind::Unchanged);
+	}
+	
+	#[test]
+	fn iteration_is_ordered_by_guid() {
+		let mut storage = UuidStorage::default();
+		for digit in ['c', '3', 'f', '0', 'a'] {
+			storage.added(uuid(digit), PathBuf::from(format!("{digit}.png.meta")));
+		}
+		assert_eq!(uuids(storage.iter()), vec![uuid('0'), uuid('3'), uuid('a'), uuid('c'), uuid('f')]);
+		let output = written(|out| storage.debug_write(out));
+		let order: Vec<&str> = output.lines().filter(|line| !line.starts_with(' ')).collect();
+		assert_eq!(order, ['0', '3', 'a', 'c', 'f'].map(|digit| format!("{}:", uuid(digit))));
+	}
+	
+	#[test]
+	fn summary_counts_each_kind() {
+		let mut storage = mixed_storage();
+		storage.added(uuid('1'), PathBuf::from("Other/a.png.meta"));
+		let summary = storage.summary();
+		// The split rename counts as addition and removal.
+		assert_eq!(summary, ChangeSummary { added: 2, removed: 2, renamed: 1, modified: 1, conflicts: 1 });
+		assert_eq!(summary.to_string(), "2 added, 2 removed, 1 renamed, 1 modified, 1 GUID conflicts");
+		assert!(summary.has_guid_changes());
+		assert!(!ChangeSummary { modified: 3, ..ChangeSummary::default() }.has_guid_changes());
+	}
+	
+	#[test]
+	fn collisions_are_reported_with_all_paths() {
+		let mut storage = UuidStorage::default();
+		storage.added(uuid('1'), PathBuf::from("A/x.png.meta"));
+		storage.added(uuid('1'), PathBuf::from("B/x.png.meta"));
+		storage.removed(uuid('2'), PathBuf::from("C/y.png.meta"));
+		let collisions = storage.collisions();
+		assert_eq!(collisions.len(), 1);
+		assert_eq!(collisions[0].uuid, uuid('1'));
+		assert!(collisions[0].is_adding);
+		assert_eq!(collisions[0].paths, vec![PathBuf::from("A/x.png"), PathBuf::from("B/x.png")]);
+		assert_eq!(
+			written(|out| storage.write_collisions(out)),
+			format!("  {} is added by 2 files:\n    - A/x.png\n    - B/x.png\n", uuid('1')),
+		);
+	}
+	
+	#[test]
+	fn copy_source_collides_with_the_copy() {
+		let mut storage = UuidStorage::default();
+		storage.added(uuid('1'), PathBuf::from("B/x.png.meta"));
+		storage.copied_from(uuid('1'), PathBuf::from("A/x.png.meta"));
+		let entry = &storage.lookup[&uuid('1')];
+		assert_eq!(entry.classify(), ChangeKind::Added);
+		assert!(entry.has_duplicates());
+		let collisions = storage.collisions();
+		assert_eq!(collisions.len(), 1);
+		assert!(collisions[0].is_adding);
+		assert_eq!(collisions[0].paths, vec![PathBuf::from("B/x.png"), PathBuf::from("A/x.png")]);
+		assert_eq!(storage.summary().conflicts, 1);
+	}
+	
+	#[test]
+	fn inverted_copy_does_not_collide() {
+		let mut storage = UuidStorage::default();
+		storage.added(uuid('1'), PathBuf::from("B/x.png.meta"));
+		storage.copied_from(uuid('1'), PathBuf::from("A/x.png.meta"));
+		storage.invert();
+		assert_eq!(storage.lookup[&uuid('1')].classify(), ChangeKind::Removed);
+		assert!(storage.collisions().is_empty());
+		assert_eq!(storage.summary().conflicts, 0);
+	}
+	
+	#[test]
+	fn records_of_each_kind() {
+		let mut storage = UuidStorage::default();
+		storage.added(uuid('1'), PathBuf::from("A/x.png.meta"));
+		storage.removed(uuid('2'), PathBuf::from("A/y.png.meta"));
+		storage.added(uuid('2'), PathBuf::from("B/y.png.meta"));
+		storage.added(uuid('2'), PathBuf::from("C/y.png.meta"));
+		storage.removed(uuid('3'), PathBuf::from("A/z.png.meta"));
+		storage.added(uuid('3'), PathBuf::from("B/z.png.meta"));
+		storage.lookup.get_mut(&uuid('3')).unwrap().is_split = true;
+		storage.modified(uuid('4'), PathBuf::from("A/w.png.meta"));
+		let records: Vec<_> = storage.records()
+			.map(|record| (record.kind, record.uuid, record.old_path.map(Path::to_path_buf), record.new_path.map(Path::to_path_buf)))
+			.collect();
+		let path = |path: &str| Some(PathBuf::from(path));
+		assert_eq!(records, vec![
+			(ChangeKind::Added, uuid('1'), None, path("A/x.png")),
+			(ChangeKind::Renamed, uuid('2'), path("A/y.png"), path("B/y.png")),
+			(ChangeKind::Renamed, uuid('2'), path("A/y.png"), path("C/y.png")),
+			// A split rename is an addition and a removal.
+			(ChangeKind::Added, uuid('3'), None, path("B/z.png")),
+			(ChangeKind::Removed, uuid('3'), path("A/z.png"), None),
+		]);
+	}
+	
+	#[test]
+	fn iteration_in_insertion_order() {
+		let mut storage = UuidStorage::default();
+		storage.added(uuid('3'), PathBuf::from("Assets/c.png.meta"));
+		storage.removed(uuid('1'), PathBuf::from("Assets/a.png.meta"));
+		storage.added(uuid('2'), PathBuf::from("Assets/b.png.meta"));
+		let order = |storage: &UuidStorage| storage.iter().map(|(uuid, _)| *uuid).collect::<Vec<_>>();
+		assert_eq!(order(&storage), vec![uuid('1'), uuid('2'), uuid('3')]);
+		storage.keep_insertion_order = true;
+		assert_eq!(order(&storage), vec![uuid('3'), uuid('1'), uuid('2')]);
+	}
+	
+	#[test]
+	fn tallies_per_asset_extension() {
+		let mut storage = UuidStorage::default();
+		storage.added(uuid('1'), PathBuf::from("Assets/a.png.meta"));
+		storage.added(uuid('2'), PathBuf::from("Assets/b.PNG.meta"));
+		storage.removed(uuid('3'), PathBuf::from("Assets/c.png.meta"));
+		storage.removed(uuid('4'), PathBuf::from("Assets/Old.prefab.meta"));
+		storage.added(uuid('4'), PathBuf::from("Assets/New.prefab.meta"));
+		storage.modified(uuid('5'), PathBuf::from("Assets/Stone.mat.meta"));
+		storage.added(uuid('6'), PathBuf::from("Assets/Level.v2.meta"));
+		storage.mark_folder(uuid('6'));
+		storage.removed(uuid('7'), PathBuf::from("Assets/LICENSE.meta"));
+		assert_eq!(written(|out| storage.write_by_extension(out)), concat!(
+			"Extension      Added Removed Renamed Modified\n",
+			".png               2       1       0        0\n",
+			"(folder)           1       0       0        0\n",
+			"(none)             0       1       0        0\n",
+			".mat               0       0       0        1\n",
+			".prefab            0       0       1        0\n",
+		));
 	}
 }
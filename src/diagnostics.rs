@@ -0,0 +1,60 @@
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+
+#[derive(Debug)]
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub enum Severity {
+	// Expected situations, which still explain why something is missing from the results.
+	Info,
+	// Something in the repository is likely broken and should be looked at.
+	Warning,
+}
+
+impl Display for Severity {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		f.write_str(match self {
+			Severity::Info => "info",
+			Severity::Warning => "warning",
+		})
+	}
+}
+
+pub struct Diagnostic {
+	pub severity: Severity,
+	pub message: String,
+	// The paths the message is about, for tools which do not want to parse the message.
+	pub paths: Vec<PathBuf>,
+}
+
+// Collects problems found while analyzing, instead of printing them in between the results.
+// It is up to the caller to present them - after the results, or as part of a machine-readable format.
+#[derive(Default)]
+pub struct Diagnostics {
+	entries: Vec<Diagnostic>,
+}
+
+impl Diagnostics {
+	pub fn push(&mut self, severity: Severity, message: String, paths: Vec<PathBuf>) {
+		self.entries.push(Diagnostic { severity, message, paths });
+	}
+	
+	pub fn info(&mut self, message: String, paths: Vec<PathBuf>) {
+		self.push(Severity::Info, message, paths);
+	}
+	
+	pub fn warning(&mut self, message: String, paths: Vec<PathBuf>) {
+		self.push(Severity::Warning, message, paths);
+	}
+	
+	pub fn iter(&self) -> impl Iterator<Item = &Diagnostic> {
+		self.entries.iter()
+	}
+	
+	pub fn len(&self) -> usize {
+		self.entries.len()
+	}
+	
+	pub fn is_empty(&self) -> bool {
+		self.entries.is_empty()
+	}
+}
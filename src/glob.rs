@@ -0,0 +1,119 @@
+use std::path::Path;
+use std::str::FromStr;
+
+// Part of a pattern between two slashes.
+enum Segment {
+	// '**' matches any amount of path elements, including none.
+	AnyElements,
+	// Matches a single path element, '*' stands for any amount of characters and '?' for a single one.
+	Element(Vec<char>),
+}
+
+// Path patterns similar to the simple cases of '.gitignore' files:
+//  'Temp' or 'Temp/' (no inner slash) matches a file or folder with that name anywhere.
+//  'Assets/Temp' or '/Assets/Temp' (with inner or leading slash) only matches relative to the repository root.
+//  '**/Temp/**' matches any amount of folders in place of each '**'.
+// A matched folder also matches everything inside it.
+pub struct GlobPattern {
+	segments: Vec<Segment>,
+	// Whether the pattern has to match from the first path element on.
+	anchored: bool,
+}
+
+impl FromStr for GlobPattern {
+	type Err = String;
+	
+	fn from_str(pattern: &str) -> Result<Self, Self::Err> {
+		// A trailing slash only says, that a folder is meant. Folders match their content anyway.
+		let trimmed = pattern.trim_end_matches('/');
+		let anchored = trimmed.contains('/');
+		let segments: Vec<Segment> = trimmed.split('/')
+			.filter(|segment| !segment.is_empty())
+			.map(|segment| match segment {
+				"**" => Segment::AnyElements,
+				_ => Segment::Element(segment.chars().collect()),
+			})
+			.collect();
+		if segments.is_empty() {
+			return Err(format!("Invalid pattern '{pattern}', it does not contain any path element."));
+		}
+		Ok(GlobPattern { segments, anchored })
+	}
+}
+
+impl GlobPattern {
+	// Backslashes are treated as separator too, like everywhere else paths are split.
+	pub fn matches(&self, path: &Path) -> bool {
+		let path = path.to_string_lossy();
+		let elements: Vec<&str> = path.split(['/', '\\']).filter(|element| !element.is_empty()).collect();
+		// Any folder of the path matching means that the path is inside a matched folder.
+		let last_start = if self.anchored { 0 } else { elements.len() };
+		(0..=last_start).any(|start| {
+			(start + 1..=elements.len()).any(|end| Self::matches_elements(&self.segments, &elements[start..end]))
+		})
+	}
+	
+	fn matches_elements(segments: &[Segment], elements: &[&str]) -> bool {
+		match segments.split_first() {
+			None => elements.is_empty(),
+			Some((Segment::AnyElements, other_segments)) => {
+				(0..=elements.len()).any(|skipped| Self::matches_elements(other_segments, &elements[skipped..]))
+			}
+			Some((Segment::Element(pattern), other_segments)) => {
+				elements.split_first().is_some_and(|(element, other_elements)| {
+					let element: Vec<char> = element.chars().collect();
+					Self::matches_characters(pattern, &element) && Self::matches_elements(other_segments, other_elements)
+				})
+			}
+		}
+	}
+	
+	fn matches_characters(pattern: &[char], text: &[char]) -> bool {
+		match pattern.split_first() {
+			None => text.is_empty(),
+			Some(('*', other_pattern)) => (0..=text.len()).any(|skipped| Self::matches_characters(other_pattern, &text[skipped..])),
+			Some(('?', other_pattern)) => !text.is_empty() && Self::matches_characters(other_pattern, &text[1..]),
+			Some((character, other_pattern)) => text.first() == Some(character) && Self::matches_characters(other_pattern, &text[1..]),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	fn matches(pattern: &str, path: &str) -> bool {
+		pattern.parse::<GlobPattern>().unwrap().matches(Path::new(path))
+	}
+	
+	#[test]
+	fn any_depth_pattern() {
+		assert!(matches("**/Temp/**", "Temp/x.png.meta"));
+		assert!(matches("**/Temp/**", "Assets/Temp/x.png.meta"));
+		assert!(matches("**/Temp/**", "Assets/Deep/Temp/Sub/x.png.meta"));
+		assert!(!matches("**/Temp/**", "Assets/Temporary/x.png.meta"));
+		assert!(!matches("**/Temp/**", "Assets/Temp.meta"));
+	}
+	
+	#[test]
+	fn name_without_slash_matches_anywhere() {
+		assert!(matches("Temp/", "Assets/Temp/x.png.meta"));
+		assert!(matches("Temp", "Temp/x.png.meta"));
+		assert!(matches("*.asset.meta", "Assets/Data/x.asset.meta"));
+		assert!(!matches("Temp", "Assets/Temp.meta"));
+	}
+	
+	#[test]
+	fn inner_slash_anchors_at_the_root() {
+		assert!(matches("Assets/Generated", "Assets/Generated/x.meta"));
+		assert!(matches("/Assets/Generated", "Assets/Generated/x.meta"));
+		assert!(!matches("Assets/Generated", "Packages/Assets/Generated/x.meta"));
+		assert!(matches("Assets/?en*", "Assets/Generated.meta"));
+	}
+	
+	#[test]
+	fn empty_pattern_is_rejected() {
+		assert!("/".parse::<GlobPattern>().is_err());
+		assert!("".parse::<GlobPattern>().is_err());
+	}
+}
@@ -0,0 +1,26 @@
+// The analysis as a library, for other tools to embed it. The command line interface lives in main.rs.
+pub mod analysis;
+pub mod diagnostics;
+pub mod glob;
+pub mod data {
+	pub mod uuid;
+	pub mod uuid_storage;
+	pub mod blob_uuid_cache;
+	pub mod path_tree_storage;
+	pub mod path_diff;
+}
+pub mod output {
+	pub mod color;
+	pub mod format;
+	pub mod json;
+	pub mod log;
+	pub mod porcelain;
+	pub mod csv;
+	pub mod dot;
+	pub mod markdown;
+}
+
+pub use crate::analysis::analyze;
+pub use crate::data::path_tree_storage::PathTreeStorage;
+pub use crate::data::uuid::Uuid;
+pub use crate::data::uuid_storage::UuidStorage;
@@ -1,139 +1,559 @@
-pub mod argument_parsing;
-pub mod data {
-	pub mod uuid;
-	pub mod uuid_storage;
-	pub mod path_tree_storage;
-}
+mod argument_parsing;
+mod time_point;
+mod timings;
+#[cfg(feature = "notify")]
+mod watch;
 
-use crate::argument_parsing::{parse_arguments_open_repo, parse_arguments_create_diff};
-use crate::data::path_tree_storage::PathTreeStorage;
-use crate::data::uuid::Uuid;
-use crate::data::uuid_storage::UuidStorage;
+use crate::argument_parsing::{changed_submodules, create_diff_between, create_submodule_diff, open_submodule, parse_arguments_open_repo, parse_arguments_create_diff, Options};
+use crate::timings::Timings;
 use ecc_ansi_lib::ansi;
-use git2::{Delta, Diff, DiffDelta, Repository};
-use std::path::Path;
+use git2::{Diff, DiffDelta, Repository};
+use std::fs::File;
+use std::io::{self, BufRead, BufWriter, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::OnceLock;
+use std::time::Instant;
+use unity_engine_meta_git_differ::analysis::{analyze_deltas_with_progress, count_meta_files, find_assets_without_meta, find_context_siblings, find_orphans, gather_filtered_deltas_from_diff, gather_filtered_submodule_deltas, AnalysisError, AnalysisResult, DiffTarget};
+use unity_engine_meta_git_differ::data::path_diff;
+use unity_engine_meta_git_differ::data::uuid::DEFAULT_GUID_KEY;
+use unity_engine_meta_git_differ::data::path_tree_storage::{PathTreeStorage, TreePrintOptions};
+use unity_engine_meta_git_differ::output::color;
+use unity_engine_meta_git_differ::output::csv;
+use unity_engine_meta_git_differ::output::dot;
+use unity_engine_meta_git_differ::output::format::OutputFormat;
+use unity_engine_meta_git_differ::output::json;
+use unity_engine_meta_git_differ::output::log;
+use unity_engine_meta_git_differ::output::markdown;
+use unity_engine_meta_git_differ::output::porcelain;
+use unity_engine_meta_git_differ::Uuid;
+
+// With '--interactive', renames between paths less similar than this (see path_diff::similarity()) have to be confirmed.
+const CONFIRM_RENAME_SIMILARITY: f32 = 0.5;
+
+// With '--exit-code', like 'git diff --exit-code'. Exit code 1 stays reserved for errors, panics exit with 101.
+const EXIT_CODE_NO_CHANGES: i32 = 0;
+const EXIT_CODE_CHANGES: i32 = 2;
+// With '--fail-on-conflict', takes precedence over the codes of '--exit-code'.
+const EXIT_CODE_CONFLICTS: i32 = 3;
+
+// What a run found, for the exit code.
+#[derive(Default)]
+struct RunOutcome {
+	// What '--exit-code' reports.
+	found_changes: bool,
+	// What '--fail-on-conflict' reports, see AnalysisResult::conflicting_guids().
+	conflicting_guids: Vec<Uuid>,
+}
 
 fn main() {
-	let (repo, temp) = parse_arguments_open_repo(); // Due to lifetime rules, parsing arguments is a two-step operation.
-	let diff = parse_arguments_create_diff(&repo, temp);
-	let diffs = gather_filtered_deltas_from_diff(&diff);
-	println!("Unstaged: {}", diffs.len());
-	println!();
-	
-	let mut uuid_storage = UuidStorage::default();
-	let mut addition_tree = PathTreeStorage::default();
-	let mut removal_tree = PathTreeStorage::default();
-	
-	sort_deltas_into_storages(
-		&repo, &diffs,
-		&mut uuid_storage,
-		&mut addition_tree, &mut removal_tree,
-	);
-	
-	// uuid_storage.debug_print();
-	// println!();
+	let (repo, options, temp) = parse_arguments_open_repo(); // Due to lifetime rules, parsing arguments is a two-step operation.
+	color::set_enabled(options.color);
 	
-	// Currently just print the two trees. That is sufficient information for starters.
-	// Eventually a bunch of optimizations and improvements to the printing should be added.
-	println!(ansi!("«lr»By removal tree«»:"));
-	removal_tree.debug_print(&uuid_storage, false);
-	println!();
-	
-	println!(ansi!("«lg»By addition tree«»:"));
-	addition_tree.debug_print(&uuid_storage, true);
-}
-
-fn sort_deltas_into_storages(
-	repository: &Repository, diffs: &Vec<DiffDelta>,
-	uuid_storage: &mut UuidStorage,
-	addition_tree: &mut PathTreeStorage, removal_tree: &mut PathTreeStorage,
-) {
-	fn added(
-		uuid_storage: &mut UuidStorage, addition_tree: &mut PathTreeStorage,
-		path: &Path, uuid: Uuid
-	) {
-		if let Some(previous_entry) = uuid_storage.added(uuid, path.to_path_buf()) {
-			println!(
-				ansi!("«y»WARNING:«» Trying to add a file to Git with a Unity GUID ({}) that is already added to the Git via path '{}'\n"),
-				uuid, previous_entry.display(),
-			);
-			println!(">> IGNORING newer path '{}'", path.display());
-		} else {
-			addition_tree.add_to_tree(path, uuid);
-		}
+	// A file is not a terminal, thus 'auto' never colors it.
+	let color_output = !options.format.is_machine_readable() && match options.output {
+		Some(_) => options.color.should_color(false),
+		None => color::is_enabled(),
+	};
+	let mut timings = Timings::new(options.timings);
+	// The revisions are resolved before any output file gets touched, thus an invalid one leaves no trace.
+	let single = (!options.batch && !options.watch).then(|| {
+		let diff = parse_arguments_create_diff(&repo, &options, temp);
+		timings.lap("creating diff");
+		diff
+	});
+	let mut out = color::writer(open_output(&options), color_output);
+	// Created right away, so that a wrong path fails before the work is done.
+	let mut extra_outputs: Vec<_> = options.extra_outputs.iter()
+		.map(|extra| (extra.format, color::writer(create_output_file(&extra.path), extra.format == OutputFormat::Tree && options.color.should_color(false))))
+		.collect();
+	let outcome = match single {
+		Some((diff, target)) => run(&repo, &options, &diff, &target, &mut out, &mut extra_outputs, &mut timings),
+		None if options.batch => run_batch(&repo, &options, &mut out, &mut timings),
+		None => run_watch(&repo, &options, &mut out, &mut timings),
+	};
+	drop(out);
+	drop(extra_outputs);
+	timings.write(&mut io::stderr()).expect("Failed to write to stderr.");
+	
+	if options.fail_on_conflict && !outcome.conflicting_guids.is_empty() {
+		let guids: Vec<_> = outcome.conflicting_guids.iter().map(Uuid::to_string).collect();
+		eprintln!("Found GUID conflicts (used by multiple files or for the same path): {}", guids.join(", "));
+		process::exit(EXIT_CODE_CONFLICTS);
+	}
+	if options.exit_code {
+		process::exit(if outcome.found_changes { EXIT_CODE_CHANGES } else { EXIT_CODE_NO_CHANGES });
+	}
+}
+
+// Analyzes and prints a single diff. Returns what was found, for the exit code.
+// The results are written to the extra outputs too, in their own format.
+fn run(
+	repo: &Repository, options: &Options, diff: &Diff, target: &DiffTarget,
+	out: &mut dyn Write, extra_outputs: &mut [(OutputFormat, Box<dyn Write>)], timings: &mut Timings,
+) -> RunOutcome {
+	let diffs = gather_filtered_deltas_from_diff(diff, &options.subdirs, &options.excludes);
+	timings.lap("filtering deltas");
+	if options.list_files {
+		list_files(out, &diffs).and_then(|_| out.flush()).expect("Failed to write output.");
+		return RunOutcome::default();
+	}
+	let total = options.stats.then(|| count_meta_files_or_warn(repo, target, &options.subdirs)).flatten();
+	if !options.format.is_machine_readable() && !options.summary && !options.only_conflicts {
+		match total {
+			Some(total) => writeln!(out, "Changed meta files: {} (of {total} meta files)", diffs.len()),
+			None => writeln!(out, "Changed meta files: {}", diffs.len()),
+		}.expect("Failed to write output.");
+		writeln!(out).expect("Failed to write output.");
+	} else if let Some(total) = total {
+		// Machine-readable output has no place for it.
+		log::message(format_args!("Scanned {total} meta files, {} changed.", diffs.len()));
 	}
 	
-	fn removed(
-		uuid_storage: &mut UuidStorage, removal_tree: &mut PathTreeStorage,
-		path: &Path, uuid: Uuid
-	) {
-		if let Some(previous_entry) = uuid_storage.removed(uuid, path.to_path_buf()) {
-			println!(
-				ansi!("«y»WARNING:«» Trying to remove a file from Git with a Unity GUID ({}) that is already removed from the Git via path '{}'\n"),
-				uuid, previous_entry.display(),
-			);
-			println!(">> IGNORING newer path '{}'", path.display());
-		} else {
-			removal_tree.add_to_tree(path, uuid);
+	// The blobs are read before the trees get built. The last progress report tells when that is done.
+	let blobs_read = OnceLock::new();
+	let progress = |done, total| {
+		log::progress(done, total);
+		if done == total {
+			blobs_read.get_or_init(Instant::now);
 		}
+	};
+	let guid_key = options.guid_key.as_deref().unwrap_or(DEFAULT_GUID_KEY);
+	let mut result = analyze_deltas_with_progress(repo, &diffs, &options.excludes, guid_key, &progress).unwrap_or_else(|error| exit_with_analysis_error(error));
+	if let Some(&end) = blobs_read.get() {
+		timings.lap_at("reading blobs", end);
+	}
+	if options.recurse_submodules {
+		add_submodules(repo, options, diff, target, &mut result).unwrap_or_else(|error| exit_with_analysis_error(error));
+	}
+	result.retain_subdirs(&options.subdirs);
+	timings.lap("building trees");
+	if options.invert {
+		result.invert();
+	}
+	result.uuid_storage.keep_insertion_order = options.no_sort;
+	// Asking for each pair in a batch would be confusing - and stdin provides the pairs there anyway.
+	if options.interactive && !options.batch && io::stdin().is_terminal() {
+		confirm_renames(&mut result, &mut io::stdin().lock(), &mut io::stderr()).expect("Failed to ask for rename confirmation.");
 	}
 	
-	for delta in diffs.iter() {
-		// When working with libgit2, it does not detect renames by default. Thus, only additions/removals & modifications.
-		// This means that old/new paths should always be set and always be the same. If that is not the case something is wrong - stop then.
-		if delta.new_file().path().is_none() || delta.old_file().path().is_none() || delta.new_file().path().unwrap() != delta.old_file().path().unwrap() {
-			panic!("The path of the old/new file did not match or one/both had not been set: {:?} ||| {:?}", delta.old_file(), delta.new_file());
+	if options.check_orphans {
+		let orphans = find_orphans(repo, target, &result.uuid_storage).unwrap_or_else(|error| {
+			eprintln!("Failed to look up the assets of the added meta files.\nDetails (by gitlib2): {error}");
+			process::exit(1);
+		});
+		for orphan in &orphans {
+			writeln!(out, "Meta file without asset: {}.meta", orphan.display()).expect("Failed to write output.");
 		}
-		
-		let path = delta.old_file().path().unwrap().to_path_buf();
-		// Not sure why this would ever happen. But let's not take the chance.
-		if path.iter().next().is_none() {
-			panic!("Path for diff delta was empty. This should never happen.");
+		out.flush().expect("Failed to write output.");
+		log::diagnostics(&result.diagnostics);
+		return RunOutcome { found_changes: !orphans.is_empty(), conflicting_guids: result.conflicting_guids() };
+	}
+	
+	for asset_path in find_assets_without_meta(diff, &options.subdirs, &options.excludes) {
+		let message = format!("Asset '{}' got added without a meta file, Unity will assign it a new GUID.", asset_path.display());
+		result.diagnostics.warning(message, vec![asset_path]);
+	}
+	if options.only_renames {
+		result.retain_renames();
+	}
+	// After retaining, as the context of left out changes would stay otherwise.
+	if options.context {
+		for meta_path in find_context_siblings(repo, &result.uuid_storage) {
+			// Such a path would not show up in the tree anyway.
+			let _ = result.addition_tree.add_context(&meta_path);
 		}
-		
-		match delta.status() {
-			Delta::Untracked => {
-				// The work-directory file (at path) was not in Git and is freshly added.
-				let uuid = Uuid::from_disk_or_panic(&path);
-				added(uuid_storage, addition_tree, &path, uuid);
+	}
+	// After the orphan check and the context, as these look up the full paths on disk. The messages of diagnostics keep the full paths.
+	if let Some(prefix) = &options.strip_prefix {
+		result.strip_prefix(prefix);
+	}
+	if options.only_conflicts {
+		// The conflicts are the results now, the other diagnostics would only distract.
+		let conflict_count = result.collisions.len() + result.addition_tree.conflicts().len() + result.removal_tree.conflicts().len();
+		match options.format {
+			OutputFormat::Json => json::write_conflicts_json(out, &result),
+			_ => print_conflicts(out, &result),
+		}.and_then(|_| out.flush()).expect("Failed to write output.");
+		return RunOutcome { found_changes: conflict_count != 0, conflicting_guids: result.conflicting_guids() };
+	}
+	
+	// result.uuid_storage.debug_print();
+	// println!();
+	timings.lap("post-processing");
+	
+	// An empty label stands for the name of the repository folder.
+	let root_label = options.root_label.as_ref().map(|label| match label.as_str() {
+		"" => repo.workdir().unwrap_or(repo.path()).file_name().map_or_else(|| ".".to_owned(), |name| name.to_string_lossy().into_owned()),
+		_ => label.clone(),
+	});
+	let print_options = TreePrintOptions {
+		ascii: options.ascii,
+		root_label,
+		keep_insertion_order: options.no_sort,
+		..TreePrintOptions::default()
+	};
+	write_results(out, options, options.format, &result, &print_options).and_then(|_| out.flush()).expect("Failed to write output.");
+	for (format, extra_out) in extra_outputs.iter_mut() {
+		write_results(extra_out, options, *format, &result, &print_options).and_then(|_| extra_out.flush()).expect("Failed to write output.");
+	}
+	timings.lap("printing");
+	
+	// JSON already contains them.
+	if options.format != OutputFormat::Json {
+		log::diagnostics(&result.diagnostics);
+	}
+	RunOutcome { found_changes: result.uuid_storage.summary().has_guid_changes(), conflicting_guids: result.conflicting_guids() }
+}
+
+// The total is only context, thus a failure leaves it out instead of failing the diff.
+fn count_meta_files_or_warn(repo: &Repository, target: &DiffTarget, subdirs: &[PathBuf]) -> Option<usize> {
+	count_meta_files(repo, target, subdirs)
+		.inspect_err(|error| log::warning(format_args!("Could not count the meta files, the total is left out.\nDetails (by gitlib2): {error}")))
+		.ok()
+}
+
+// Submodules outside of the requested folders are not even opened. Those which cannot be inspected end up as warnings.
+// Meta files which cannot be read inside an inspected submodule fail the analysis, just like in the main repository.
+fn add_submodules(repo: &Repository, options: &Options, diff: &Diff, target: &DiffTarget, result: &mut AnalysisResult) -> Result<(), AnalysisError> {
+	let is_relevant = |path: &Path| (options.subdirs.is_empty() || options.subdirs.iter().any(|subdir| path.starts_with(subdir) || subdir.starts_with(path)))
+		&& !options.excludes.iter().any(|pattern| pattern.matches(path));
+	for change in changed_submodules(diff).into_iter().filter(|change| is_relevant(&change.path)) {
+		let submodule_repo = match open_submodule(repo, &change) {
+			Ok(submodule_repo) => submodule_repo,
+			Err(reason) => {
+				result.skip_submodule(change.path, reason);
+				continue;
 			}
-			Delta::Added => {
-				// The file (at path) is added to Git.
-				let hash = delta.new_file().id();
-				let uuid = Uuid::from_blob_or_panic(repository, hash);
-				added(uuid_storage, addition_tree, &path, uuid);
+		};
+		let submodule_diff = match create_submodule_diff(&submodule_repo, options, &change, target) {
+			Ok(submodule_diff) => submodule_diff,
+			Err(reason) => {
+				result.skip_submodule(change.path, reason);
+				continue;
 			}
-			Delta::Deleted => {
-				// The file (at path) was removed from Git
-				let hash = delta.old_file().id();
-				let uuid = Uuid::from_blob_or_panic(repository, hash);
-				removed(uuid_storage, removal_tree, &path, uuid);
+		};
+		let deltas = gather_filtered_submodule_deltas(&submodule_diff, &change.path, &options.subdirs, &options.excludes);
+		result.add_submodule(&submodule_repo, &deltas, &change.path, &options.excludes, options.guid_key.as_deref().unwrap_or(DEFAULT_GUID_KEY))?;
+	}
+	Ok(())
+}
+
+// One '<from> <to>' (or '<from>..<to>') pair per line of stdin, each gets its own section. Empty lines and lines starting with '#' are skipped.
+// JSON output becomes an array with one document per pair, in input order.
+fn run_batch(repo: &Repository, options: &Options, out: &mut dyn Write, timings: &mut Timings) -> RunOutcome {
+	let is_json = options.format == OutputFormat::Json;
+	if is_json {
+		writeln!(out, "[").expect("Failed to write output.");
+	}
+	let mut outcome = RunOutcome::default();
+	let mut is_first = true;
+	for (line_index, line) in io::stdin().lock().lines().enumerate() {
+		let line = line.unwrap_or_else(|error| panic!("Failed to read from stdin: {error}"));
+		let line = line.trim();
+		if line.is_empty() || line.starts_with('#') {
+			continue;
+		}
+		let parts: Vec<&str> = line.split_whitespace().collect();
+		let (from, to) = match parts[..] {
+			[from, to] => (from, to),
+			[range] if range.contains("..") => range.split_once("..").unwrap(),
+			_ => {
+				eprintln!("Invalid line {} on stdin '{line}', expected '<from> <to>'.", line_index + 1);
+				process::exit(1);
 			}
-			Delta::Modified => {
-				// The file path has not changed, but the content did.
-				let uuid_from = Uuid::from_blob_or_panic(repository, delta.old_file().id());
-				let uuid_to = Uuid::from_blob_or_panic(repository, delta.new_file().id());
-				// For the purpose of this program, only care about this file, when the UUID changed.
-				// As in all other cases, everything is expected and okay.
-				if uuid_from != uuid_to {
-					added(uuid_storage, addition_tree, &path, uuid_to);
-					removed(uuid_storage, removal_tree, &path, uuid_from);
-				}
+		};
+		let (diff, target) = create_diff_between(repo, options, from, to).unwrap_or_else(|error| {
+			eprintln!("{error}");
+			process::exit(1);
+		});
+		timings.lap("creating diff");
+		
+		match options.format {
+			OutputFormat::Json if !is_first => writeln!(out, ","),
+			OutputFormat::Json => Ok(()),
+			OutputFormat::Markdown => writeln!(out, "## {from}..{to}\n"),
+			_ => writeln!(out, ansi!("«lb»Diff {}..{}«»:\n"), from, to),
+		}.expect("Failed to write output.");
+		is_first = false;
+		let pair_outcome = run(repo, options, &diff, &target, out, &mut [], timings);
+		outcome.found_changes |= pair_outcome.found_changes;
+		outcome.conflicting_guids.extend(pair_outcome.conflicting_guids);
+		if !is_json {
+			writeln!(out).expect("Failed to write output.");
+		}
+	}
+	if is_json {
+		writeln!(out, "]").expect("Failed to write output.");
+	}
+	out.flush().expect("Failed to write output.");
+	// The same GUID may conflict in multiple pairs.
+	outcome.conflicting_guids.sort();
+	outcome.conflicting_guids.dedup();
+	outcome
+}
+
+// Runs until interrupted (Ctrl+C). Returns what the last run found. Only the requested folders are watched, Unity's 'Library' folder is huge.
+#[cfg(feature = "notify")]
+fn run_watch(repo: &Repository, options: &Options, out: &mut dyn Write, timings: &mut Timings) -> RunOutcome {
+	// Validation made sure there is a work directory.
+	let workdir = repo.workdir().unwrap();
+	let folders = if options.subdirs.is_empty() { vec![workdir.to_path_buf()] } else { options.subdirs.iter().map(|subdir| workdir.join(subdir)).collect() };
+	let mut outcome = RunOutcome::default();
+	let watched = watch::watch(&folders, || {
+		// Only the results of the current state are of interest, thus clear the terminal first.
+		if io::stdout().is_terminal() {
+			write!(out, "\x1b[2J\x1b[H").expect("Failed to write output.");
+		}
+		let diff = argument_parsing::create_workdir_diff(repo, options);
+		timings.lap("creating diff");
+		outcome = run(repo, options, &diff, &DiffTarget::WorkDirectory, out, &mut [], timings);
+	});
+	if let Err(error) = watched {
+		eprintln!("Failed to watch the work directory for changes: {error}");
+		process::exit(1);
+	}
+	outcome
+}
+
+// Rejected while parsing the arguments.
+#[cfg(not(feature = "notify"))]
+fn run_watch(_repo: &Repository, _options: &Options, _out: &mut dyn Write, _timings: &mut Timings) -> RunOutcome {
+	unreachable!("Option '--watch' needs the 'notify' feature.");
+}
+
+// Only the deltas which passed the filters, as Git reported them. Helps with finding out why an asset does not show up.
+fn list_files(out: &mut dyn Write, diffs: &[DiffDelta]) -> io::Result<()> {
+	for delta in diffs {
+		let old_path = delta.old_file().path();
+		let new_path = delta.new_file().path();
+		write!(out, "{:<11} ", format!("{:?}", delta.status()))?;
+		match (old_path, new_path) {
+			(Some(old_path), Some(new_path)) if old_path != new_path => writeln!(out, "{} -> {}", old_path.display(), new_path.display())?,
+			(Some(path), _) | (None, Some(path)) => writeln!(out, "{}", path.display())?,
+			// Not expected from libgit2, but the analysis would fail on it.
+			(None, None) => writeln!(out, "(no path)")?,
+		}
+	}
+	Ok(())
+}
+
+fn write_results(out: &mut dyn Write, options: &Options, format: OutputFormat, result: &AnalysisResult, print_options: &TreePrintOptions) -> io::Result<()> {
+	match format {
+		OutputFormat::Tree if options.summary => writeln!(out, "Summary: {}", result.uuid_storage.summary()),
+		OutputFormat::Tree if options.by_extension => result.uuid_storage.write_by_extension(out),
+		OutputFormat::Tree if options.group_moves => {
+			writeln!(out, ansi!("«lb»Moves«»:"))?;
+			result.uuid_storage.write_move_groups(out, options.verbose)
+		}
+		OutputFormat::Tree => print_results(out, options, result, print_options),
+		// Never colored, no matter if a terminal is attached.
+		OutputFormat::Json => json::write_json(out, &result.uuid_storage, &result.replaced, &result.diagnostics),
+		OutputFormat::Porcelain => porcelain::write_porcelain(out, &result.uuid_storage),
+		OutputFormat::Csv => csv::write_csv(out, &result.uuid_storage),
+		OutputFormat::Dot => dot::write_dot(out, &result.uuid_storage),
+		OutputFormat::Markdown => markdown::write_markdown(out, &result.uuid_storage),
+	}
+}
+
+fn exit_with_analysis_error(error: AnalysisError) -> ! {
+	eprintln!("Failed to analyze the diff: {error}");
+	process::exit(1);
+}
+
+// Where the results go, diagnostics always go to stderr.
+fn open_output(options: &Options) -> Box<dyn Write> {
+	match &options.output {
+		Some(path) => create_output_file(path),
+		None => Box::new(io::stdout().lock()),
+	}
+}
+
+fn create_output_file(path: &Path) -> Box<dyn Write> {
+	match File::create(path) {
+		Ok(file) => Box::new(BufWriter::new(file)),
+		Err(error) => {
+			eprintln!("Could not create output file '{}': {error}", path.display());
+			process::exit(1);
+		}
+	}
+}
+
+// A GUID being the same proves that Unity sees the same asset. But when the paths have little in common, the user may know better:
+// For example a meta file got copied over to a new asset, while the old asset got deleted.
+// Rejected renames are split into removal and addition. Without any more input (end of file), the remaining renames are kept.
+fn confirm_renames(result: &mut AnalysisResult, input: &mut dyn BufRead, prompt_out: &mut dyn Write) -> io::Result<()> {
+	let unlikely_renames: Vec<_> = result.uuid_storage.renames()
+		.filter(|(_, entry)| {
+			let best_similarity = entry.removed.iter()
+				.flat_map(|old_path| entry.added.iter().map(|new_path| path_diff::similarity(old_path, new_path)))
+				.fold(0.0, f32::max);
+			best_similarity < CONFIRM_RENAME_SIMILARITY
+		})
+		.map(|(uuid, _)| *uuid)
+		.collect();
+	for uuid in unlikely_renames {
+		let entry = result.uuid_storage.lookup.get_mut(&uuid).unwrap();
+		let join = |paths: &[PathBuf]| paths.iter().map(|path| format!("'{}'", path.display())).collect::<Vec<_>>().join(", ");
+		loop {
+			write!(prompt_out, "Is {} => {} a rename of {uuid}? [Y]es, or [s]plit into removal and addition: ", join(&entry.removed), join(&entry.added))?;
+			prompt_out.flush()?;
+			let mut answer = String::new();
+			if input.read_line(&mut answer)? == 0 {
+				return Ok(());
 			}
-			_ => {
-				panic!("Cannot yet handle diff delta type of {:?}", delta.status());
+			match answer.trim().to_ascii_lowercase().as_str() {
+				"" | "y" | "yes" => break,
+				"s" | "split" | "n" | "no" => {
+					entry.is_split = true;
+					break;
+				}
+				_ => writeln!(prompt_out, "Please answer with 'y' or 's'.")?,
 			}
 		}
 	}
+	Ok(())
 }
 
-fn gather_filtered_deltas_from_diff<'a>(diff: &'a Diff<'a>) -> Vec<DiffDelta<'a>> {
-	diff.deltas().filter(|delta| {
-		let old = delta.old_file().path();
-		let new = delta.new_file().path();
-		// New/Old paths are always the same (in my case).
-		// Anyway, check if either path has the '.meta' file extension.
-		old.is_some() && old.unwrap().to_str().unwrap().ends_with(".meta")
-			|| new.is_some() && new.unwrap().to_str().unwrap().ends_with(".meta")
-	}).collect()
+fn print_conflicts(out: &mut dyn Write, result: &AnalysisResult) -> io::Result<()> {
+	let path_conflicts: Vec<_> = result.addition_tree.conflicts().iter().map(|conflict| (conflict, "added"))
+		.chain(result.removal_tree.conflicts().iter().map(|conflict| (conflict, "removed")))
+		.collect();
+	if result.collisions.is_empty() && path_conflicts.is_empty() {
+		return writeln!(out, "No GUID collisions or path conflicts.");
+	}
+	if !result.collisions.is_empty() {
+		writeln!(out, ansi!("«lr»GUID COLLISION«» - multiple files share the same Unity GUID:"))?;
+		result.uuid_storage.write_collisions(out)?;
+		writeln!(out)?;
+	}
+	if !path_conflicts.is_empty() {
+		writeln!(out, ansi!("«lr»PATH CONFLICT«» - a path got multiple Unity GUIDs:"))?;
+		for (conflict, direction) in path_conflicts {
+			writeln!(out, ansi!("  «w»{}«» got {} with {} & {}"), conflict.path.display(), direction, conflict.kept, conflict.ignored)?;
+		}
+		writeln!(out)?;
+	}
+	Ok(())
+}
+
+fn print_results(out: &mut dyn Write, options: &Options, result: &AnalysisResult, print_options: &TreePrintOptions) -> io::Result<()> {
+	let AnalysisResult { uuid_storage, addition_tree, removal_tree, .. } = result;
+	if !options.no_legend && !options.quiet {
+		// Colors are stripped with the rest of the output, the words still explain the entries.
+		writeln!(out, ansi!("Legend: «lg»ADDED«» «lr»REMOVED«» «lb»RENAMED«» «gr»UNCHANGED«» | «lb»(renamed)«» «y»(moved)«» «lr»(moved+renamed)«» | «gr»same/«lb»changed«w»/«gr»path«»"))?;
+		writeln!(out)?;
+	}
+	if options.unified {
+		writeln!(out, ansi!("«lb»Unified tree«»:"))?;
+		PathTreeStorage::write_unified_tree(out, uuid_storage, addition_tree, removal_tree, print_options)?;
+		writeln!(out)?;
+	} else {
+		// Print the two trees. This allows to check changes in both directions and makes them obvious.
+		writeln!(out, ansi!("«lr»By removal tree«»:"))?;
+		removal_tree.write_tree(out, uuid_storage, false, print_options)?;
+		writeln!(out)?;
+		
+		writeln!(out, ansi!("«lg»By addition tree«»:"))?;
+		addition_tree.write_tree(out, uuid_storage, true, print_options)?;
+		writeln!(out)?;
+	}
+	
+	if options.stats {
+		let (folders, files) = uuid_storage.count_changed_assets();
+		writeln!(out, "Changed assets: {folders} folders, {files} files")?;
+		writeln!(out)?;
+	}
+	
+	if !result.replaced.is_empty() {
+		writeln!(out, ansi!("«lr»Replaced«» - same path, but a different GUID (references to the old GUID break):"))?;
+		for replaced in &result.replaced {
+			writeln!(out, ansi!("  «w»{}«»: «lr»{}«» => «lg»{}«»"), replaced.path.display(), replaced.old_guid, replaced.new_guid)?;
+		}
+		writeln!(out)?;
+	}
+	
+	writeln!(out, ansi!("«y»Modified without GUID change«»:"))?;
+	uuid_storage.write_modified(out)?;
+	writeln!(out)?;
+	
+	let summary = uuid_storage.summary();
+	if summary.conflicts != 0 {
+		writeln!(out, ansi!("«lr»GUID COLLISION«» - multiple files share the same Unity GUID:"))?;
+		uuid_storage.write_collisions(out)?;
+		writeln!(out)?;
+	}
+	
+	writeln!(out, "Summary: {summary}")
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	use unity_engine_meta_git_differ::data::uuid_storage::ChangeKind;
+	use unity_engine_meta_git_differ::Uuid;
+	
+	fn uuid(digit: char) -> Uuid {
+		std::iter::repeat_n(digit, 32).collect::<String>().parse().unwrap()
+	}
+	
+	#[test]
+	fn only_unlikely_renames_are_confirmed() {
+		let mut result = AnalysisResult::default();
+		let mut rename = |digit: char, old_path: &str, new_path: &str| {
+			result.uuid_storage.removed(uuid(digit), PathBuf::from(old_path));
+			result.uuid_storage.added(uuid(digit), PathBuf::from(new_path));
+		};
+		// Similar enough to not ask.
+		rename('1', "Assets/A/x.png.meta", "Assets/A/y.png.meta");
+		rename('2', "Assets/Hero.png.meta", "Other/Deep/Thing.png.meta");
+		rename('3', "Weird/a.png.meta", "Strange/b.png.meta");
+		
+		// An invalid answer asks again, the end of the input keeps the remaining renames.
+		let mut prompts = Vec::new();
+		confirm_renames(&mut result, &mut io::Cursor::new("maybe\ns\n"), &mut prompts).unwrap();
+		let prompts = String::from_utf8(prompts).unwrap();
+		assert_eq!(prompts.matches("Is 'Assets/Hero.png' => 'Other/Deep/Thing.png' a rename of").count(), 2, "{prompts}");
+		assert_eq!(prompts.matches("Please answer with 'y' or 's'.\n").count(), 1, "{prompts}");
+		assert_eq!(prompts.matches("Is 'Weird/a.png' => 'Strange/b.png' a rename of").count(), 1, "{prompts}");
+		assert!(!prompts.contains("Assets/A/x.png"), "{prompts}");
+		
+		let kind = |digit: char| result.uuid_storage.lookup[&uuid(digit)].classify();
+		assert_eq!((kind('1'), kind('2'), kind('3')), (ChangeKind::Renamed, ChangeKind::Split, ChangeKind::Renamed));
+	}
+	
+	#[test]
+	fn confirmed_renames_stay_renames() {
+		let mut result = AnalysisResult::default();
+		result.uuid_storage.removed(uuid('1'), PathBuf::from("Assets/Hero.png.meta"));
+		result.uuid_storage.added(uuid('1'), PathBuf::from("Other/Deep/Thing.png.meta"));
+		confirm_renames(&mut result, &mut io::Cursor::new("Yes\n"), &mut Vec::new()).unwrap();
+		assert_eq!(result.uuid_storage.lookup[&uuid('1')].classify(), ChangeKind::Renamed);
+	}
+	
+	#[test]
+	fn meta_file_total_is_left_out_on_git_errors() {
+		let path = std::env::temp_dir().join(format!("unity-meta-diff-count-broken-{}", process::id()));
+		let _ = std::fs::remove_dir_all(&path);
+		let repo = Repository::init_bare(&path).unwrap();
+		let blob = repo.blob(b"fileFormatVersion: 2\nguid: 63079bf56d891f040a461867b5dc65cb\n").unwrap();
+		let tree_with = |name: &str, id: git2::Oid, mode: i32| {
+			let mut builder = repo.treebuilder(None).unwrap();
+			builder.insert(name, id, mode).unwrap();
+			builder.write().unwrap()
+		};
+		let folder = tree_with("x.png.meta", blob, 0o100644);
+		let root = tree_with("Assets", folder, 0o040000);
+		assert_eq!(count_meta_files_or_warn(&repo, &DiffTarget::Tree(repo.find_tree(root).unwrap()), &[]), Some(1));
+		
+		// A missing folder object only fails when walking into it. Opened again, as the objects are cached.
+		let folder = folder.to_string();
+		std::fs::remove_file(path.join("objects").join(&folder[..2]).join(&folder[2..])).unwrap();
+		let repo = Repository::open_bare(&path).unwrap();
+		assert_eq!(count_meta_files_or_warn(&repo, &DiffTarget::Tree(repo.find_tree(root).unwrap()), &[]), None);
+		
+		drop(repo);
+		std::fs::remove_dir_all(path).unwrap();
+	}
 }
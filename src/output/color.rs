@@ -0,0 +1,162 @@
+use std::env;
+use std::io::{self, IsTerminal, Write};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+#[derive(Debug, Default)]
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub enum ColorChoice {
+	// Color only when writing to a terminal and NO_COLOR is not set.
+	#[default]
+	Auto,
+	Always,
+	Never,
+}
+
+impl FromStr for ColorChoice {
+	type Err = String;
+	
+	fn from_str(input: &str) -> Result<Self, Self::Err> {
+		match input {
+			"auto" => Ok(ColorChoice::Auto),
+			"always" => Ok(ColorChoice::Always),
+			"never" => Ok(ColorChoice::Never),
+			_ => Err(format!("Unknown color mode '{input}', expected one of: auto, always, never")),
+		}
+	}
+}
+
+impl ColorChoice {
+	pub fn should_color(self, is_terminal: bool) -> bool {
+		match self {
+			ColorChoice::Always => true,
+			ColorChoice::Never => false,
+			// See https://no-color.org - an empty value does not count.
+			ColorChoice::Auto => is_terminal && env::var_os("NO_COLOR").is_none_or(|value| value.is_empty()),
+		}
+	}
+}
+
+// The ansi! macro bakes the escape sequences into the format strings at compile time.
+// Thus, color cannot be disabled at the source - instead the sequences get filtered out while writing.
+static COLOR_ENABLED: AtomicBool = AtomicBool::new(true);
+
+pub fn set_enabled(choice: ColorChoice) {
+	COLOR_ENABLED.store(choice.should_color(io::stdout().is_terminal()), Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+	COLOR_ENABLED.load(Ordering::Relaxed)
+}
+
+// Stdout, with escape sequences removed if color got disabled.
+pub fn stdout() -> Box<dyn Write> {
+	writer(io::stdout().lock(), is_enabled())
+}
+
+// Stderr, for diagnostics. Uses the same decision as stdout, as both usually end up in the same terminal.
+pub fn stderr() -> Box<dyn Write> {
+	writer(io::stderr().lock(), is_enabled())
+}
+
+pub fn writer<'a>(out: impl Write + 'a, color: bool) -> Box<dyn Write + 'a> {
+	if color {
+		Box::new(out)
+	} else {
+		Box::new(AnsiStripper::new(out))
+	}
+}
+
+#[derive(Copy, Clone)]
+enum EscapeState {
+	Text,
+	// Got ESC, expecting '['.
+	Escape,
+	// Inside "ESC [", until the final byte (0x40 to 0x7E).
+	Sequence,
+}
+
+// Writer which drops all ANSI escape sequences. Sequences may be split over multiple writes.
+pub struct AnsiStripper<W: Write> {
+	inner: W,
+	state: EscapeState,
+}
+
+impl<W: Write> AnsiStripper<W> {
+	pub fn new(inner: W) -> Self {
+		AnsiStripper {
+			inner,
+			state: EscapeState::Text,
+		}
+	}
+}
+
+impl<W: Write> Write for AnsiStripper<W> {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		let mut text = Vec::with_capacity(buf.len());
+		for &byte in buf {
+			self.state = match (self.state, byte) {
+				(EscapeState::Text, 0x1b) => EscapeState::Escape,
+				(EscapeState::Text, _) => {
+					text.push(byte);
+					EscapeState::Text
+				}
+				(EscapeState::Escape, b'[') => EscapeState::Sequence,
+				// Not a control sequence, the escape is dropped on its own.
+				(EscapeState::Escape, _) => {
+					text.push(byte);
+					EscapeState::Text
+				}
+				(EscapeState::Sequence, 0x40..=0x7e) => EscapeState::Text,
+				(EscapeState::Sequence, _) => EscapeState::Sequence,
+			};
+		}
+		self.inner.write_all(&text)?;
+		Ok(buf.len())
+	}
+	
+	fn flush(&mut self) -> io::Result<()> {
+		self.inner.flush()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	#[test]
+	fn color_choice_decision() {
+		assert!(ColorChoice::Always.should_color(false));
+		assert!(!ColorChoice::Never.should_color(true));
+		// Not a terminal, NO_COLOR does not matter then.
+		assert!(!ColorChoice::Auto.should_color(false));
+		assert_eq!("never".parse(), Ok(ColorChoice::Never));
+		assert!("sometimes".parse::<ColorChoice>().is_err());
+	}
+	
+	#[test]
+	fn stripper_removes_escape_sequences() {
+		let mut output = Vec::new();
+		let mut out = writer(&mut output, false);
+		write!(out, "\x1b[97mAssets\x1b[0m: \x1b[92mADDED\x1b[0m").unwrap();
+		drop(out);
+		assert_eq!(String::from_utf8(output).unwrap(), "Assets: ADDED");
+	}
+	
+	#[test]
+	fn stripper_handles_sequences_split_over_writes() {
+		let mut output = Vec::new();
+		let mut stripper = AnsiStripper::new(&mut output);
+		stripper.write_all(b"a\x1b").unwrap();
+		stripper.write_all(b"[9").unwrap();
+		stripper.write_all(b"1mb").unwrap();
+		assert_eq!(output, b"ab");
+	}
+	
+	#[test]
+	fn enabled_writer_keeps_escape_sequences() {
+		let mut output = Vec::new();
+		write!(writer(&mut output, true), "\x1b[92mADDED\x1b[0m").unwrap();
+		assert_eq!(output, b"\x1b[92mADDED\x1b[0m");
+	}
+}
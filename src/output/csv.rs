@@ -0,0 +1,78 @@
+use crate::data::uuid_storage::{ChangeKind, UuidStorage};
+use std::io::{self, Write};
+use std::path::Path;
+
+// Quoting as described by RFC 4180: only when needed, quotes inside get doubled.
+fn write_field(out: &mut dyn Write, text: &str) -> io::Result<()> {
+	if text.contains([',', '"', '\n', '\r']) {
+		write!(out, "\"{}\"", text.replace('"', "\"\""))
+	} else {
+		write!(out, "{text}")
+	}
+}
+
+fn write_row(out: &mut dyn Write, kind: &str, guid: &str, old_path: Option<&Path>, new_path: Option<&Path>) -> io::Result<()> {
+	write!(out, "{kind},{guid},")?;
+	write_field(out, &old_path.map(|path| path.to_string_lossy()).unwrap_or_default())?;
+	write!(out, ",")?;
+	write_field(out, &new_path.map(|path| path.to_string_lossy()).unwrap_or_default())?;
+	writeln!(out)
+}
+
+// Same records as the porcelain format, but for spreadsheets. Paths which do not exist on one side stay empty.
+pub fn write_csv(out: &mut dyn Write, storage: &UuidStorage) -> io::Result<()> {
+	writeln!(out, "kind,guid,old_path,new_path")?;
+	for record in storage.records() {
+		let kind = match record.kind {
+			ChangeKind::Added => "added",
+			ChangeKind::Removed => "removed",
+			_ => "renamed",
+		};
+		write_row(out, kind, &record.uuid.to_string(), record.old_path, record.new_path)?;
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	use std::path::PathBuf;
+	
+	fn uuid(digit: char) -> crate::Uuid {
+		std::iter::repeat_n(digit, 32).collect::<String>().parse().unwrap()
+	}
+	
+	fn written(storage: &UuidStorage) -> String {
+		let mut output = Vec::new();
+		write_csv(&mut output, storage).unwrap();
+		String::from_utf8(output).unwrap()
+	}
+	
+	#[test]
+	fn paths_with_commas_and_quotes_are_quoted() {
+		let mut storage = UuidStorage::default();
+		storage.removed(uuid('1'), PathBuf::from("Assets/Hero, old.png.meta"));
+		storage.added(uuid('1'), PathBuf::from("Assets/The \"Hero\".png.meta"));
+		assert_eq!(written(&storage), format!(
+			"kind,guid,old_path,new_path\nrenamed,{},\"Assets/Hero, old.png\",\"Assets/The \"\"Hero\"\".png\"\n",
+			uuid('1'),
+		));
+	}
+	
+	#[test]
+	fn row_per_change_kind() {
+		let mut storage = UuidStorage::default();
+		storage.added(uuid('1'), PathBuf::from("Assets/Added.png.meta"));
+		storage.removed(uuid('2'), PathBuf::from("Assets/Removed.png.meta"));
+		assert_eq!(written(&storage), format!(
+			"kind,guid,old_path,new_path\nadded,{},,Assets/Added.png\nremoved,{},Assets/Removed.png,\n",
+			uuid('1'), uuid('2'),
+		));
+	}
+	
+	#[test]
+	fn only_the_header_for_no_changes() {
+		assert_eq!(written(&UuidStorage::default()), "kind,guid,old_path,new_path\n");
+	}
+}
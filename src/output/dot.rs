@@ -0,0 +1,70 @@
+use crate::data::uuid_storage::UuidStorage;
+use std::io::{self, Write};
+use std::path::Path;
+
+// DOT strings are double-quoted, only quotes and backslashes need escaping.
+fn quote(path: &Path) -> String {
+	format!("\"{}\"", path.to_string_lossy().replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+// A Graphviz graph, renames are edges from the old to the new path. Pure additions and removals are standalone nodes.
+// Render for example with: dot -Tsvg changes.dot -o changes.svg
+pub fn write_dot(out: &mut dyn Write, storage: &UuidStorage) -> io::Result<()> {
+	writeln!(out, "digraph meta_changes {{")?;
+	writeln!(out, "\trankdir=LR;")?;
+	writeln!(out, "\tnode [shape=box];")?;
+	for record in storage.records() {
+		let uuid = record.uuid;
+		match (record.old_path, record.new_path) {
+			(Some(old_path), Some(new_path)) => writeln!(out, "\t{} -> {} [label=\"{uuid}\"];", quote(old_path), quote(new_path))?,
+			(None, Some(path)) => writeln!(out, "\t{} [color=green, tooltip=\"{uuid}\"];", quote(path))?,
+			(Some(path), None) => writeln!(out, "\t{} [color=red, tooltip=\"{uuid}\"];", quote(path))?,
+			(None, None) => {}
+		}
+	}
+	writeln!(out, "}}")
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	use std::path::PathBuf;
+	
+	fn uuid(digit: char) -> crate::Uuid {
+		std::iter::repeat_n(digit, 32).collect::<String>().parse().unwrap()
+	}
+	
+	fn written(storage: &UuidStorage) -> String {
+		let mut output = Vec::new();
+		write_dot(&mut output, storage).unwrap();
+		String::from_utf8(output).unwrap()
+	}
+	
+	#[test]
+	fn renames_are_edges_and_the_rest_are_nodes() {
+		let mut storage = UuidStorage::default();
+		storage.added(uuid('1'), PathBuf::from("Assets/Added.png.meta"));
+		storage.removed(uuid('2'), PathBuf::from("Assets/Removed.png.meta"));
+		storage.removed(uuid('3'), PathBuf::from("Assets/A/x.png.meta"));
+		storage.added(uuid('3'), PathBuf::from("Assets/B/x.png.meta"));
+		storage.modified(uuid('4'), PathBuf::from("Assets/Modified.png.meta"));
+		assert_eq!(written(&storage), format!(
+			"digraph meta_changes {{\n\trankdir=LR;\n\tnode [shape=box];\n\
+			\t\"Assets/Added.png\" [color=green, tooltip=\"{}\"];\n\
+			\t\"Assets/Removed.png\" [color=red, tooltip=\"{}\"];\n\
+			\t\"Assets/A/x.png\" -> \"Assets/B/x.png\" [label=\"{}\"];\n}}\n",
+			uuid('1'), uuid('2'), uuid('3'),
+		));
+	}
+	
+	#[test]
+	fn quotes_and_backslashes_are_escaped() {
+		assert_eq!(quote(Path::new("Assets/\"Quoted\"\\x.png")), "\"Assets/\\\"Quoted\\\"\\\\x.png\"");
+	}
+	
+	#[test]
+	fn empty_graph_for_no_changes() {
+		assert_eq!(written(&UuidStorage::default()), "digraph meta_changes {\n\trankdir=LR;\n\tnode [shape=box];\n}\n");
+	}
+}
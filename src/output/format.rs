@@ -0,0 +1,40 @@
+use std::str::FromStr;
+
+#[derive(Debug, Default)]
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub enum OutputFormat {
+	// The colored trees meant for humans.
+	#[default]
+	Tree,
+	Json,
+	// One line per change, for shell pipelines.
+	Porcelain,
+	Csv,
+	// Graphviz graph of the renames.
+	Dot,
+	// Report for pull request descriptions.
+	Markdown,
+}
+
+impl FromStr for OutputFormat {
+	type Err = String;
+	
+	fn from_str(input: &str) -> Result<Self, Self::Err> {
+		match input {
+			"tree" => Ok(OutputFormat::Tree),
+			"json" => Ok(OutputFormat::Json),
+			"porcelain" => Ok(OutputFormat::Porcelain),
+			"csv" => Ok(OutputFormat::Csv),
+			"dot" => Ok(OutputFormat::Dot),
+			"markdown" | "md" => Ok(OutputFormat::Markdown),
+			_ => Err(format!("Unknown output format '{input}', expected one of: tree, json, porcelain, csv, dot, markdown")),
+		}
+	}
+}
+
+impl OutputFormat {
+	// Machine-readable formats must not contain anything but the results on stdout.
+	pub fn is_machine_readable(self) -> bool {
+		self != OutputFormat::Tree
+	}
+}
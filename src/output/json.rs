@@ -0,0 +1,195 @@
+use crate::analysis::{AnalysisResult, ReplacedAsset};
+use crate::data::path_tree_storage::PathConflict;
+use crate::data::uuid_storage::{GuidCollision, UuidStorage};
+use crate::diagnostics::Diagnostics;
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+// There is only a handful of value types to write, thus no need for a JSON library.
+fn write_string(out: &mut dyn Write, text: &str) -> io::Result<()> {
+	write!(out, "\"")?;
+	for character in text.chars() {
+		match character {
+			'"' => write!(out, "\\\"")?,
+			'\\' => write!(out, "\\\\")?,
+			'\n' => write!(out, "\\n")?,
+			'\r' => write!(out, "\\r")?,
+			'\t' => write!(out, "\\t")?,
+			c if c.is_control() => write!(out, "\\u{:04x}", c as u32)?,
+			c => write!(out, "{c}")?,
+		}
+	}
+	write!(out, "\"")
+}
+
+fn write_paths(out: &mut dyn Write, paths: &[PathBuf]) -> io::Result<()> {
+	write!(out, "[")?;
+	for (index, path) in paths.iter().enumerate() {
+		if index != 0 {
+			write!(out, ", ")?;
+		}
+		write_string(out, &path.to_string_lossy())?;
+	}
+	write!(out, "]")
+}
+
+// Writes the elements of a JSON array, one object per line. The closure writes the fields of one object.
+fn write_objects<T>(
+	out: &mut dyn Write,
+	items: impl Iterator<Item = T>,
+	write_fields: impl Fn(&mut dyn Write, T) -> io::Result<()>,
+) -> io::Result<()> {
+	let mut is_first = true;
+	for item in items {
+		if !is_first {
+			writeln!(out, ",")?;
+		}
+		is_first = false;
+		write!(out, "\t\t{{")?;
+		write_fields(out, item)?;
+		write!(out, "}}")?;
+	}
+	if !is_first {
+		writeln!(out)?;
+	}
+	Ok(())
+}
+
+// Paths are arrays, as a GUID shared by multiple files (collision) has more than one path per direction.
+// Paths which got a different GUID are listed again in "replaced", their GUIDs are part of "added" and "removed" too.
+// The diagnostics end up in "warnings", instead of being printed to stderr.
+pub fn write_json(out: &mut dyn Write, storage: &UuidStorage, replaced: &[ReplacedAsset], diagnostics: &Diagnostics) -> io::Result<()> {
+	writeln!(out, "{{")?;
+	
+	writeln!(out, "\t\"added\": [")?;
+	write_objects(out, storage.pure_additions(), |out, (uuid, entry)| {
+		write!(out, "\"guid\": \"{uuid}\", \"folder\": {}, \"paths\": ", entry.is_folder)?;
+		write_paths(out, &entry.added)
+	})?;
+	writeln!(out, "\t],")?;
+	
+	writeln!(out, "\t\"removed\": [")?;
+	write_objects(out, storage.pure_removals(), |out, (uuid, entry)| {
+		write!(out, "\"guid\": \"{uuid}\", \"folder\": {}, \"paths\": ", entry.is_folder)?;
+		write_paths(out, &entry.removed)
+	})?;
+	writeln!(out, "\t],")?;
+	
+	writeln!(out, "\t\"renamed\": [")?;
+	write_objects(out, storage.renames(), |out, (uuid, entry)| {
+		write!(out, "\"guid\": \"{uuid}\", \"folder\": {}, \"old_paths\": ", entry.is_folder)?;
+		write_paths(out, &entry.removed)?;
+		write!(out, ", \"new_paths\": ")?;
+		write_paths(out, &entry.added)
+	})?;
+	writeln!(out, "\t],")?;
+	
+	writeln!(out, "\t\"replaced\": [")?;
+	write_objects(out, replaced.iter(), |out, replaced| {
+		write!(out, "\"path\": ")?;
+		write_string(out, &replaced.path.to_string_lossy())?;
+		write!(out, ", \"old_guid\": \"{}\", \"new_guid\": \"{}\"", replaced.old_guid, replaced.new_guid)
+	})?;
+	writeln!(out, "\t],")?;
+	
+	writeln!(out, "\t\"warnings\": [")?;
+	write_objects(out, diagnostics.iter(), |out, diagnostic| {
+		write!(out, "\"severity\": \"{}\", \"message\": ", diagnostic.severity)?;
+		write_string(out, &diagnostic.message)?;
+		write!(out, ", \"paths\": ")?;
+		write_paths(out, &diagnostic.paths)
+	})?;
+	writeln!(out, "\t]")?;
+	
+	writeln!(out, "}}")
+}
+
+// Only the problems: GUIDs used by multiple paths, and paths which got two GUIDs.
+pub fn write_conflicts_json(out: &mut dyn Write, result: &AnalysisResult) -> io::Result<()> {
+	enum Conflict<'a> {
+		Collision(&'a GuidCollision),
+		Path(&'a PathConflict, bool),
+	}
+	let direction = |is_adding: bool| if is_adding { "added" } else { "removed" };
+	let conflicts = result.collisions.iter().map(Conflict::Collision)
+		.chain(result.addition_tree.conflicts().iter().map(|conflict| Conflict::Path(conflict, true)))
+		.chain(result.removal_tree.conflicts().iter().map(|conflict| Conflict::Path(conflict, false)));
+	writeln!(out, "{{")?;
+	
+	writeln!(out, "\t\"conflicts\": [")?;
+	write_objects(out, conflicts, |out, conflict| match conflict {
+		Conflict::Collision(collision) => {
+			write!(out, "\"kind\": \"guid_collision\", \"direction\": \"{}\", \"guid\": \"{}\", \"paths\": ", direction(collision.is_adding), collision.uuid)?;
+			write_paths(out, &collision.paths)
+		}
+		Conflict::Path(conflict, is_adding) => {
+			write!(out, "\"kind\": \"path_conflict\", \"direction\": \"{}\", \"guids\": [\"{}\", \"{}\"], \"path\": ", direction(is_adding), conflict.kept, conflict.ignored)?;
+			write_string(out, &conflict.path.to_string_lossy())
+		}
+	})?;
+	writeln!(out, "\t]")?;
+	
+	writeln!(out, "}}")
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	use crate::data::uuid::test_helpers::uuid;
+	use serde_json::Value;
+	
+	fn parsed_json(storage: &UuidStorage, replaced: &[ReplacedAsset], diagnostics: &Diagnostics) -> Value {
+		let mut output = Vec::new();
+		write_json(&mut output, storage, replaced, diagnostics).unwrap();
+		serde_json::from_slice(&output).unwrap()
+	}
+	
+	#[test]
+	fn rename_has_both_paths_and_the_guid() {
+		let mut storage = UuidStorage::default();
+		storage.removed(uuid('1'), PathBuf::from("Assets/Old/Hero.png.meta"));
+		storage.added(uuid('1'), PathBuf::from("Assets/New/Hero.png.meta"));
+		storage.added(uuid('2'), PathBuf::from("Assets/Added.png.meta"));
+		storage.removed(uuid('3'), PathBuf::from("Assets/Removed.png.meta"));
+		storage.mark_folder(uuid('3'));
+		let json = parsed_json(&storage, &[], &Diagnostics::default());
+		
+		assert_eq!(json["renamed"], serde_json::json!([{
+			"guid": uuid('1').to_string(), "folder": false,
+			"old_paths": ["Assets/Old/Hero.png"], "new_paths": ["Assets/New/Hero.png"],
+		}]));
+		assert_eq!(json["added"], serde_json::json!([{"guid": uuid('2').to_string(), "folder": false, "paths": ["Assets/Added.png"]}]));
+		assert_eq!(json["removed"], serde_json::json!([{"guid": uuid('3').to_string(), "folder": true, "paths": ["Assets/Removed.png"]}]));
+	}
+	
+	#[test]
+	fn no_changes_are_empty_arrays() {
+		let json = parsed_json(&UuidStorage::default(), &[], &Diagnostics::default());
+		for key in ["added", "removed", "renamed", "replaced", "warnings"] {
+			assert_eq!(json[key], serde_json::json!([]), "{key}");
+		}
+	}
+	
+	#[test]
+	fn special_characters_are_escaped() {
+		let mut storage = UuidStorage::default();
+		let path = "Assets/\"Quoted\" \\ Tab\t\u{1}.png";
+		storage.added(uuid('1'), PathBuf::from(format!("{path}.meta")));
+		let mut diagnostics = Diagnostics::default();
+		diagnostics.warning("Line one\nLine two".to_owned(), vec![PathBuf::from(path)]);
+		let json = parsed_json(&storage, &[], &diagnostics);
+		assert_eq!(json["added"][0]["paths"][0], path);
+		assert_eq!(json["warnings"][0]["message"], "Line one\nLine two");
+		assert_eq!(json["warnings"][0]["severity"], "warning");
+	}
+	
+	#[test]
+	fn replaced_assets_are_listed() {
+		let replaced = [ReplacedAsset { path: PathBuf::from("Assets/x.png"), old_guid: uuid('1'), new_guid: uuid('2') }];
+		let json = parsed_json(&UuidStorage::default(), &replaced, &Diagnostics::default());
+		assert_eq!(json["replaced"], serde_json::json!([{
+			"path": "Assets/x.png", "old_guid": uuid('1').to_string(), "new_guid": uuid('2').to_string(),
+		}]));
+	}
+}
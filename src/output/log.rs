@@ -0,0 +1,56 @@
+use crate::diagnostics::{Diagnostics, Severity};
+use crate::output::color;
+use ecc_ansi_lib::ansi;
+use std::fmt::Arguments;
+use std::io::{self, IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// Informational and warning messages go to stderr, so that they never end up in the results.
+// Hard errors do not use this, they are always printed.
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+pub fn set_quiet(quiet: bool) {
+	QUIET.store(quiet, Ordering::Relaxed);
+}
+
+pub fn is_quiet() -> bool {
+	QUIET.load(Ordering::Relaxed)
+}
+
+fn write(label: &str, arguments: Arguments) {
+	if is_quiet() {
+		return;
+	}
+	writeln!(color::stderr(), "{label}{arguments}").expect("Failed to write to stderr.");
+}
+
+// Progress and context, without a label.
+pub fn message(arguments: Arguments) {
+	write("", arguments);
+}
+
+pub fn info(arguments: Arguments) {
+	write(ansi!("«y»INFO:«» "), arguments);
+}
+
+pub fn warning(arguments: Arguments) {
+	write(ansi!("«y»WARNING:«» "), arguments);
+}
+
+pub fn diagnostics(diagnostics: &Diagnostics) {
+	for diagnostic in diagnostics.iter() {
+		match diagnostic.severity {
+			Severity::Info => info(format_args!("{}", diagnostic.message)),
+			Severity::Warning => warning(format_args!("{}", diagnostic.message)),
+		}
+	}
+}
+
+// A single line, which gets overwritten with every update. Only shown in a terminal - a log file should not get flooded.
+pub fn progress(done: usize, total: usize) {
+	if is_quiet() || !io::stderr().is_terminal() {
+		return;
+	}
+	let end = if done == total { "\n" } else { "" };
+	write!(color::stderr(), "\rReading meta files: {done}/{total}{end}").expect("Failed to write to stderr.");
+}
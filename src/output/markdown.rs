@@ -0,0 +1,95 @@
+use crate::data::uuid::Uuid;
+use crate::data::uuid_storage::{UuidStorage, UuidStorageEntry};
+use std::io::{self, Write};
+use std::path::Path;
+
+// Paths may contain characters, which Markdown would turn into formatting, links or headings. A backslash keeps them literal.
+fn escape(path: &Path) -> String {
+	let mut escaped = String::new();
+	for character in path.to_string_lossy().chars() {
+		if "\\`*_[]<>#|~".contains(character) {
+			escaped.push('\\');
+		}
+		escaped.push(character);
+	}
+	escaped
+}
+
+fn write_section<'a>(
+	out: &mut dyn Write,
+	title: &str,
+	entries: impl Iterator<Item = (&'a Uuid, &'a UuidStorageEntry)>,
+	write_bullets: impl Fn(&mut dyn Write, &Uuid, &UuidStorageEntry) -> io::Result<()>,
+) -> io::Result<()> {
+	writeln!(out, "### {title}")?;
+	writeln!(out)?;
+	let mut is_empty = true;
+	for (uuid, entry) in entries {
+		is_empty = false;
+		write_bullets(out, uuid, entry)?;
+	}
+	if is_empty {
+		writeln!(out, "_None_")?;
+	}
+	writeln!(out)
+}
+
+// A report meant to be pasted into pull request descriptions or comments. The GUIDs are kept as code, to not distract.
+pub fn write_markdown(out: &mut dyn Write, storage: &UuidStorage) -> io::Result<()> {
+	write_section(out, "Added", storage.pure_additions(), |out, uuid, entry| {
+		for path in &entry.added {
+			writeln!(out, "- {} `{uuid}`", escape(path))?;
+		}
+		Ok(())
+	})?;
+	write_section(out, "Removed", storage.pure_removals(), |out, uuid, entry| {
+		for path in &entry.removed {
+			writeln!(out, "- {} `{uuid}`", escape(path))?;
+		}
+		Ok(())
+	})?;
+	write_section(out, "Renamed", storage.renames(), |out, uuid, entry| {
+		for old_path in &entry.removed {
+			for new_path in &entry.added {
+				writeln!(out, "- {} → {} `{uuid}`", escape(old_path), escape(new_path))?;
+			}
+		}
+		Ok(())
+	})?;
+	writeln!(out, "**Summary:** {}", storage.summary())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	use std::path::PathBuf;
+	
+	fn uuid(digit: char) -> Uuid {
+		std::iter::repeat_n(digit, 32).collect::<String>().parse().unwrap()
+	}
+	
+	fn written(storage: &UuidStorage) -> String {
+		let mut output = Vec::new();
+		write_markdown(&mut output, storage).unwrap();
+		String::from_utf8(output).unwrap()
+	}
+	
+	#[test]
+	fn sections_with_bullets() {
+		let mut storage = UuidStorage::default();
+		storage.added(uuid('1'), PathBuf::from("Assets/Added.png.meta"));
+		storage.removed(uuid('3'), PathBuf::from("Assets/A/x.png.meta"));
+		storage.added(uuid('3'), PathBuf::from("Assets/B/x.png.meta"));
+		assert_eq!(written(&storage), format!(
+			"### Added\n\n- Assets/Added.png `{}`\n\n### Removed\n\n_None_\n\n### Renamed\n\n- Assets/A/x.png → Assets/B/x.png `{}`\n\n**Summary:** {}\n",
+			uuid('1'), uuid('3'), storage.summary(),
+		));
+	}
+	
+	#[test]
+	fn special_characters_are_escaped() {
+		assert_eq!(escape(Path::new("Assets/_Hidden_/*x*[1]#2.png")), "Assets/\\_Hidden\\_/\\*x\\*\\[1\\]\\#2.png");
+		assert_eq!(escape(Path::new("Assets/Plain name.png")), "Assets/Plain name.png");
+	}
+}
@@ -0,0 +1,115 @@
+use crate::data::uuid_storage::{ChangeKind, UuidStorage};
+use std::io::{self, Write};
+use std::path::Path;
+
+// One record per line, ordered by GUID:
+//  A <guid> <path>
+//  D <guid> <path>
+//  R <guid> <old path>\t<new path>
+// A GUID shared by multiple files (collision) results in one line per path (or path pair for renames).
+// Paths with a tab, line break, quote, backslash or other control character get quoted like Git does (with core.quotePath off):
+// Inside double quotes, with C-style escapes. Thus every line stays one record.
+pub fn write_porcelain(out: &mut dyn Write, storage: &UuidStorage) -> io::Result<()> {
+	for record in storage.records() {
+		let letter = match record.kind {
+			ChangeKind::Added => 'A',
+			ChangeKind::Removed => 'D',
+			_ => 'R',
+		};
+		let paths: Vec<_> = record.old_path.into_iter().chain(record.new_path).map(quote_path).collect();
+		writeln!(out, "{letter} {} {}", record.uuid, paths.join("\t"))?;
+	}
+	Ok(())
+}
+
+fn quote_path(path: &Path) -> String {
+	let path = path.to_string_lossy();
+	if !path.chars().any(|char| char == '"' || char == '\\' || char.is_ascii_control()) {
+		return path.into_owned();
+	}
+	let mut quoted = String::from("\"");
+	for char in path.chars() {
+		match char {
+			'"' => quoted.push_str("\\\""),
+			'\\' => quoted.push_str("\\\\"),
+			'\t' => quoted.push_str("\\t"),
+			'\n' => quoted.push_str("\\n"),
+			'\r' => quoted.push_str("\\r"),
+			char if char.is_ascii_control() => quoted.push_str(&format!("\\{:03o}", char as u8)),
+			char => quoted.push(char),
+		}
+	}
+	quoted.push('"');
+	quoted
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	use std::path::PathBuf;
+	
+	fn uuid(digit: char) -> crate::Uuid {
+		std::iter::repeat_n(digit, 32).collect::<String>().parse().unwrap()
+	}
+	
+	fn written(storage: &UuidStorage) -> String {
+		let mut output = Vec::new();
+		write_porcelain(&mut output, storage).unwrap();
+		String::from_utf8(output).unwrap()
+	}
+	
+	#[test]
+	fn line_per_change_kind() {
+		let mut storage = UuidStorage::default();
+		storage.added(uuid('1'), PathBuf::from("Assets/Added.png.meta"));
+		storage.removed(uuid('2'), PathBuf::from("Assets/Removed.png.meta"));
+		storage.removed(uuid('3'), PathBuf::from("Assets/Old name.png.meta"));
+		storage.added(uuid('3'), PathBuf::from("Assets/New name.png.meta"));
+		// Modified meta files keep their GUID and path, nothing to report.
+		storage.modified(uuid('4'), PathBuf::from("Assets/Modified.png.meta"));
+		assert_eq!(written(&storage), format!(
+			"A {} Assets/Added.png\nD {} Assets/Removed.png\nR {} Assets/Old name.png\tAssets/New name.png\n",
+			uuid('1'), uuid('2'), uuid('3'),
+		));
+	}
+	
+	#[test]
+	fn duplicated_rename_has_a_line_per_path_pair() {
+		let mut storage = UuidStorage::default();
+		storage.removed(uuid('1'), PathBuf::from("Assets/x.png.meta"));
+		storage.added(uuid('1'), PathBuf::from("Assets/A/x.png.meta"));
+		storage.added(uuid('1'), PathBuf::from("Assets/B/x.png.meta"));
+		assert_eq!(written(&storage), format!(
+			"R {0} Assets/x.png\tAssets/A/x.png\nR {0} Assets/x.png\tAssets/B/x.png\n",
+			uuid('1'),
+		));
+	}
+	
+	#[test]
+	fn split_rename_is_a_removal_and_an_addition() {
+		let mut storage = UuidStorage::default();
+		storage.removed(uuid('1'), PathBuf::from("Assets/A/x.png.meta"));
+		storage.added(uuid('1'), PathBuf::from("Assets/B/x.png.meta"));
+		storage.lookup.get_mut(&uuid('1')).unwrap().is_split = true;
+		assert_eq!(written(&storage), format!("A {0} Assets/B/x.png\nD {0} Assets/A/x.png\n", uuid('1')));
+	}
+	
+	#[test]
+	fn nothing_for_no_changes() {
+		assert_eq!(written(&UuidStorage::default()), "");
+	}
+	
+	#[test]
+	fn paths_with_tabs_and_line_breaks_are_quoted() {
+		let mut storage = UuidStorage::default();
+		storage.added(uuid('1'), PathBuf::from("Assets/Tab\there.png.meta"));
+		storage.removed(uuid('2'), PathBuf::from("Assets/Line\nbreak \"quoted\".png.meta"));
+		storage.added(uuid('2'), PathBuf::from("Assets/Back\\slash\u{7}.png.meta"));
+		storage.added(uuid('3'), PathBuf::from("Assets/Plain ünïcode.png.meta"));
+		assert_eq!(written(&storage), format!(
+			"A {} \"Assets/Tab\\there.png\"\nR {} \"Assets/Line\\nbreak \\\"quoted\\\".png\"\t\"Assets/Back\\\\slash\\007.png\"\nA {} Assets/Plain ünïcode.png\n",
+			uuid('1'), uuid('2'), uuid('3'),
+		));
+	}
+}
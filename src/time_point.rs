@@ -0,0 +1,116 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Seconds since the Unix epoch. Either an ISO date in UTC ('2024-05-01', '2024-05-01T12:30' or '2024-05-01T12:30:00Z'),
+// or a duration back from now ('30s', '90m', '12h', '7d', '2w').
+pub fn parse_time_point(input: &str) -> Result<i64, String> {
+	let error = || format!("Invalid date or duration '{input}', expected for example '2024-05-01', '2024-05-01T12:30:00' or '7d'.");
+	if let Some(seconds) = parse_duration(input) {
+		let now = SystemTime::now().duration_since(UNIX_EPOCH).expect("System time is before 1970.").as_secs() as i64;
+		return Ok(now - seconds);
+	}
+	parse_iso_date(input).ok_or_else(error)
+}
+
+fn parse_duration(input: &str) -> Option<i64> {
+	let unit_seconds = match input.chars().last()? {
+		's' => 1,
+		'm' => 60,
+		'h' => 60 * 60,
+		'd' => 24 * 60 * 60,
+		'w' => 7 * 24 * 60 * 60,
+		_ => return None,
+	};
+	let amount: i64 = input[..input.len() - 1].parse().ok().filter(|amount| *amount >= 0)?;
+	amount.checked_mul(unit_seconds)
+}
+
+fn parse_iso_date(input: &str) -> Option<i64> {
+	let input = input.strip_suffix('Z').unwrap_or(input);
+	let (date, time) = match input.split_once(['T', ' ']) {
+		Some((date, time)) => (date, Some(time)),
+		None => (input, None),
+	};
+	
+	let mut date_parts = date.split('-');
+	let year: i64 = date_parts.next()?.parse().ok()?;
+	let month: i64 = date_parts.next()?.parse().ok()?;
+	let day: i64 = date_parts.next()?.parse().ok()?;
+	if date_parts.next().is_some() || !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) {
+		return None;
+	}
+	
+	let mut seconds_of_day = 0;
+	if let Some(time) = time {
+		let mut time_parts = time.split(':');
+		let hour: i64 = time_parts.next()?.parse().ok()?;
+		let minute: i64 = time_parts.next()?.parse().ok()?;
+		let second: i64 = time_parts.next().map_or(Some(0), |second| second.parse().ok())?;
+		if time_parts.next().is_some() || hour > 23 || minute > 59 || second > 59 {
+			return None;
+		}
+		seconds_of_day = hour * 3600 + minute * 60 + second;
+	}
+	Some(days_from_civil(year, month, day) * 24 * 60 * 60 + seconds_of_day)
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+	match month {
+		2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+		2 => 28,
+		4 | 6 | 9 | 11 => 30,
+		_ => 31,
+	}
+}
+
+// Days since 1970-01-01 in the proleptic Gregorian calendar, see https://howardhinnant.github.io/date_algorithms.html
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+	// Years start in March, so that the leap day is the last day of the year.
+	let year = if month <= 2 { year - 1 } else { year };
+	let era = year.div_euclid(400);
+	let year_of_era = year - era * 400;
+	let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+	let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+	era * 146097 + day_of_era - 719468
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	const DAY: i64 = 24 * 60 * 60;
+	
+	#[test]
+	fn iso_dates() {
+		assert_eq!(parse_time_point("1970-01-01"), Ok(0));
+		assert_eq!(parse_time_point("1970-01-02T00:00:01Z"), Ok(DAY + 1));
+		assert_eq!(parse_time_point("2024-05-01"), Ok(1714521600));
+		assert_eq!(parse_time_point("2024-05-01T12:30"), Ok(1714521600 + 12 * 3600 + 30 * 60));
+		assert_eq!(parse_time_point("2024-05-01 12:30:15"), Ok(1714521600 + 12 * 3600 + 30 * 60 + 15));
+		// Leap days, including the century rules.
+		assert_eq!(parse_time_point("2024-03-01").unwrap() - parse_time_point("2024-02-29").unwrap(), DAY);
+		assert_eq!(parse_time_point("2000-03-01").unwrap() - parse_time_point("2000-02-28").unwrap(), 2 * DAY);
+		assert_eq!(parse_time_point("1969-12-31"), Ok(-DAY));
+	}
+	
+	#[test]
+	fn invalid_dates() {
+		for input in ["2023-02-29", "1900-02-29", "2024-13-01", "2024-04-31", "2024-05-00", "2024-05-01T24:00", "2024-05-01T12:60", "2024-05", "2024-05-01-02", "yesterday", ""] {
+			let error = parse_time_point(input).unwrap_err();
+			assert!(error.starts_with(&format!("Invalid date or duration '{input}'")), "{error}");
+		}
+	}
+	
+	#[test]
+	fn durations_go_back_from_now() {
+		let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
+		let back = |input: &str| now - parse_time_point(input).unwrap();
+		// A second might pass in between.
+		assert!((7 * DAY..=7 * DAY + 1).contains(&back("7d")));
+		assert!((2 * 7 * DAY..=2 * 7 * DAY + 1).contains(&back("2w")));
+		assert!((90 * 60..=90 * 60 + 1).contains(&back("90m")));
+		assert!((0..=1).contains(&back("0s")));
+		for input in ["-1d", "d", "7y", "1.5h", "99999999999999999w"] {
+			assert!(parse_time_point(input).is_err(), "{input}");
+		}
+	}
+}
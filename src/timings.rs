@@ -0,0 +1,85 @@
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+// Wall clock time of the phases of a run, printed with '--timings'. When disabled, the clock is not even read.
+// Phases with the same name (for example of multiple '--batch' pairs) are summed up.
+pub struct Timings {
+	// End of the previous phase, None if disabled.
+	last: Option<Instant>,
+	phases: Vec<(&'static str, Duration)>,
+}
+
+impl Timings {
+	pub fn new(enabled: bool) -> Timings {
+		Timings {
+			last: enabled.then(Instant::now),
+			phases: Vec::new(),
+		}
+	}
+	
+	// Ends the current phase now, the next phase starts.
+	pub fn lap(&mut self, phase: &'static str) {
+		if self.last.is_some() {
+			self.lap_at(phase, Instant::now());
+		}
+	}
+	
+	// Ends the current phase at an earlier point in time, for phases which end inside of other code.
+	pub fn lap_at(&mut self, phase: &'static str, end: Instant) {
+		let Some(last) = self.last else {
+			return;
+		};
+		let duration = end.saturating_duration_since(last);
+		match self.phases.iter_mut().find(|(name, _)| *name == phase) {
+			Some((_, total)) => *total += duration,
+			None => self.phases.push((phase, duration)),
+		}
+		self.last = Some(end);
+	}
+	
+	pub fn write(&self, out: &mut dyn Write) -> io::Result<()> {
+		if self.last.is_none() {
+			return Ok(());
+		}
+		writeln!(out, "Timings:")?;
+		let mut total = Duration::ZERO;
+		for (phase, duration) in &self.phases {
+			writeln!(out, "  {phase:<18} {:>10.3} ms", duration.as_secs_f64() * 1000.0)?;
+			total += *duration;
+		}
+		writeln!(out, "  {:<18} {:>10.3} ms", "total", total.as_secs_f64() * 1000.0)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	fn written(timings: &Timings) -> String {
+		let mut output = Vec::new();
+		timings.write(&mut output).unwrap();
+		String::from_utf8(output).unwrap()
+	}
+	
+	#[test]
+	fn phases_with_the_same_name_are_summed_up() {
+		let mut timings = Timings::new(true);
+		let start = timings.last.unwrap();
+		timings.lap_at("diff", start + Duration::from_millis(2));
+		timings.lap_at("print", start + Duration::from_millis(3));
+		timings.lap_at("diff", start + Duration::from_millis(7));
+		assert_eq!(written(&timings), concat!(
+			"Timings:\n",
+			"  diff                    6.000 ms\n",
+			"  print                   1.000 ms\n",
+			"  total                   7.000 ms\n",
+		));
+	}
+	
+	#[test]
+	fn disabled_timings_write_nothing() {
+		let mut timings = Timings::new(false);
+		timings.lap("diff");
+		assert_eq!(written(&timings), "");
+	}
+}
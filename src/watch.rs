@@ -0,0 +1,112 @@
+use notify::{Event, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::Duration;
+
+// Unity and editors write several files at once, each of them in multiple steps. Waiting for a quiet moment re-runs only once per burst.
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+// Calls rerun right away, then again after every burst of meta file changes inside the folders. Only returns when watching fails.
+pub fn watch(folders: &[PathBuf], mut rerun: impl FnMut()) -> notify::Result<()> {
+	let (sender, receiver) = mpsc::channel();
+	let mut watcher = notify::recommended_watcher(sender)?;
+	for folder in folders {
+		watcher.watch(folder, RecursiveMode::Recursive)?;
+	}
+	rerun();
+	while wait_for_change(&receiver, DEBOUNCE)? {
+		rerun();
+	}
+	Ok(())
+}
+
+// Blocks until a meta file changed, and then until no more events came in for the debounce duration. False once the watcher stopped.
+fn wait_for_change(receiver: &Receiver<notify::Result<Event>>, debounce: Duration) -> notify::Result<bool> {
+	loop {
+		let Ok(event) = receiver.recv() else {
+			return Ok(false);
+		};
+		if is_meta_change(&event?) {
+			break;
+		}
+	}
+	loop {
+		match receiver.recv_timeout(debounce) {
+			Ok(event) => {
+				event?;
+			}
+			Err(RecvTimeoutError::Timeout) => return Ok(true),
+			// The change still happened. The next wait notices that the watcher stopped.
+			Err(RecvTimeoutError::Disconnected) => return Ok(true),
+		}
+	}
+}
+
+// Reading the meta files for the analysis must not trigger the next run. Neither does Git updating its own files.
+fn is_meta_change(event: &Event) -> bool {
+	let is_meta_file = |path: &Path| path.extension().is_some_and(|extension| extension == "meta")
+		&& !path.components().any(|component| component.as_os_str() == ".git");
+	!event.kind.is_access() && event.paths.iter().any(|path| is_meta_file(path))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use notify::event::{AccessKind, CreateKind, ModifyKind};
+	use notify::EventKind;
+	use std::sync::mpsc::Sender;
+	
+	fn send(sender: &Sender<notify::Result<Event>>, kind: EventKind, path: &str) {
+		sender.send(Ok(Event::new(kind).add_path(PathBuf::from(path)))).unwrap();
+	}
+	
+	// Counts the re-runs like watch() does, until the watcher stopped.
+	fn reruns(receiver: &Receiver<notify::Result<Event>>) -> usize {
+		let mut count = 0;
+		while wait_for_change(receiver, Duration::from_millis(50)).unwrap() {
+			count += 1;
+		}
+		count
+	}
+	
+	#[test]
+	fn meta_change_triggers_a_rerun() {
+		let (sender, receiver) = mpsc::channel();
+		send(&sender, EventKind::Modify(ModifyKind::Any), "/project/Assets/Hero.png.meta");
+		drop(sender);
+		assert_eq!(reruns(&receiver), 1);
+	}
+	
+	#[test]
+	fn burst_of_changes_triggers_one_rerun() {
+		let (sender, receiver) = mpsc::channel();
+		send(&sender, EventKind::Create(CreateKind::File), "/project/Assets/Hero.png.meta");
+		send(&sender, EventKind::Modify(ModifyKind::Any), "/project/Assets/Hero.png.meta");
+		send(&sender, EventKind::Modify(ModifyKind::Any), "/project/Assets/Hero.png");
+		send(&sender, EventKind::Create(CreateKind::File), "/project/Assets/Villain.png.meta");
+		drop(sender);
+		assert_eq!(reruns(&receiver), 1);
+	}
+	
+	#[test]
+	fn changes_after_a_quiet_moment_trigger_another_rerun() {
+		let (sender, receiver) = mpsc::channel();
+		send(&sender, EventKind::Modify(ModifyKind::Any), "/project/Assets/Hero.png.meta");
+		let later = std::thread::spawn(move || {
+			std::thread::sleep(Duration::from_millis(300));
+			send(&sender, EventKind::Modify(ModifyKind::Any), "/project/Assets/Hero.png.meta");
+		});
+		assert_eq!(reruns(&receiver), 2);
+		later.join().unwrap();
+	}
+	
+	#[test]
+	fn other_events_trigger_nothing() {
+		let (sender, receiver) = mpsc::channel();
+		send(&sender, EventKind::Access(AccessKind::Any), "/project/Assets/Hero.png.meta");
+		send(&sender, EventKind::Modify(ModifyKind::Any), "/project/Assets/Hero.png");
+		send(&sender, EventKind::Modify(ModifyKind::Any), "/project/.git/modules/x.meta");
+		drop(sender);
+		assert_eq!(reruns(&receiver), 0);
+	}
+}
@@ -0,0 +1,332 @@
+// The library analysis on fixture repositories, without going through the command line.
+mod common;
+
+use common::{guid, meta_content, Fixture};
+use git2::{Delta, Diff, DiffFindOptions, DiffOptions, Oid};
+use std::fs;
+use std::path::{Path, PathBuf};
+use unity_engine_meta_git_differ::analysis::{analyze, find_assets_without_meta, find_orphans, AnalysisError, DiffTarget};
+use unity_engine_meta_git_differ::data::path_tree_storage::TreePrintOptions;
+use unity_engine_meta_git_differ::data::uuid_storage::{ChangeKind, UuidStorage};
+use unity_engine_meta_git_differ::data::uuid::{MetaBlobError, MetaReadError};
+use unity_engine_meta_git_differ::diagnostics::Severity;
+use unity_engine_meta_git_differ::output::color::AnsiStripper;
+use unity_engine_meta_git_differ::Uuid;
+
+fn uuid(digit: char) -> Uuid {
+	guid(digit).parse().unwrap()
+}
+
+fn paths(paths: &[&str]) -> Vec<PathBuf> {
+	paths.iter().map(PathBuf::from).collect()
+}
+
+// Same rename and copy detection as the binary uses.
+fn find_similar(diff: &mut Diff) {
+	let mut find_opts = DiffFindOptions::new();
+	find_opts.renames(true);
+	find_opts.copies(true);
+	find_opts.for_untracked(true);
+	diff.find_similar(Some(&mut find_opts)).unwrap();
+}
+
+fn diff_commits<'a>(fixture: &'a Fixture, from: Oid, to: Oid) -> Diff<'a> {
+	let from = fixture.repo.find_commit(from).unwrap().tree().unwrap();
+	let to = fixture.repo.find_commit(to).unwrap().tree().unwrap();
+	fixture.repo.diff_tree_to_tree(Some(&from), Some(&to), None).unwrap()
+}
+
+// Same as the binary does without revisions: the head commit against the work directory, including untracked files.
+fn diff_head_to_workdir(fixture: &Fixture) -> Diff<'_> {
+	let head = fixture.repo.head().unwrap().peel_to_tree().unwrap();
+	let mut diff = fixture.repo.diff_tree_to_workdir_with_index(
+		Some(&head), Some(DiffOptions::new().include_untracked(true).recurse_untracked_dirs(true)),
+	).unwrap();
+	find_similar(&mut diff);
+	diff
+}
+
+#[test]
+fn tracked_meta_moved_to_untracked_path_is_a_rename() {
+	let fixture = Fixture::new("untracked-rename");
+	fixture.asset("Assets/A/x.png", &guid('1'));
+	fixture.commit("first");
+	fixture.rename("Assets/A/x.png.meta", "Assets/B/x.png.meta");
+	// Only the deletion gets staged, the new path stays untracked.
+	let mut index = fixture.repo.index().unwrap();
+	index.remove_path(Path::new("Assets/A/x.png.meta")).unwrap();
+	index.write().unwrap();
+	
+	let diff = diff_head_to_workdir(&fixture);
+	let result = analyze(&fixture.repo, &diff).unwrap();
+	
+	let renames: Vec<_> = result.uuid_storage.renames().collect();
+	assert_eq!(renames.len(), 1);
+	let (uuid, entry) = renames[0];
+	assert_eq!(*uuid, self::uuid('1'));
+	assert_eq!(entry.removed, paths(&["Assets/A/x.png"]));
+	assert_eq!(entry.added, paths(&["Assets/B/x.png"]));
+	assert_eq!(result.uuid_storage.pure_additions().count(), 0);
+	assert_eq!(result.uuid_storage.pure_removals().count(), 0);
+	assert!(result.collisions.is_empty());
+}
+
+#[test]
+fn removal_paired_with_multiple_additions_is_recorded_once() {
+	let fixture = Fixture::new("multi-pairing");
+	fixture.asset("Assets/A/x.png", &guid('1'));
+	fixture.commit("first");
+	fixture.rename("Assets/A/x.png.meta", "Assets/B/x.png.meta");
+	// Similar meta files, which rename detection may pair with the removed one too.
+	fixture.write("Assets/C/y.png.meta", meta_content(&guid('2')));
+	fixture.write("Assets/C/z.png.meta", meta_content(&guid('3')));
+	
+	let diff = diff_head_to_workdir(&fixture);
+	let result = analyze(&fixture.repo, &diff).unwrap();
+	
+	let entry = &result.uuid_storage.lookup[&uuid('1')];
+	assert_eq!(entry.removed, paths(&["Assets/A/x.png"]));
+	assert_eq!(entry.added, paths(&["Assets/B/x.png"]));
+	assert_eq!(result.uuid_storage.pure_additions().count(), 2);
+	assert!(result.collisions.is_empty());
+	assert!(result.conflicting_guids().is_empty());
+}
+
+#[test]
+fn result_classifies_every_kind_of_change() {
+	let fixture = Fixture::new("classifications");
+	fixture.asset("Assets/Removed.png", &guid('1'));
+	fixture.asset("Assets/Old/Moved.png", &guid('2'));
+	fixture.asset("Assets/Replaced.png", &guid('3'));
+	fixture.asset("Assets/Modified.png", &guid('4'));
+	fixture.commit("first");
+	fixture.remove("Assets/Removed.png.meta");
+	fixture.rename("Assets/Old/Moved.png.meta", "Assets/New/Moved.png.meta");
+	fixture.write("Assets/Replaced.png.meta", meta_content(&guid('5')));
+	fixture.write("Assets/Modified.png.meta", meta_content(&guid('4')).replace("userData: ", "userData: changed"));
+	fixture.asset("Assets/Added.png", &guid('6'));
+	
+	// The work directory files are read relative to the repository, the tests run in the crate folder.
+	let diff = diff_head_to_workdir(&fixture);
+	let result = analyze(&fixture.repo, &diff).unwrap();
+	
+	let kinds: Vec<_> = result.uuid_storage.iter().map(|(uuid, entry)| (*uuid, entry.classify())).collect();
+	assert_eq!(kinds, vec![
+		(uuid('1'), ChangeKind::Removed),
+		(uuid('2'), ChangeKind::Renamed),
+		(uuid('3'), ChangeKind::Removed),
+		(uuid('4'), ChangeKind::Unchanged),
+		(uuid('5'), ChangeKind::Added),
+		(uuid('6'), ChangeKind::Added),
+	]);
+	assert_eq!(result.uuid_storage.lookup[&uuid('4')].modified, paths(&["Assets/Modified.png"]));
+	assert_eq!(result.replaced.len(), 1);
+	assert_eq!(result.replaced[0].path, PathBuf::from("Assets/Replaced.png"));
+	assert_eq!((result.replaced[0].old_guid, result.replaced[0].new_guid), (uuid('3'), uuid('5')));
+	assert!(result.collisions.is_empty());
+	assert!(result.skipped.is_empty());
+}
+
+#[test]
+fn broken_meta_file_in_commit_is_an_error() {
+	let fixture = Fixture::new("broken-blob");
+	fixture.asset("Assets/x.png", &guid('1'));
+	let first = fixture.commit("first");
+	fixture.write("Assets/y.png.meta", "fileFormatVersion: 2\nguid: not-a-guid\n");
+	let second = fixture.commit("second");
+	
+	let diff = diff_commits(&fixture, first, second);
+	match analyze(&fixture.repo, &diff) {
+		Err(AnalysisError::Blob(MetaBlobError::MalformedGuid { text, .. })) => assert_eq!(text, "not-a-guid"),
+		Err(error) => panic!("Expected a malformed GUID, got: {error}"),
+		Ok(_) => panic!("Expected an error"),
+	}
+}
+
+#[test]
+fn broken_meta_file_on_disk_is_an_error() {
+	let fixture = Fixture::new("broken-disk");
+	fixture.asset("Assets/x.png", &guid('1'));
+	fixture.commit("first");
+	fixture.write("Assets/y.png.meta", "fileFormatVersion: 2\n");
+	
+	let diff = diff_head_to_workdir(&fixture);
+	match analyze(&fixture.repo, &diff) {
+		Err(AnalysisError::Disk(MetaReadError::MissingGuid { path })) => assert_eq!(path, fixture.path("Assets/y.png.meta")),
+		Err(error) => panic!("Expected a missing GUID, got: {error}"),
+		Ok(_) => panic!("Expected an error"),
+	}
+}
+
+#[test]
+fn folder_metas_are_told_apart_from_file_metas() {
+	let fixture = Fixture::new("folders");
+	fixture.asset("Assets/Base.png", &guid('9'));
+	let first = fixture.commit("first");
+	fixture.folder("Assets/Level", &guid('1'));
+	fixture.asset("Assets/Level/Rock.png", &guid('2'));
+	// Without the 'folderAsset' line, the folder on disk tells what it is.
+	fs::create_dir_all(fixture.path("Assets/Plain")).unwrap();
+	fixture.write("Assets/Plain.meta", meta_content(&guid('3')));
+	let second = fixture.commit("second");
+	
+	let diff = diff_commits(&fixture, first, second);
+	let result = analyze(&fixture.repo, &diff).unwrap();
+	
+	let lookup = &result.uuid_storage.lookup;
+	assert!(lookup[&uuid('1')].is_folder);
+	assert!(!lookup[&uuid('2')].is_folder);
+	assert!(lookup[&uuid('3')].is_folder);
+	let mut tree = Vec::new();
+	result.addition_tree.write_tree(&mut AnsiStripper::new(&mut tree), &result.uuid_storage, true, &TreePrintOptions::default()).unwrap();
+	assert_eq!(String::from_utf8(tree).unwrap(), format!(
+		"└─Assets: (+3 -0 ~0)\n  ├─Level: ADDED {} [folder] (+1 -0 ~0)\n  │ └─Rock.png: ADDED {}\n  └─Plain: ADDED {} [folder]\n",
+		guid('1'), guid('2'), guid('3'),
+	));
+}
+
+#[cfg(unix)]
+#[test]
+fn orphans_on_the_index_with_a_non_utf8_path() {
+	use std::os::unix::ffi::OsStrExt;
+	let fixture = Fixture::new("orphans-index");
+	fixture.asset("Assets/Kept.png", &guid('1'));
+	fixture.write("Assets/Folder/Deep/file.txt", "inside");
+	fs::write(fixture.path("Assets").join(std::ffi::OsStr::from_bytes(b"caf\xE9.png")), "asset").unwrap();
+	fixture.stage_all();
+	assert!(fixture.repo.index().unwrap().iter().any(|entry| entry.path == b"Assets/caf\xE9.png"));
+	
+	let mut storage = UuidStorage::default();
+	for (digit, path) in [('1', "Assets/Kept.png.meta"), ('2', "Assets/Folder.meta"), ('3', "Assets/Orphan.png.meta"), ('4', "Assets/Fold.meta")] {
+		storage.added(uuid(digit), PathBuf::from(path));
+	}
+	// Folders exist through the files inside, a path prefix is no folder.
+	assert_eq!(find_orphans(&fixture.repo, &DiffTarget::Index, &storage).unwrap(), paths(&["Assets/Orphan.png", "Assets/Fold"]));
+}
+
+#[test]
+fn assets_added_without_meta_file() {
+	let fixture = Fixture::new("without-meta");
+	fixture.asset("Assets/Base.png", &guid('9'));
+	let first = fixture.commit("first");
+	fixture.asset("Assets/WithMeta.png", &guid('1'));
+	fixture.write("Assets/A/NoMeta.png", "asset");
+	fixture.write("Packages/Lib/NoMeta.cs", "code");
+	// Unity does not import these, thus they need no meta file.
+	fixture.write("Assets/.hidden", "hidden");
+	fixture.write("Assets/Backup.png~", "backup");
+	fixture.write("ProjectSettings/Settings.asset", "settings");
+	let second = fixture.commit("second");
+	
+	let diff = diff_commits(&fixture, first, second);
+	assert_eq!(find_assets_without_meta(&diff, &[], &[]), paths(&["Assets/A/NoMeta.png", "Packages/Lib/NoMeta.cs"]));
+	assert_eq!(find_assets_without_meta(&diff, &paths(&["Assets"]), &[]), paths(&["Assets/A/NoMeta.png"]));
+}
+
+#[test]
+fn assets_without_meta_file_in_a_project_subfolder() {
+	let fixture = Fixture::new("without-meta-subfolder");
+	fixture.asset("Game/Assets/Base.png", &guid('9'));
+	let first = fixture.commit("first");
+	fixture.write("Game/Assets/A/NoMeta.png", "asset");
+	fixture.write("Game/Assets/Temp/Generated.png", "asset");
+	fixture.write("Game/ProjectSettings/Settings.asset", "settings");
+	fixture.write("Assets/Outside.png", "asset");
+	let second = fixture.commit("second");
+	
+	let diff = diff_commits(&fixture, first, second);
+	let excludes = ["**/Temp/**".parse().unwrap()];
+	// The 'Assets' folder is looked for below the requested folder, or the requested folder is inside of it.
+	assert_eq!(find_assets_without_meta(&diff, &paths(&["Game"]), &excludes), paths(&["Game/Assets/A/NoMeta.png"]));
+	assert_eq!(find_assets_without_meta(&diff, &paths(&["Game/Assets/A"]), &excludes), paths(&["Game/Assets/A/NoMeta.png"]));
+	assert_eq!(find_assets_without_meta(&diff, &[], &excludes), paths(&["Assets/Outside.png"]));
+	assert_eq!(find_assets_without_meta(&diff, &paths(&["Game"]), &[]), paths(&["Game/Assets/A/NoMeta.png", "Game/Assets/Temp/Generated.png"]));
+}
+
+#[test]
+fn duplicate_guid_is_collected_as_warning() {
+	let fixture = Fixture::new("duplicate-diagnostic");
+	fixture.asset("Assets/Base.png", &guid('9'));
+	let first = fixture.commit("first");
+	fixture.asset("Assets/A/copy.png", &guid('2'));
+	fixture.asset("Assets/B/copy.png", &guid('2'));
+	let second = fixture.commit("second");
+	
+	let diff = diff_commits(&fixture, first, second);
+	let result = analyze(&fixture.repo, &diff).unwrap();
+	
+	let diagnostics: Vec<_> = result.diagnostics.iter().collect();
+	assert_eq!(diagnostics.len(), 1);
+	assert_eq!(diagnostics[0].severity, Severity::Warning);
+	assert_eq!(diagnostics[0].paths, paths(&["Assets/A/copy.png", "Assets/B/copy.png"]));
+	assert!(diagnostics[0].message.contains(&format!("GUID {} is added by 2 files", guid('2'))), "{}", diagnostics[0].message);
+}
+
+#[test]
+fn invert_swaps_additions_and_removals() {
+	let fixture = Fixture::new("invert");
+	fixture.asset("Assets/Removed.png", &guid('1'));
+	let first = fixture.commit("first");
+	fixture.remove("Assets/Removed.png.meta");
+	fixture.asset("Assets/A/copy.png", &guid('2'));
+	fixture.asset("Assets/B/copy.png", &guid('2'));
+	let second = fixture.commit("second");
+	
+	let diff = diff_commits(&fixture, first, second);
+	let mut result = analyze(&fixture.repo, &diff).unwrap();
+	result.invert();
+	
+	assert_eq!(result.uuid_storage.lookup[&uuid('1')].added, paths(&["Assets/Removed.png"]));
+	assert!(result.uuid_storage.lookup[&uuid('1')].removed.is_empty());
+	assert_eq!(result.uuid_storage.lookup[&uuid('2')].removed, paths(&["Assets/A/copy.png", "Assets/B/copy.png"]));
+	let mut tree = Vec::new();
+	result.addition_tree.write_tree(&mut AnsiStripper::new(&mut tree), &result.uuid_storage, true, &TreePrintOptions::default()).unwrap();
+	assert_eq!(String::from_utf8(tree).unwrap(), format!("└─Assets: (+1 -0 ~0)\n  └─Removed.png: ADDED {}\n", guid('1')));
+	// The collision is now one of removed files, the messages tell so too.
+	assert!(!result.collisions[0].is_adding);
+	let messages: Vec<_> = result.diagnostics.iter().map(|diagnostic| diagnostic.message.as_str()).collect();
+	assert_eq!(messages.len(), 1);
+	assert!(messages[0].contains("is removed by 2 files"), "{}", messages[0]);
+}
+
+#[test]
+fn modified_meta_with_new_guid_is_replaced() {
+	let fixture = Fixture::new("replaced");
+	fixture.asset("Assets/x.png", &guid('1'));
+	let first = fixture.commit("first");
+	fixture.write("Assets/x.png.meta", meta_content(&guid('2')));
+	let second = fixture.commit("second");
+	
+	let diff = diff_commits(&fixture, first, second);
+	assert!(diff.deltas().all(|delta| delta.status() == Delta::Modified));
+	let result = analyze(&fixture.repo, &diff).unwrap();
+	
+	assert_eq!(result.replaced.len(), 1);
+	assert_eq!(result.replaced[0].path, PathBuf::from("Assets/x.png"));
+	assert_eq!((result.replaced[0].old_guid, result.replaced[0].new_guid), (uuid('1'), uuid('2')));
+	assert_eq!(result.uuid_storage.lookup[&uuid('1')].classify(), ChangeKind::Removed);
+	assert_eq!(result.uuid_storage.lookup[&uuid('2')].classify(), ChangeKind::Added);
+	assert!(result.uuid_storage.lookup.values().all(|entry| entry.modified.is_empty()));
+}
+
+#[test]
+fn unstaged_deletion_is_a_removal() {
+	let fixture = Fixture::new("unstaged-deletion");
+	fixture.asset("Assets/x.png", &guid('1'));
+	fixture.asset("Assets/y.png", &guid('2'));
+	fixture.commit("first");
+	fixture.remove("Assets/x.png.meta");
+	fixture.remove("Assets/x.png");
+	
+	let diff = diff_head_to_workdir(&fixture);
+	let deletions: Vec<_> = diff.deltas().filter(|delta| delta.status() == Delta::Deleted).collect();
+	assert_eq!(deletions.len(), 2);
+	let result = analyze(&fixture.repo, &diff).unwrap();
+	
+	let removals: Vec<_> = result.uuid_storage.pure_removals().collect();
+	assert_eq!(removals.len(), 1);
+	assert_eq!(*removals[0].0, uuid('1'));
+	assert_eq!(removals[0].1.removed, paths(&["Assets/x.png"]));
+	assert!(result.skipped.is_empty());
+	assert_eq!(result.uuid_storage.lookup.len(), 1);
+}
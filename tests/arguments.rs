@@ -0,0 +1,386 @@
+// How the binary picks the repository and the revisions from its arguments.
+mod common;
+
+use common::{guid, stderr, stdout, Fixture, EXIT_CODE_NO_CHANGES};
+use git2::Oid;
+use std::fs;
+
+// Two commits: 'x' gets added by the second one, 'y' is only added in the work directory.
+fn fixture_with_two_commits(name: &str) -> (Fixture, Oid, Oid) {
+	let fixture = Fixture::new(name);
+	fixture.asset("Assets/Base.png", &guid('9'));
+	let first = fixture.commit("first");
+	fixture.asset("Assets/x.png", &guid('1'));
+	let second = fixture.commit("second");
+	fixture.asset("Assets/y.png", &guid('2'));
+	(fixture, first, second)
+}
+
+fn line_x() -> String {
+	format!("A {} Assets/x.png\n", guid('1'))
+}
+
+fn line_y() -> String {
+	format!("A {} Assets/y.png\n", guid('2'))
+}
+
+#[test]
+fn explicit_path_and_two_hashes() {
+	let (fixture, first, second) = fixture_with_two_commits("args-explicit-path");
+	let root = fixture.root.to_str().unwrap();
+	// Started outside of the repository, it is only found by the argument.
+	let output = fixture.run_in(&std::env::temp_dir(), &["--path", root, &first.to_string(), &second.to_string(), "--format", "porcelain"]);
+	assert_eq!(output.status.code(), Some(EXIT_CODE_NO_CHANGES), "{}", stderr(&output));
+	assert_eq!(stdout(&output), line_x());
+}
+
+#[test]
+fn path_without_flag() {
+	let (fixture, first, second) = fixture_with_two_commits("args-path");
+	let root = fixture.root.to_str().unwrap();
+	let output = fixture.run_in(&std::env::temp_dir(), &[root, &first.to_string(), &second.to_string(), "--format", "porcelain"]);
+	assert_eq!(output.status.code(), Some(EXIT_CODE_NO_CHANGES), "{}", stderr(&output));
+	assert_eq!(stdout(&output), line_x());
+}
+
+#[test]
+fn single_hash_is_compared_with_the_work_directory() {
+	let (fixture, first, _) = fixture_with_two_commits("args-single-hash");
+	let output = fixture.run(&[&first.to_string(), "--format", "porcelain"]);
+	assert!(output.status.success(), "{}", stderr(&output));
+	assert_eq!(stdout(&output), line_x() + &line_y());
+}
+
+#[test]
+fn without_hash_head_is_compared_with_the_work_directory() {
+	let (fixture, _, _) = fixture_with_two_commits("args-no-hash");
+	// Any folder inside of the repository works.
+	let output = fixture.run_in(&fixture.path("Assets"), &["--format", "porcelain"]);
+	assert!(output.status.success(), "{}", stderr(&output));
+	assert_eq!(stdout(&output), line_y());
+}
+
+#[test]
+fn default_output_are_the_trees() {
+	let (fixture, first, second) = fixture_with_two_commits("args-trees");
+	let output = fixture.run(&[&first.to_string(), &second.to_string()]);
+	assert_eq!(output.status.code(), Some(EXIT_CODE_NO_CHANGES));
+	let stdout = stdout(&output);
+	assert!(stdout.contains(&format!("x.png: ADDED {}", guid('1'))), "{stdout}");
+	assert!(!stdout.contains("y.png"), "{stdout}");
+}
+
+#[test]
+fn path_that_is_no_repository_fails() {
+	let fixture = Fixture::new("args-no-repository");
+	fixture.remove(".git");
+	let root = fixture.root.to_str().unwrap();
+	let output = fixture.run_in(&std::env::temp_dir(), &["--path", root]);
+	assert_eq!(output.status.code(), Some(1));
+	assert!(stderr(&output).starts_with(&format!("Did not find OR could not open repository at location: {root}\n")));
+	assert!(stdout(&output).is_empty());
+}
+
+#[test]
+fn staged_only_shows_the_index() {
+	let (fixture, _, _) = fixture_with_two_commits("args-staged");
+	fixture.stage_all();
+	// Changed after staging, only the work directory knows about this one.
+	fixture.asset("Assets/z.png", &guid('3'));
+	let output = fixture.run(&["--staged", "--format", "porcelain"]);
+	assert!(output.status.success(), "{}", stderr(&output));
+	assert_eq!(stdout(&output), line_y());
+	// Without the option, both show up.
+	let output = fixture.run(&["--format", "porcelain"]);
+	assert_eq!(stdout(&output), line_y() + &format!("A {} Assets/z.png\n", guid('3')));
+}
+
+#[test]
+fn staged_with_hashes_is_rejected() {
+	let (fixture, first, second) = fixture_with_two_commits("args-staged-hashes");
+	let output = fixture.run(&["--staged", &first.to_string(), &second.to_string()]);
+	assert_eq!(output.status.code(), Some(1));
+	assert!(stderr(&output).contains("Option '--staged' always diffs the head commit with the index, it cannot be combined with commit hashes.\n"));
+	assert!(stdout(&output).is_empty());
+}
+
+#[test]
+fn range_of_hashes() {
+	let (fixture, first, second) = fixture_with_two_commits("args-range");
+	let output = fixture.run(&[&format!("{first}..{second}"), "--format", "porcelain"]);
+	assert!(output.status.success(), "{}", stderr(&output));
+	assert_eq!(stdout(&output), line_x());
+	// The same as two separate arguments.
+	let separate = fixture.run(&[&first.to_string(), &second.to_string(), "--format", "porcelain"]);
+	assert_eq!(separate.stdout, output.stdout);
+}
+
+#[test]
+fn range_of_revisions() {
+	let (fixture, _, _) = fixture_with_two_commits("args-range-revisions");
+	let output = fixture.run(&["HEAD~1..HEAD", "--format", "porcelain"]);
+	assert!(output.status.success(), "{}", stderr(&output));
+	assert_eq!(stdout(&output), line_x());
+	// The same as two separate arguments.
+	let separate = fixture.run(&["HEAD~1", "HEAD", "--format", "porcelain"]);
+	assert_eq!(separate.stdout, output.stdout);
+}
+
+#[test]
+fn range_with_missing_side_is_rejected() {
+	let (fixture, first, _) = fixture_with_two_commits("args-range-invalid");
+	for range in [format!("{first}.."), "..HEAD".to_owned(), format!("{first}...HEAD")] {
+		let output = fixture.run(&[&range]);
+		assert_eq!(output.status.code(), Some(1), "{range}");
+		assert!(stderr(&output).contains(&format!("Invalid range '{range}', expected '<from>..<to>'.\n")), "{}", stderr(&output));
+		assert!(stdout(&output).is_empty());
+	}
+}
+
+#[test]
+fn branch_and_tag_names() {
+	let (fixture, first, second) = fixture_with_two_commits("args-refs");
+	let first = fixture.repo.find_commit(first).unwrap();
+	fixture.repo.branch("before", &first, false).unwrap();
+	let second = fixture.repo.find_object(second, None).unwrap();
+	let signature = git2::Signature::now("Fixture", "fixture@example.com").unwrap();
+	fixture.repo.tag("v1.0", &second, &signature, "Release", false).unwrap();
+	fixture.repo.tag_lightweight("light", &second, false).unwrap();
+	for arguments in [["before", "v1.0"], ["before", "light"]] {
+		let output = fixture.run(&[arguments[0], arguments[1], "--format", "porcelain"]);
+		assert!(output.status.success(), "{}", stderr(&output));
+		assert_eq!(stdout(&output), line_x());
+	}
+	let output = fixture.run(&["before..v1.0", "--format", "porcelain"]);
+	assert_eq!(stdout(&output), line_x());
+}
+
+#[test]
+fn unknown_revisions_are_all_reported() {
+	let (fixture, _, _) = fixture_with_two_commits("args-unknown-refs");
+	let output = fixture.run(&["no-such-branch", "no-such-tag"]);
+	assert_eq!(output.status.code(), Some(1));
+	let stderr = stderr(&output);
+	assert!(stderr.contains("Did not find OR could not load commit/branch/tag: no-such-branch\n"), "{stderr}");
+	assert!(stderr.contains("Did not find OR could not load commit/branch/tag: no-such-tag\n"), "{stderr}");
+	assert!(stdout(&output).is_empty());
+}
+
+#[test]
+fn valid_and_invalid_hash_fails_before_any_work() {
+	let (fixture, first, _) = fixture_with_two_commits("args-one-invalid");
+	let first = first.to_string();
+	for arguments in [
+		vec![first.as_str(), "0123456789abcdef0123456789abcdef01234567"],
+		vec!["0123456789abcdef0123456789abcdef01234567", first.as_str()],
+	] {
+		let mut arguments = arguments;
+		arguments.extend(["--output", "out.txt"]);
+		let output = fixture.run(&arguments);
+		assert_eq!(output.status.code(), Some(1));
+		let stderr = stderr(&output);
+		assert!(stderr.contains("Did not find OR could not load commit/branch/tag: 0123456789abcdef0123456789abcdef01234567\n"), "{stderr}");
+		assert!(!stderr.contains(&first), "{stderr}");
+		// Nothing got written, not even the output file.
+		assert!(stdout(&output).is_empty());
+		assert!(!fixture.path("out.txt").exists());
+	}
+}
+
+#[test]
+fn help_is_printed_to_stdout() {
+	let (fixture, _, _) = fixture_with_two_commits("args-help");
+	for flag in ["--help", "-h"] {
+		let output = fixture.run(&[flag]);
+		assert_eq!(output.status.code(), Some(0), "{flag}");
+		assert!(stdout(&output).starts_with("Help: This tool will create a diff"), "{flag}");
+		assert!(stdout(&output).contains(" -V, --version "), "{flag}");
+		assert!(stderr(&output).is_empty(), "{flag}");
+	}
+}
+
+#[test]
+fn version_is_printed_to_stdout() {
+	let (fixture, _, _) = fixture_with_two_commits("args-version");
+	for flag in ["--version", "-V"] {
+		let output = fixture.run(&[flag]);
+		assert_eq!(output.status.code(), Some(0), "{flag}");
+		assert_eq!(stdout(&output), format!("unity_engine_meta_git_differ {}\n", env!("CARGO_PKG_VERSION")), "{flag}");
+	}
+}
+
+#[test]
+fn help_wins_over_invalid_arguments() {
+	// Outside of any repository, with an unknown option after it.
+	let fixture = Fixture::new("args-help-first");
+	let output = fixture.run_in(&std::env::temp_dir(), &["--help", "--no-such-option"]);
+	assert_eq!(output.status.code(), Some(0));
+	assert!(stdout(&output).starts_with("Help: "));
+}
+
+#[test]
+fn unknown_option_prints_the_help_to_stderr() {
+	let (fixture, _, _) = fixture_with_two_commits("args-unknown-option");
+	let output = fixture.run(&["--no-such-option"]);
+	assert_eq!(output.status.code(), Some(1));
+	assert!(stderr(&output).contains("Help: "));
+	assert!(stdout(&output).is_empty());
+}
+
+#[test]
+fn repository_without_commits() {
+	let fixture = Fixture::new("args-unborn");
+	fixture.asset("Assets/x.png", &guid('1'));
+	fixture.folder("Assets/Folder", &guid('2'));
+	let output = fixture.run(&["--format", "porcelain"]);
+	assert!(output.status.success(), "{}", stderr(&output));
+	assert_eq!(stdout(&output), format!("A {} Assets/x.png\nA {} Assets/Folder\n", guid('1'), guid('2')));
+	assert!(!stderr(&output).contains("panicked"));
+	// Also with the staged files only.
+	fixture.stage_all();
+	let output = fixture.run(&["--staged", "--format", "porcelain"]);
+	assert_eq!(stdout(&output), format!("A {} Assets/x.png\nA {} Assets/Folder\n", guid('1'), guid('2')));
+}
+
+#[test]
+fn detached_head() {
+	let (fixture, first, second) = fixture_with_two_commits("args-detached");
+	fixture.repo.set_head_detached(second).unwrap();
+	let output = fixture.run(&["--format", "porcelain"]);
+	assert!(output.status.success(), "{}", stderr(&output));
+	assert_eq!(stdout(&output), line_y());
+	fixture.repo.set_head_detached(first).unwrap();
+	let output = fixture.run(&["--format", "porcelain"]);
+	assert_eq!(stdout(&output), line_x() + &line_y());
+}
+
+#[test]
+fn head_pointing_at_a_missing_commit_is_reported() {
+	let (fixture, _, _) = fixture_with_two_commits("args-missing-head");
+	fixture.write(".git/HEAD", format!("{}\n", "1".repeat(40)));
+	let output = fixture.run(&[]);
+	assert_eq!(output.status.code(), Some(1));
+	let stderr = stderr(&output);
+	assert!(stderr.contains(&format!("Could not resolve HEAD of the repository at location: {}", fixture.root.display())), "{stderr}");
+	assert!(stderr.contains(" Details (by gitlib2): "), "{stderr}");
+	assert!(!stderr.contains("panicked"), "{stderr}");
+}
+
+#[test]
+fn nested_folder_gives_the_same_results_as_the_root() {
+	let (fixture, first, _) = fixture_with_two_commits("args-nested");
+	fixture.asset("Assets/Deep/Er/z.png", &guid('3'));
+	for arguments in [vec![], vec![first.to_string()], vec!["HEAD~1..HEAD".to_owned()]] {
+		let mut arguments: Vec<&str> = arguments.iter().map(String::as_str).collect();
+		arguments.extend(["--format", "porcelain"]);
+		let from_root = fixture.run(&arguments);
+		let from_nested = fixture.run_in(&fixture.path("Assets/Deep/Er"), &arguments);
+		assert!(from_nested.status.success(), "{}", stderr(&from_nested));
+		assert!(!from_root.stdout.is_empty());
+		// Paths stay relative to the repository root.
+		assert_eq!(stdout(&from_nested), stdout(&from_root), "{arguments:?}");
+	}
+}
+
+// Commits on the first of January, March and May 2024 (UTC). Each one adds an asset.
+fn fixture_with_dated_commits(name: &str) -> Fixture {
+	let fixture = Fixture::new(name);
+	for (digit, date) in [('1', 1704067200), ('2', 1709251200), ('3', 1714521600)] {
+		fixture.asset(&format!("Assets/{digit}.png"), &guid(digit));
+		fixture.commit_at("dated", date);
+	}
+	fixture.asset("Assets/4.png", &guid('4'));
+	fixture
+}
+
+fn added_line(digit: char) -> String {
+	format!("A {} Assets/{digit}.png\n", guid(digit))
+}
+
+#[test]
+fn since_picks_the_newest_commit_before_the_date() {
+	let fixture = fixture_with_dated_commits("args-since");
+	// Compared with the work directory.
+	let output = fixture.run(&["--since", "2024-04-01", "--format", "porcelain"]);
+	assert!(output.status.success(), "{}", stderr(&output));
+	assert_eq!(stdout(&output), added_line('3') + &added_line('4'));
+	// The exact time of a commit still counts as before.
+	let output = fixture.run(&["--since", "2024-03-01T00:00:00Z", "--format", "porcelain"]);
+	assert_eq!(stdout(&output), added_line('3') + &added_line('4'));
+	let output = fixture.run(&["--since", "2024-01-15", "--until", "2024-04-01", "--format", "porcelain"]);
+	assert_eq!(stdout(&output), added_line('2'));
+	// All commits are older than a day.
+	let output = fixture.run(&["--since", "1d", "--format", "porcelain"]);
+	assert_eq!(stdout(&output), added_line('4'));
+}
+
+#[test]
+fn since_before_the_first_commit_is_an_error() {
+	let fixture = fixture_with_dated_commits("args-since-too-early");
+	let output = fixture.run(&["--since", "2023-01-01"]);
+	assert_eq!(output.status.code(), Some(1));
+	assert!(stderr(&output).contains("There is no commit at or before the requested time (Unix time 1672531200)."), "{}", stderr(&output));
+}
+
+#[test]
+fn since_with_hash_or_invalid_date_is_rejected() {
+	let fixture = fixture_with_dated_commits("args-since-invalid");
+	let output = fixture.run(&["--since", "7d", "HEAD"]);
+	assert!(stderr(&output).contains("Option '--since' already selects the first commit, it cannot be combined with commit hashes."));
+	let output = fixture.run(&["--until", "7d"]);
+	assert!(stderr(&output).contains("Option '--until' selects the second commit, it requires '--since' for the first one."));
+	let output = fixture.run(&["--since", "last week"]);
+	assert_eq!(output.status.code(), Some(1));
+	assert!(stderr(&output).contains("Invalid date or duration 'last week'"));
+}
+
+#[test]
+fn commit_compared_with_the_index() {
+	let (fixture, first, second) = fixture_with_two_commits("args-index");
+	fixture.stage_all();
+	fixture.asset("Assets/z.png", &guid('3'));
+	let output = fixture.run(&["--index", &first.to_string(), "--format", "porcelain"]);
+	assert!(output.status.success(), "{}", stderr(&output));
+	assert_eq!(stdout(&output), line_x() + &line_y());
+	// Without a hash, it is the same as '--staged'.
+	let index = fixture.run(&["--index", "--format", "porcelain"]);
+	let staged = fixture.run(&["--staged", "--format", "porcelain"]);
+	assert_eq!(stdout(&index), line_y());
+	assert_eq!(index.stdout, staged.stdout);
+	
+	let output = fixture.run(&["--index", &first.to_string(), &second.to_string()]);
+	assert_eq!(output.status.code(), Some(1));
+	assert!(stderr(&output).contains("Option '--index' replaces the second commit with the index, it can only be combined with a single commit hash."));
+}
+
+#[test]
+fn separated_git_dir_and_work_tree() {
+	let fixture = Fixture::new("git-dir");
+	fixture.asset("Assets/x.png", &guid('1'));
+	fixture.asset("Assets/y.png", &guid('2'));
+	fixture.commit("first");
+	fixture.remove("Assets/y.png.meta");
+	// Nothing left in the work tree tells where the repository is.
+	fs::create_dir_all(fixture.path("Store")).unwrap();
+	fs::rename(fixture.path(".git"), fixture.path("Store/project.git")).unwrap();
+	let git_dir = fixture.path("Store/project.git");
+	let work_tree = fixture.root.clone();
+	
+	let output = fixture.run_in(&fixture.path("Store"), &[
+		"--git-dir", git_dir.to_str().unwrap(), "--work-tree", work_tree.to_str().unwrap(), "--format", "porcelain",
+	]);
+	assert!(output.status.success(), "{}", stderr(&output));
+	assert_eq!(stdout(&output), format!("D {} Assets/y.png\n", guid('2')));
+}
+
+#[test]
+fn git_dir_and_work_tree_must_exist() {
+	let fixture = Fixture::new("git-dir-missing");
+	fixture.asset("Assets/x.png", &guid('1'));
+	fixture.commit("first");
+	for option in ["--git-dir", "--work-tree"] {
+		let output = fixture.run(&[option, "Missing"]);
+		assert!(!output.status.success());
+		assert!(stderr(&output).contains(&format!("No existing folder at 'Missing' for '{option}'.")), "{}", stderr(&output));
+	}
+}
@@ -0,0 +1,783 @@
+// Running the binary on fixture repositories: exit status, stdout and stderr.
+mod common;
+
+use common::{guid, meta_content, stderr, stdout, Fixture, EXIT_CODE_CHANGES, EXIT_CODE_CONFLICTS, EXIT_CODE_NO_CHANGES};
+
+#[test]
+fn broken_meta_file_is_reported_without_panic() {
+	let fixture = Fixture::new("cli-broken-meta");
+	fixture.asset("Assets/x.png", &guid('1'));
+	fixture.commit("first");
+	fixture.write("Assets/y.png.meta", "fileFormatVersion: 2\nguid: not-a-guid\n");
+	let output = fixture.run(&[]);
+	assert_eq!(output.status.code(), Some(1));
+	let stderr = stderr(&output);
+	assert!(stderr.contains("Failed to analyze the diff: Could not convert UUID 'not-a-guid'"), "{stderr}");
+	assert!(!stderr.contains("panicked"), "{stderr}");
+	assert!(!stdout(&output).contains("By addition tree"));
+}
+
+#[test]
+fn nil_guid_is_skipped() {
+	let fixture = Fixture::new("cli-nil-guid");
+	fixture.asset("Assets/x.png", &guid('1'));
+	let first = fixture.commit("first");
+	fixture.asset("Assets/Placeholder.png", &guid('0'));
+	fixture.remove("Assets/x.png.meta");
+	let second = fixture.commit("second");
+	
+	let output = fixture.run(&[&first.to_string(), &second.to_string()]);
+	assert!(output.status.success());
+	assert!(stderr(&output).contains("Skipping added path 'Assets/Placeholder.png.meta', as its Unity GUID is all zeros"), "{}", stderr(&output));
+	let stdout = stdout(&output);
+	// Only mentioned by the message, not in the trees.
+	assert!(!stdout.contains("Placeholder"), "{stdout}");
+	assert!(stdout.contains(&guid('1')), "{stdout}");
+}
+
+#[test]
+fn modified_meta_with_same_guid() {
+	let fixture = Fixture::new("cli-modified");
+	fixture.asset("Assets/x.png", &guid('1'));
+	let first = fixture.commit("first");
+	fixture.write("Assets/x.png.meta", meta_content(&guid('1')).replace("userData: ", "userData: changed"));
+	let second = fixture.commit("second");
+	
+	let output = fixture.run(&[&first.to_string(), &second.to_string()]);
+	assert!(output.status.success());
+	let stdout = stdout(&output);
+	let (trees, modified) = stdout.split_once("Modified without GUID change").unwrap();
+	assert!(!trees.contains("x.png"), "{stdout}");
+	assert!(modified.contains("x.png") && modified.contains(&guid('1')), "{stdout}");
+}
+
+#[test]
+fn summary_line_after_the_trees() {
+	let fixture = Fixture::new("cli-summary");
+	fixture.asset("Assets/x.png", &guid('1'));
+	fixture.asset("Assets/y.png", &guid('2'));
+	let first = fixture.commit("first");
+	fixture.remove("Assets/x.png.meta");
+	fixture.rename("Assets/y.png.meta", "Assets/Moved/y.png.meta");
+	fixture.asset("Assets/z.png", &guid('3'));
+	let second = fixture.commit("second");
+	let output = fixture.run(&[&first.to_string(), &second.to_string()]);
+	let stdout = stdout(&output);
+	assert!(stdout.ends_with("Summary: 1 added, 1 removed, 1 renamed, 0 modified, 0 GUID conflicts\n"), "{stdout}");
+}
+
+#[test]
+fn guid_collision_section() {
+	let fixture = Fixture::new("cli-collision");
+	fixture.asset("Assets/x.png", &guid('1'));
+	let first = fixture.commit("first");
+	fixture.asset("Assets/A/copy.png", &guid('2'));
+	fixture.asset("Assets/B/copy.png", &guid('2'));
+	let second = fixture.commit("second");
+	let output = fixture.run(&[&first.to_string(), &second.to_string()]);
+	let stdout = stdout(&output);
+	let section = format!(
+		"GUID COLLISION - multiple files share the same Unity GUID:\n  {} is added by 2 files:\n    - Assets/A/copy.png\n    - Assets/B/copy.png\n",
+		guid('2'),
+	);
+	assert!(stdout.contains(&section), "{stdout}");
+}
+
+#[test]
+fn color_can_be_turned_off_and_on() {
+	let fixture = Fixture::new("cli-color");
+	fixture.asset("Assets/x.png", &guid('1'));
+	let first = fixture.commit("first");
+	fixture.asset("Assets/y.png", &guid('2'));
+	let second = fixture.commit("second");
+	let (first, second) = (first.to_string(), second.to_string());
+	// Not a terminal, thus 'auto' leaves the colors out.
+	let auto = fixture.run(&[&first, &second, "--color", "auto"]);
+	assert!(!auto.stdout.contains(&0x1b));
+	let never = fixture.run(&[&first, &second]);
+	assert!(!never.stdout.contains(&0x1b) && !never.stderr.contains(&0x1b));
+	assert_eq!(auto.stdout, never.stdout);
+	let always = fixture.run(&[&first, &second, "--color", "always"]);
+	assert!(always.stdout.contains(&0x1b));
+}
+
+#[test]
+fn invalid_color_choice_is_rejected() {
+	let fixture = Fixture::new("cli-color-invalid");
+	fixture.asset("Assets/x.png", &guid('1'));
+	fixture.commit("first");
+	let output = fixture.run(&["--color", "sometimes"]);
+	assert!(!output.status.success());
+	assert!(stderr(&output).contains("Unknown color mode 'sometimes'"));
+}
+
+#[test]
+fn ascii_flag_draws_the_trees_with_ascii() {
+	let fixture = Fixture::new("cli-ascii");
+	fixture.asset("Assets/Base.png", &guid('9'));
+	let first = fixture.commit("first");
+	fixture.asset("Assets/A/B/x.png", &guid('1'));
+	fixture.asset("Assets/A/y.png", &guid('2'));
+	let second = fixture.commit("second");
+	let output = fixture.run(&[&first.to_string(), &second.to_string(), "--ascii"]);
+	let stdout = stdout(&output);
+	assert!(stdout.is_ascii(), "{stdout}");
+	assert!(stdout.contains("`-"), "{stdout}");
+}
+
+#[test]
+fn renamed_asset_is_reported_once() {
+	let fixture = Fixture::new("cli-rename");
+	fixture.asset("Assets/Hero.png", &guid('1'));
+	let first = fixture.commit("first");
+	// The asset moves together with its meta file, Git detects both renames.
+	fixture.rename("Assets/Hero.png", "Assets/Characters/Hero.png");
+	fixture.rename("Assets/Hero.png.meta", "Assets/Characters/Hero.png.meta");
+	let second = fixture.commit("second");
+	let output = fixture.run(&[&first.to_string(), &second.to_string(), "--format", "porcelain"]);
+	assert_eq!(stdout(&output), format!("R {} Assets/Hero.png\tAssets/Characters/Hero.png\n", guid('1')));
+	let output = fixture.run(&[&first.to_string(), &second.to_string()]);
+	let stdout = stdout(&output);
+	// A single rename delta instead of a removal and an addition.
+	assert!(stdout.contains("Changed meta files: 1\n"), "{stdout}");
+	assert!(stdout.contains("  └─Hero.png: => 'Assets/Characters/Hero.png' (moved)\n"), "{stdout}");
+	assert!(stdout.contains("    └─Hero.png: <= 'Assets/Hero.png' (moved)\n"), "{stdout}");
+	assert!(stdout.ends_with("Summary: 0 added, 0 removed, 1 renamed, 0 modified, 0 GUID conflicts\n"), "{stdout}");
+}
+
+#[test]
+fn json_output_is_valid() {
+	let fixture = Fixture::new("cli-json");
+	fixture.asset("Assets/Old/Hero.png", &guid('1'));
+	let first = fixture.commit("first");
+	fixture.rename("Assets/Old/Hero.png.meta", "Assets/New/Hero.png.meta");
+	let second = fixture.commit("second");
+	let output = fixture.run(&[&first.to_string(), &second.to_string(), "--format", "json"]);
+	assert!(output.status.success());
+	let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+	assert_eq!(json["renamed"][0]["guid"], guid('1'));
+	assert_eq!(json["renamed"][0]["old_paths"], serde_json::json!(["Assets/Old/Hero.png"]));
+	assert_eq!(json["renamed"][0]["new_paths"], serde_json::json!(["Assets/New/Hero.png"]));
+	assert_eq!(json["added"], serde_json::json!([]));
+	// Without changes, the document is still complete.
+	let output = fixture.run(&[&second.to_string(), &second.to_string(), "--format", "json"]);
+	let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+	assert_eq!(json["renamed"], serde_json::json!([]));
+}
+
+#[test]
+fn porcelain_output_has_no_color() {
+	let fixture = Fixture::new("cli-porcelain");
+	fixture.asset("Assets/x.png", &guid('1'));
+	let first = fixture.commit("first");
+	fixture.asset("Assets/y.png", &guid('2'));
+	let second = fixture.commit("second");
+	let output = fixture.run(&[&format!("{first}..{second}"), "--format", "porcelain", "--color", "always"]);
+	assert_eq!(stdout(&output), format!("A {} Assets/y.png\n", guid('2')));
+}
+
+// Two new meta files share a GUID, which is worth a warning.
+fn fixture_with_duplicate_guid(name: &str) -> Fixture {
+	let fixture = Fixture::new(name);
+	fixture.asset("Assets/x.png", &guid('1'));
+	fixture.commit("first");
+	fixture.asset("Assets/A/copy.png", &guid('2'));
+	fixture.asset("Assets/B/copy.png", &guid('2'));
+	fixture
+}
+
+#[test]
+fn output_file_matches_stdout() {
+	let fixture = fixture_with_duplicate_guid("cli-output");
+	for format in ["tree", "porcelain"] {
+		let on_stdout = fixture.run(&["--format", format]);
+		let into_file = fixture.run(&["--format", format, "--output", "report.txt"]);
+		assert!(into_file.status.success(), "{}", stderr(&into_file));
+		assert!(into_file.stdout.is_empty(), "{format}");
+		assert_eq!(std::fs::read(fixture.path("report.txt")).unwrap(), on_stdout.stdout, "{format}");
+		// The warnings stay on stderr.
+		assert!(stderr(&into_file).contains("WARNING: GUID"), "{format}");
+	}
+}
+
+#[test]
+fn output_file_gets_truncated() {
+	let fixture = fixture_with_duplicate_guid("cli-output-truncate");
+	fixture.write("report.txt", "x".repeat(10000));
+	fixture.run(&["--format", "porcelain", "--output", "report.txt"]);
+	let report = std::fs::read_to_string(fixture.path("report.txt")).unwrap();
+	assert!(report.starts_with("A "), "{report}");
+	assert!(!report.contains('x'), "{report}");
+}
+
+#[test]
+fn quiet_suppresses_warnings() {
+	let fixture = fixture_with_duplicate_guid("cli-quiet");
+	let output = fixture.run(&[]);
+	assert!(!stdout(&output).contains("WARNING"));
+	assert!(stderr(&output).contains(&format!("WARNING: GUID {} is added by 2 files.", guid('2'))), "{}", stderr(&output));
+	let output = fixture.run(&["--quiet"]);
+	assert!(output.status.success());
+	assert!(!stdout(&output).contains("WARNING"));
+	assert!(stderr(&output).is_empty(), "{}", stderr(&output));
+	// The results are still there.
+	assert!(stdout(&output).contains("GUID COLLISION"));
+}
+
+#[test]
+fn quiet_keeps_hard_errors() {
+	let fixture = Fixture::new("cli-quiet-error");
+	fixture.asset("Assets/x.png", &guid('1'));
+	fixture.commit("first");
+	fixture.write("Assets/y.png.meta", "fileFormatVersion: 2\nguid: not-a-guid\n");
+	let output = fixture.run(&["-q"]);
+	assert_eq!(output.status.code(), Some(1));
+	assert!(stderr(&output).contains("Failed to analyze the diff"));
+}
+
+#[test]
+fn exit_code_tells_whether_meta_files_changed() {
+	let fixture = Fixture::new("cli-exit-code");
+	fixture.asset("Assets/x.png", &guid('1'));
+	fixture.commit("first");
+	// Only a change to an asset, its meta file stays.
+	fixture.write("Assets/x.png", "changed asset");
+	assert_eq!(fixture.run(&["--exit-code"]).status.code(), Some(EXIT_CODE_NO_CHANGES));
+	fixture.asset("Assets/y.png", &guid('2'));
+	assert_eq!(fixture.run(&["--exit-code"]).status.code(), Some(EXIT_CODE_CHANGES));
+	// Without the option, finding changes is no failure.
+	assert_eq!(fixture.run(&[]).status.code(), Some(EXIT_CODE_NO_CHANGES));
+}
+
+#[test]
+fn conflicts_take_precedence_over_changes() {
+	let fixture = fixture_with_duplicate_guid("cli-exit-code-conflict");
+	assert_eq!(fixture.run(&["--exit-code"]).status.code(), Some(EXIT_CODE_CHANGES));
+	let output = fixture.run(&["--exit-code", "--fail-on-conflict"]);
+	assert_eq!(output.status.code(), Some(EXIT_CODE_CONFLICTS));
+	assert!(stderr(&output).contains(&format!("Found GUID conflicts (used by multiple files or for the same path): {}", guid('2'))));
+}
+
+#[test]
+fn list_files_shows_the_meta_deltas() {
+	let fixture = Fixture::new("cli-list-files");
+	fixture.asset("Assets/Gone.png", &guid('1'));
+	fixture.asset("Assets/Old/Moved.png", &guid('2'));
+	fixture.asset("Assets/Changed.png", &guid('3'));
+	let first = fixture.commit("first");
+	fixture.remove("Assets/Gone.png.meta");
+	fixture.rename("Assets/Old/Moved.png.meta", "Assets/New/Moved.png.meta");
+	fixture.write("Assets/Changed.png.meta", common::meta_content(&guid('3')).replace("userData: ", "userData: changed"));
+	// Only listed, not read - thus the broken one does not fail.
+	fixture.write("Assets/Broken.png.meta", "guid: broken\n");
+	fixture.write("Assets/Changed.png", "not a meta file");
+	let second = fixture.commit("second");
+	let output = fixture.run(&[&format!("{first}..{second}"), "--list-files"]);
+	assert!(output.status.success(), "{}", stderr(&output));
+	assert_eq!(stdout(&output), concat!(
+		"Added       Assets/Broken.png.meta\n",
+		"Modified    Assets/Changed.png.meta\n",
+		"Deleted     Assets/Gone.png.meta\n",
+		"Renamed     Assets/Old/Moved.png.meta -> Assets/New/Moved.png.meta\n",
+	));
+}
+
+#[test]
+fn orphaned_meta_files_are_flagged() {
+	let fixture = Fixture::new("cli-orphans");
+	fixture.asset("Assets/Base.png", &guid('9'));
+	let first = fixture.commit("first");
+	fixture.asset("Assets/Kept.png", &guid('1'));
+	fixture.write("Assets/Orphan.png.meta", common::meta_content(&guid('2')));
+	fixture.folder("Assets/Folder", &guid('3'));
+	fixture.write("Assets/Folder/file.txt", "inside");
+	fixture.write("Assets/EmptyFolder.meta", common::meta_content(&guid('4')));
+	// Ordered by GUID, after the header.
+	let expected = "Changed meta files: 4\n\nMeta file without asset: Assets/Orphan.png.meta\nMeta file without asset: Assets/EmptyFolder.meta\n";
+	
+	// In the work directory, on the index and in a commit.
+	let output = fixture.run(&["--check-orphans", "--exit-code"]);
+	assert_eq!(stdout(&output), expected);
+	assert_eq!(output.status.code(), Some(EXIT_CODE_CHANGES));
+	fixture.stage_all();
+	assert_eq!(stdout(&fixture.run(&["--check-orphans", "--staged"])), expected);
+	let second = fixture.commit("second");
+	assert_eq!(stdout(&fixture.run(&["--check-orphans", &format!("{first}..{second}")])), expected);
+	
+	fixture.write("Assets/Orphan.png", "asset");
+	fixture.write("Assets/EmptyFolder/file.txt", "inside");
+	let output = fixture.run(&["--check-orphans", "--exit-code", &first.to_string()]);
+	assert_eq!(stdout(&output), "Changed meta files: 4\n\n");
+	assert_eq!(output.status.code(), Some(EXIT_CODE_NO_CHANGES));
+}
+
+#[test]
+fn asset_without_meta_file_is_a_warning() {
+	let fixture = Fixture::new("cli-without-meta");
+	fixture.asset("Assets/Base.png", &guid('9'));
+	fixture.commit("first");
+	fixture.write("Assets/NoMeta.png", "asset");
+	let output = fixture.run(&[]);
+	assert!(output.status.success());
+	assert!(stderr(&output).contains("WARNING: Asset 'Assets/NoMeta.png' got added without a meta file, Unity will assign it a new GUID.\n"), "{}", stderr(&output));
+	let output = fixture.run(&["--format", "json"]);
+	let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+	assert_eq!(json["warnings"][0]["paths"], serde_json::json!(["Assets/NoMeta.png"]));
+}
+
+#[test]
+fn summary_only() {
+	let fixture = fixture_with_duplicate_guid("cli-summary-only");
+	fixture.remove("Assets/x.png.meta");
+	let output = fixture.run(&["--summary"]);
+	assert!(output.status.success());
+	let stdout = stdout(&output);
+	assert_eq!(stdout, "Summary: 1 added, 1 removed, 0 renamed, 0 modified, 1 GUID conflicts\n");
+	assert!(!stdout.contains(['├', '└', '│']));
+	// Implies '--quiet', the warnings would be longer than the summary.
+	assert!(stderr(&output).is_empty(), "{}", stderr(&output));
+}
+
+#[test]
+fn dot_graph_has_an_edge_for_the_rename() {
+	let fixture = Fixture::new("cli-dot");
+	fixture.asset("Assets/A/x.png", &guid('1'));
+	let first = fixture.commit("first");
+	fixture.rename("Assets/A/x.png.meta", "Assets/B/x.png.meta");
+	fixture.asset("Assets/New.png", &guid('2'));
+	let second = fixture.commit("second");
+	let output = fixture.run(&[&format!("{first}..{second}"), "--format", "dot"]);
+	let stdout = stdout(&output);
+	assert!(stdout.starts_with("digraph meta_changes {\n") && stdout.ends_with("}\n"), "{stdout}");
+	assert!(stdout.contains(&format!("\t\"Assets/A/x.png\" -> \"Assets/B/x.png\" [label=\"{}\"];\n", guid('1'))), "{stdout}");
+	assert!(stdout.contains(&format!("\t\"Assets/New.png\" [color=green, tooltip=\"{}\"];\n", guid('2'))), "{stdout}");
+	assert_eq!(stdout.matches("->").count(), 1);
+}
+
+#[test]
+fn markdown_report_has_sections_and_renames() {
+	let fixture = Fixture::new("cli-markdown");
+	fixture.asset("Assets/A/x.png", &guid('1'));
+	let first = fixture.commit("first");
+	fixture.rename("Assets/A/x.png.meta", "Assets/B/x.png.meta");
+	fixture.asset("Assets/New_file.png", &guid('2'));
+	let second = fixture.commit("second");
+	let output = fixture.run(&[&format!("{first}..{second}"), "--format", "markdown"]);
+	let stdout = stdout(&output);
+	for header in ["### Added\n", "### Removed\n", "### Renamed\n"] {
+		assert!(stdout.contains(header), "{stdout}");
+	}
+	assert!(stdout.contains(&format!("- Assets/New\\_file.png `{}`\n", guid('2'))), "{stdout}");
+	assert!(stdout.contains(&format!("- Assets/A/x.png → Assets/B/x.png `{}`\n", guid('1'))), "{stdout}");
+}
+
+#[test]
+fn no_progress_when_quiet_or_not_a_terminal() {
+	let fixture = Fixture::new("cli-progress");
+	fixture.asset("Assets/Base.png", &guid('0'));
+	let first = fixture.commit("first");
+	// More meta files than one progress interval.
+	for index in 1..=250 {
+		fixture.asset(&format!("Assets/Many/{index}.png"), &format!("{index:032x}"));
+	}
+	let second = fixture.commit("second");
+	let range = format!("{first}..{second}");
+	let quiet = fixture.run(&[&range, "--format", "porcelain", "--quiet"]);
+	assert_eq!(stdout(&quiet).lines().count(), 250);
+	assert!(quiet.stderr.is_empty(), "{}", stderr(&quiet));
+	// The test captures stderr, thus it is not a terminal.
+	let piped = fixture.run(&[&range, "--format", "porcelain"]);
+	assert!(!stderr(&piped).contains("Reading meta files"), "{}", stderr(&piped));
+}
+
+#[test]
+fn rename_threshold_decides_about_borderline_renames() {
+	let fixture = Fixture::new("cli-rename-threshold");
+	let settings: String = (0..10).map(|index| format!("  setting{index}: {index}\n")).collect();
+	fixture.write("Assets/A/x.png.meta", common::meta_content(&guid('1')) + &settings);
+	let first = fixture.commit("first");
+	fixture.remove("Assets/A/x.png.meta");
+	// Half of the settings change, while moving.
+	fixture.write("Assets/B/x.png.meta", common::meta_content(&guid('1')) + &settings.replacen(": ", ": changed ", 5));
+	let second = fixture.commit("second");
+	let range = format!("{first}..{second}");
+	let low = fixture.run(&[&range, "--list-files", "--rename-threshold", "20"]);
+	assert_eq!(stdout(&low), "Renamed     Assets/A/x.png.meta -> Assets/B/x.png.meta\n");
+	let high = fixture.run(&[&range, "--list-files", "--rename-threshold=90"]);
+	assert_eq!(stdout(&high), "Deleted     Assets/A/x.png.meta\nAdded       Assets/B/x.png.meta\n");
+	// Either way, the GUID makes it a rename.
+	let porcelain = format!("R {} Assets/A/x.png\tAssets/B/x.png\n", guid('1'));
+	assert_eq!(stdout(&fixture.run(&[&range, "--format", "porcelain", "--rename-threshold", "90"])), porcelain);
+}
+
+#[test]
+fn rename_threshold_must_be_a_percentage() {
+	let fixture = Fixture::new("cli-rename-threshold-invalid");
+	fixture.asset("Assets/x.png", &guid('1'));
+	fixture.commit("first");
+	for value in ["101", "-1", "half"] {
+		let output = fixture.run(&["--rename-threshold", value]);
+		assert!(!output.status.success());
+		assert!(stderr(&output).contains(&format!("Invalid rename threshold '{value}', expected a percentage from 0 to 100.")), "{}", stderr(&output));
+	}
+}
+
+#[test]
+fn json_output_has_the_warnings_instead_of_stderr() {
+	let fixture = fixture_with_duplicate_guid("cli-json-warnings");
+	let output = fixture.run(&["--format", "json"]);
+	assert!(!stderr(&output).contains("WARNING"), "{}", stderr(&output));
+	let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+	let warnings = json["warnings"].as_array().unwrap();
+	assert_eq!(warnings.len(), 1);
+	assert_eq!(warnings[0]["severity"], "warning");
+	assert_eq!(warnings[0]["paths"], serde_json::json!(["Assets/A/copy.png", "Assets/B/copy.png"]));
+}
+
+#[test]
+fn batch_prints_a_result_per_pair() {
+	let fixture = Fixture::new("cli-batch");
+	fixture.asset("Assets/x.png", &guid('1'));
+	let first = fixture.commit("first");
+	fixture.asset("Assets/y.png", &guid('2'));
+	let second = fixture.commit("second");
+	fixture.rename("Assets/x.png.meta", "Assets/Moved/x.png.meta");
+	let third = fixture.commit("third");
+	let input = format!("# Release boundaries\n{first} {second}\n\n{second}..{third}\n");
+	
+	let output = fixture.run_with_stdin(&["--batch"], &input);
+	assert!(output.status.success(), "{}", stderr(&output));
+	let stdout = stdout(&output);
+	assert_eq!(stdout.matches("Diff ").count(), 2, "{stdout}");
+	let second_block = stdout.find(&format!("Diff {second}..{third}:")).unwrap();
+	assert!(stdout.find(&format!("Diff {first}..{second}:")).unwrap() < second_block, "{stdout}");
+	assert_eq!(stdout.matches("Summary: ").count(), 2, "{stdout}");
+	
+	let output = fixture.run_with_stdin(&["--batch", "--format", "json"], &input);
+	let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+	let results = json.as_array().unwrap();
+	assert_eq!(results.len(), 2);
+	assert_eq!(results[0]["added"][0]["guid"], guid('2'));
+	assert_eq!(results[1]["renamed"][0]["guid"], guid('1'));
+}
+
+#[test]
+fn batch_rejects_invalid_lines() {
+	let fixture = Fixture::new("cli-batch-invalid");
+	fixture.asset("Assets/x.png", &guid('1'));
+	fixture.commit("first");
+	let output = fixture.run_with_stdin(&["--batch"], "HEAD\n");
+	assert_eq!(output.status.code(), Some(1));
+	assert!(stderr(&output).contains("Invalid line 1 on stdin 'HEAD', expected '<from> <to>'."), "{}", stderr(&output));
+}
+
+// An addition, a removal and a rename - each in its own folder.
+fn fixture_with_each_change_kind(name: &str) -> (Fixture, String) {
+	let fixture = Fixture::new(name);
+	fixture.asset("Assets/Gone/x.png", &guid('1'));
+	fixture.asset("Assets/Old/y.png", &guid('2'));
+	let first = fixture.commit("first");
+	fixture.remove("Assets/Gone/x.png.meta");
+	fixture.rename("Assets/Old/y.png.meta", "Assets/New/y.png.meta");
+	fixture.asset("Assets/Fresh/z.png", &guid('3'));
+	let second = fixture.commit("second");
+	(fixture, format!("{first}..{second}"))
+}
+
+#[test]
+fn only_renames_hides_additions_and_removals() {
+	let (fixture, range) = fixture_with_each_change_kind("cli-only-renames");
+	let output = fixture.run(&[&range, "--format", "porcelain", "--only-renames"]);
+	assert_eq!(stdout(&output), format!("R {} Assets/Old/y.png\tAssets/New/y.png\n", guid('2')));
+	let output = fixture.run(&[&range, "--only-renames"]);
+	let stdout = stdout(&output);
+	assert!(stdout.contains("New") && stdout.contains("Old"), "{stdout}");
+	// Not even the folders of the hidden changes are left in the trees.
+	assert!(!stdout.contains("Gone") && !stdout.contains("Fresh"), "{stdout}");
+	assert!(stdout.ends_with("Summary: 0 added, 0 removed, 1 renamed, 0 modified, 0 GUID conflicts\n"), "{stdout}");
+}
+
+#[test]
+fn only_conflicts_shows_only_the_collision() {
+	let fixture = fixture_with_duplicate_guid("cli-only-conflicts");
+	fixture.asset("Assets/Clean/a.png", &guid('3'));
+	fixture.asset("Assets/Clean/b.png", &guid('4'));
+	fixture.remove("Assets/x.png.meta");
+	let output = fixture.run(&["--only-conflicts"]);
+	assert!(output.status.success());
+	assert_eq!(stdout(&output), format!(
+		"GUID COLLISION - multiple files share the same Unity GUID:\n  {} is added by 2 files:\n    - Assets/A/copy.png\n    - Assets/B/copy.png\n\n",
+		guid('2'),
+	));
+	
+	let output = fixture.run(&["--only-conflicts", "--format", "json"]);
+	let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+	assert_eq!(json, serde_json::json!({"conflicts": [{
+		"kind": "guid_collision", "direction": "added", "guid": guid('2'), "paths": ["Assets/A/copy.png", "Assets/B/copy.png"],
+	}]}));
+	assert_eq!(fixture.run(&["--only-conflicts", "--exit-code"]).status.code(), Some(EXIT_CODE_CHANGES));
+}
+
+#[test]
+fn stats_counts_all_meta_files() {
+	let fixture = Fixture::new("cli-stats");
+	fixture.asset("Assets/A/x.png", &guid('1'));
+	fixture.asset("Assets/A/y.png", &guid('2'));
+	fixture.asset("Assets/B/z.png", &guid('3'));
+	fixture.write(".gitignore", "Library/\n");
+	let first = fixture.commit("first");
+	fixture.asset("Assets/B/new.png", &guid('4'));
+	let second = fixture.commit("second");
+	// Ignored by Git, thus not counted in the work directory either.
+	fixture.asset("Library/cache.png", &guid('5'));
+	fixture.asset("Assets/B/untracked.png", &guid('6'));
+	
+	let output = fixture.run(&[&format!("{first}..{second}"), "--stats"]);
+	assert!(stdout(&output).starts_with("Changed meta files: 1 (of 4 meta files)\n\n"), "{}", stdout(&output));
+	let output = fixture.run(&["--stats"]);
+	assert!(stdout(&output).starts_with("Changed meta files: 1 (of 5 meta files)\n\n"), "{}", stdout(&output));
+	// The work directory is walked from its root, not from the current directory.
+	let output = fixture.run_in(&fixture.path("Assets/A"), &["--stats"]);
+	assert!(stdout(&output).starts_with("Changed meta files: 1 (of 5 meta files)\n\n"), "{}", stdout(&output));
+	let output = fixture.run(&["--stats", "--subdir", "Assets/A"]);
+	assert!(stdout(&output).starts_with("Changed meta files: 0 (of 2 meta files)\n\n"), "{}", stdout(&output));
+	// Without the option, nothing gets counted.
+	assert!(stdout(&fixture.run(&[])).starts_with("Changed meta files: 1\n\n"));
+	// Machine-readable output has no header, the counts are logged instead.
+	let output = fixture.run(&["--stats", "--format", "porcelain"]);
+	assert!(stderr(&output).contains("Scanned 5 meta files, 1 changed."), "{}", stderr(&output));
+}
+
+#[test]
+fn invert_matches_the_swapped_commits() {
+	let (fixture, range) = fixture_with_each_change_kind("cli-invert");
+	let (first, second) = range.split_once("..").unwrap();
+	let inverted = fixture.run(&[&range, "--format", "porcelain", "--invert"]);
+	assert_eq!(stdout(&inverted), format!(
+		"A {} Assets/Gone/x.png\nR {} Assets/New/y.png\tAssets/Old/y.png\nD {} Assets/Fresh/z.png\n",
+		guid('1'), guid('2'), guid('3'),
+	));
+	assert_eq!(inverted.stdout, fixture.run(&[&format!("{second}..{first}"), "--format", "porcelain"]).stdout);
+	let tree = fixture.run(&[&range, "--invert"]);
+	assert_eq!(tree.stdout, fixture.run(&[&format!("{second}..{first}")]).stdout);
+}
+
+#[test]
+fn legend_above_the_trees() {
+	let (fixture, range) = fixture_with_each_change_kind("cli-legend");
+	let legend = "Legend: ADDED REMOVED RENAMED UNCHANGED | (renamed) (moved) (moved+renamed) | same/changed/path\n\n";
+	let trees = stdout(&fixture.run(&[&range]));
+	// Without color, the plain words remain.
+	assert!(trees.contains(legend), "{trees}");
+	assert!(trees.find(legend).unwrap() < trees.find("By removal tree").unwrap(), "{trees}");
+	let colored = fixture.run(&[&range, "--color", "always"]);
+	assert!(String::from_utf8(colored.stdout).unwrap().contains("Legend: \x1b["));
+	for option in ["--no-legend", "--quiet"] {
+		let output = fixture.run(&[&range, option]);
+		assert!(!stdout(&output).contains("Legend"), "{}", stdout(&output));
+	}
+}
+
+#[test]
+fn replaced_asset_section() {
+	let fixture = Fixture::new("cli-replaced");
+	fixture.asset("Assets/x.png", &guid('1'));
+	fixture.commit("first");
+	fixture.write("Assets/x.png.meta", common::meta_content(&guid('2')));
+	let stdout = stdout(&fixture.run(&[]));
+	let section = format!(
+		"Replaced - same path, but a different GUID (references to the old GUID break):\n  Assets/x.png: {} => {}\n",
+		guid('1'), guid('2'),
+	);
+	assert!(stdout.contains(&section), "{stdout}");
+}
+
+#[test]
+fn strip_prefix_shortens_the_paths() {
+	let fixture = Fixture::new("cli-strip-prefix");
+	fixture.asset("Assets/Characters/Old/Hero.png", &guid('1'));
+	let first = fixture.commit("first");
+	fixture.rename("Assets/Characters/Old/Hero.png.meta", "Assets/Characters/New/Hero.png.meta");
+	fixture.asset("Assets/Characters/Villain.png", &guid('2'));
+	fixture.asset("Assets/Props/Box.png", &guid('3'));
+	let second = fixture.commit("second");
+	let range = format!("{first}..{second}");
+	
+	let output = fixture.run(&[&range, "--format", "porcelain", "--strip-prefix", "Assets/Characters"]);
+	// Paths outside of the prefix stay as they are.
+	assert_eq!(stdout(&output), format!(
+		"R {} Old/Hero.png\tNew/Hero.png\nA {} Villain.png\nA {} Assets/Props/Box.png\n",
+		guid('1'), guid('2'), guid('3'),
+	));
+	let output = fixture.run(&[&range, "--strip-prefix", "Assets/Characters", "--no-legend"]);
+	let stdout = stdout(&output);
+	assert!(stdout.contains(&format!("└─Villain.png: ADDED {}\n", guid('2'))), "{stdout}");
+	assert!(stdout.contains("├─New: (+0 -0 ~1)\n│ └─Hero.png: <= 'Old/Hero.png' (moved)\n"), "{stdout}");
+	assert!(stdout.contains("├─Assets: (+1 -0 ~0)\n│ └─Props: (+1 -0 ~0)\n"), "{stdout}");
+	assert!(!stdout.contains("Characters"), "{stdout}");
+}
+
+#[test]
+fn timings_block_only_when_asked() {
+	let (fixture, range) = fixture_with_each_change_kind("cli-timings");
+	let output = fixture.run(&[&range, "--timings"]);
+	let stderr = stderr(&output);
+	let block = &stderr[stderr.find("Timings:\n").unwrap_or_else(|| panic!("{stderr}"))..];
+	let phases: Vec<_> = block.lines().skip(1).map(|line| line.split_whitespace().next().unwrap()).collect();
+	assert_eq!(phases, vec!["creating", "filtering", "reading", "building", "post-processing", "printing", "total"], "{block}");
+	assert!(block.lines().skip(1).all(|line| line.ends_with(" ms")), "{block}");
+	// Only stderr, the results stay the same.
+	let without = fixture.run(&[&range]);
+	assert_eq!(output.stdout, without.stdout);
+	assert!(!common::stderr(&without).contains("Timings"));
+}
+
+#[test]
+fn root_label_defaults_to_the_repository_folder() {
+	let (fixture, range) = fixture_with_each_change_kind("cli-root-label");
+	let output = fixture.run(&[&range, "--root-label", "Game"]);
+	assert!(stdout(&output).contains("By addition tree:\nGame: (+1 -0 ~1)\n└─Assets: (+1 -0 ~1)\n"), "{}", stdout(&output));
+	let folder_name = fixture.root.file_name().unwrap().to_str().unwrap();
+	let output = fixture.run(&[&range, "--root-label="]);
+	assert!(stdout(&output).contains(&format!("By removal tree:\n{folder_name}: (+0 -1 ~1)\n└─Assets")), "{}", stdout(&output));
+}
+
+#[test]
+fn stats_counts_changed_folders_and_files() {
+	let fixture = Fixture::new("cli-stats-assets");
+	fixture.asset("Assets/Old/x.png", &guid('1'));
+	fixture.asset("Assets/Kept.png", &guid('2'));
+	let first = fixture.commit("first");
+	fixture.folder("Assets/Level", &guid('3'));
+	fixture.folder("Assets/Level/Props", &guid('4'));
+	fixture.asset("Assets/Level/Props/Rock.png", &guid('5'));
+	fixture.rename("Assets/Old/x.png.meta", "Assets/New/x.png.meta");
+	// Modified without GUID change, thus not counted.
+	fixture.write("Assets/Kept.png.meta", common::meta_content(&guid('2')).replace("userData: ", "userData: changed"));
+	let second = fixture.commit("second");
+	let range = format!("{first}..{second}");
+	let output = fixture.run(&[&range, "--stats"]);
+	assert!(stdout(&output).contains("\nChanged assets: 2 folders, 2 files\n"), "{}", stdout(&output));
+	assert!(!stdout(&fixture.run(&[&range])).contains("Changed assets"));
+}
+
+#[test]
+fn json_file_next_to_the_tree_on_stdout() {
+	let fixture = fixture_with_duplicate_guid("cli-multiple-formats");
+	let output = fixture.run(&["--format", "json:report.json", "--format", "tree:-", "--format", "porcelain:changes.txt"]);
+	assert!(output.status.success(), "{}", stderr(&output));
+	assert_eq!(output.stdout, fixture.run(&[]).stdout);
+	let json: serde_json::Value = serde_json::from_slice(&std::fs::read(fixture.path("report.json")).unwrap()).unwrap();
+	assert_eq!(json["added"].as_array().unwrap().len(), 1);
+	assert_eq!(std::fs::read(fixture.path("changes.txt")).unwrap(), fixture.run(&["--format", "porcelain"]).stdout);
+}
+
+#[test]
+fn invalid_format_destinations_are_rejected() {
+	let fixture = fixture_with_duplicate_guid("cli-multiple-formats-invalid");
+	for (arguments, message) in [
+		(&["--format", "json:"][..], "Missing file after ':' in '--format json:', use '-' for the normal output."),
+		(&["--format", "yaml:out.yaml"], "Unknown output format 'yaml'"),
+		(&["--format", "json:same.txt", "--format", "csv:same.txt"], "File 'same.txt' is used as output more than once."),
+		(&["--format", "json:same.txt", "--output", "same.txt"], "File 'same.txt' is used as output more than once."),
+	] {
+		let output = fixture.run(arguments);
+		assert!(!output.status.success(), "{arguments:?}");
+		assert!(stderr(&output).contains(message), "{arguments:?}: {}", stderr(&output));
+	}
+	assert!(!fixture.path("same.txt").exists());
+}
+
+#[test]
+fn fail_on_conflict_without_exit_code() {
+	let fixture = fixture_with_duplicate_guid("cli-fail-on-conflict");
+	let output = fixture.run(&["--fail-on-conflict"]);
+	assert_eq!(output.status.code(), Some(EXIT_CODE_CONFLICTS));
+	assert!(stderr(&output).contains(&format!("Found GUID conflicts (used by multiple files or for the same path): {}", guid('2'))), "{}", stderr(&output));
+	// The results are still printed.
+	assert!(stdout(&output).contains("GUID COLLISION"));
+	assert_eq!(fixture.run(&[]).status.code(), Some(EXIT_CODE_NO_CHANGES));
+	
+	// Changes without conflicts pass.
+	fixture.remove("Assets/B/copy.png.meta");
+	let output = fixture.run(&["--fail-on-conflict"]);
+	assert_eq!(output.status.code(), Some(EXIT_CODE_NO_CHANGES));
+	assert!(!stderr(&output).contains("Found GUID conflicts"));
+}
+
+#[test]
+fn group_moves_prints_one_line_per_moved_folder() {
+	let fixture = Fixture::new("cli-group-moves");
+	for (name, digit) in [("a.png", '1'), ("b.png", '2'), ("Sub/c.png", '3')] {
+		fixture.asset(&format!("Assets/Old/{name}"), &guid(digit));
+	}
+	fixture.asset("Assets/Icon.png", &guid('4'));
+	let first = fixture.commit("first");
+	for name in ["a.png", "b.png", "Sub/c.png"] {
+		fixture.rename(&format!("Assets/Old/{name}.meta"), &format!("Assets/New/{name}.meta"));
+	}
+	fixture.rename("Assets/Icon.png.meta", "Assets/Logo.png.meta");
+	let second = fixture.commit("second");
+	let range = format!("{first}..{second}");
+	
+	let output = stdout(&fixture.run(&[&range, "--group-moves"]));
+	assert_eq!(output, "Changed meta files: 4\n\nMoves:\n  3 assets moved from Assets/Old/ to Assets/New/\n  1 asset renamed from Assets/Icon.png to Assets/Logo.png\n");
+	// Verbose expands the group into its moved assets.
+	let output = stdout(&fixture.run(&[&range, "--group-moves", "--verbose"]));
+	for (name, digit) in [("a.png", '1'), ("b.png", '2'), ("Sub/c.png", '3')] {
+		assert!(output.contains(&format!("\n    - {name} {}\n", guid(digit))), "{output}");
+	}
+	assert_eq!(output.matches("moved from").count(), 1, "{output}");
+}
+
+#[test]
+fn context_lists_unchanged_siblings_dimmed() {
+	let fixture = Fixture::new("cli-context");
+	fixture.asset("Assets/Props/Crate.png", &guid('1'));
+	fixture.asset("Assets/Props/Barrel.png", &guid('2'));
+	fixture.asset("Assets/Other/Far.png", &guid('3'));
+	fixture.commit("first");
+	fixture.asset("Assets/Props/Box.png", &guid('4'));
+	let output = stdout(&fixture.run(&["--context", "--color", "always"]));
+	// Dimmed as gray, without a description. Siblings of unchanged folders stay hidden.
+	assert!(output.contains("    ├─\x1b[90mBarrel.png\x1b[0m\n    ├─\x1b[97mBox.png\x1b[0m: "), "{output}");
+	assert!(output.contains("    └─\x1b[90mCrate.png\x1b[0m\n"), "{output}");
+	assert!(!output.contains("Far.png"), "{output}");
+	// Context is not counted.
+	assert!(output.contains("Props\x1b[0m: \x1b[90m(\x1b[92m+1\x1b[90m"), "{output}");
+	
+	// The siblings are looked up in the repository, not in the current directory.
+	let output = stdout(&fixture.run_in(&fixture.path("Assets/Props"), &["--context", "--color", "always"]));
+	assert!(output.contains("    └─\x1b[90mCrate.png\x1b[0m\n"), "{output}");
+	
+	let output = stdout(&fixture.run(&["--color", "always"]));
+	assert!(!output.contains("Barrel.png") && !output.contains("Crate.png"), "{output}");
+	assert!(output.contains("Box.png"), "{output}");
+}
+
+#[test]
+fn guid_key_reads_a_custom_sidecar_format() {
+	let fixture = Fixture::new("cli-guid-key");
+	fixture.write("Assets/Base.png.meta", "uuid: 55555555555555555555555555555555\n");
+	fixture.commit("first");
+	fixture.write("Assets/New.png", "asset");
+	fixture.write("Assets/New.png.meta", format!("version: 1\nuuid: {}\n", guid('1')));
+	let output = fixture.run(&["--format", "porcelain", "--guid-key", "uuid"]);
+	assert_eq!(stdout(&output), format!("A {} Assets/New.png\n", guid('1')));
+	// With the default key, the GUID is missing.
+	let output = fixture.run(&["--format", "porcelain"]);
+	assert!(stdout(&output).is_empty(), "{}", stdout(&output));
+}
+
+#[test]
+fn uppercase_guid_matches_its_lowercase_form() {
+	let fixture = Fixture::new("cli-uppercase-guid");
+	fixture.asset("Assets/Old.png", &guid('a').to_uppercase());
+	let first = fixture.commit("first");
+	fixture.remove("Assets/Old.png.meta");
+	fixture.asset("Assets/New.png", &guid('a'));
+	let second = fixture.commit("second");
+	let output = fixture.run(&[&format!("{first}..{second}"), "--format", "porcelain"]);
+	// A rename, not a removal and an addition - displayed in lowercase.
+	assert_eq!(stdout(&output), format!("R {} Assets/Old.png\tAssets/New.png\n", guid('a')));
+}
@@ -0,0 +1,160 @@
+// Shared by the integration tests: throwaway Git repositories with meta files, and running the binary on them.
+// Not every test file uses every helper.
+#![allow(dead_code)]
+
+use git2::{IndexAddOption, Oid, Repository, Signature, Time};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+pub const BINARY: &str = env!("CARGO_BIN_EXE_unity_engine_meta_git_differ");
+
+// The same exit codes as in main.rs. Errors exit with 1.
+pub const EXIT_CODE_NO_CHANGES: i32 = 0;
+pub const EXIT_CODE_CHANGES: i32 = 2;
+pub const EXIT_CODE_CONFLICTS: i32 = 3;
+
+// GUIDs are easier to recognize in the output, when they are all the same digit.
+pub fn guid(digit: char) -> String {
+	std::iter::repeat_n(digit, 32).collect()
+}
+
+pub fn meta_content(guid: &str) -> String {
+	format!("fileFormatVersion: 2\nguid: {guid}\nDefaultImporter:\n  userData: \n")
+}
+
+// A repository in the temp folder, which gets deleted again when dropped.
+pub struct Fixture {
+	pub root: PathBuf,
+	pub repo: Repository,
+}
+
+impl Fixture {
+	// The name only helps to find the folder while debugging, the tests of a file run in parallel.
+	pub fn new(name: &str) -> Fixture {
+		static COUNTER: AtomicUsize = AtomicUsize::new(0);
+		let root = std::env::temp_dir().join(format!(
+			"unity-meta-diff-{name}-{}-{}", std::process::id(), COUNTER.fetch_add(1, Ordering::Relaxed),
+		));
+		if root.exists() {
+			fs::remove_dir_all(&root).unwrap();
+		}
+		fs::create_dir_all(&root).unwrap();
+		let repo = Repository::init(&root).unwrap();
+		let mut config = repo.config().unwrap();
+		config.set_str("user.name", "Fixture").unwrap();
+		config.set_str("user.email", "fixture@example.com").unwrap();
+		config.set_bool("core.autocrlf", false).unwrap();
+		Fixture { root, repo }
+	}
+	
+	pub fn path(&self, path: &str) -> PathBuf {
+		self.root.join(path)
+	}
+	
+	pub fn write(&self, path: &str, content: impl AsRef<[u8]>) {
+		let path = self.path(path);
+		fs::create_dir_all(path.parent().unwrap()).unwrap();
+		fs::write(path, content).unwrap();
+	}
+	
+	// Writes the meta file '<path>.meta' and an asset file at '<path>', like Unity would.
+	pub fn asset(&self, path: &str, guid: &str) {
+		self.write(path, "asset");
+		self.write(&format!("{path}.meta"), meta_content(guid));
+	}
+	
+	// Writes the meta file '<path>.meta' and creates the folder at '<path>'.
+	pub fn folder(&self, path: &str, guid: &str) {
+		fs::create_dir_all(self.path(path)).unwrap();
+		self.write(&format!("{path}.meta"), format!("fileFormatVersion: 2\nguid: {guid}\nfolderAsset: yes\n"));
+	}
+	
+	pub fn remove(&self, path: &str) {
+		let path = self.path(path);
+		if path.is_dir() {
+			fs::remove_dir_all(path).unwrap();
+		} else {
+			fs::remove_file(path).unwrap();
+		}
+	}
+	
+	pub fn rename(&self, from: &str, to: &str) {
+		let to = self.path(to);
+		fs::create_dir_all(to.parent().unwrap()).unwrap();
+		fs::rename(self.path(from), to).unwrap();
+	}
+	
+	// Puts the whole work directory into the index, including deletions.
+	pub fn stage_all(&self) {
+		let mut index = self.repo.index().unwrap();
+		index.add_all(["*"], IndexAddOption::DEFAULT, None).unwrap();
+		index.update_all(["*"], None).unwrap();
+		index.write().unwrap();
+	}
+	
+	// Stages everything and commits it on top of the current HEAD.
+	pub fn commit(&self, message: &str) -> Oid {
+		self.commit_with(message, Signature::now("Fixture", "fixture@example.com").unwrap())
+	}
+	
+	// Same as commit(), but dated at the given Unix time.
+	pub fn commit_at(&self, message: &str, time: i64) -> Oid {
+		self.commit_with(message, Signature::new("Fixture", "fixture@example.com", &Time::new(time, 0)).unwrap())
+	}
+	
+	fn commit_with(&self, message: &str, signature: Signature) -> Oid {
+		self.stage_all();
+		let mut index = self.repo.index().unwrap();
+		let tree = self.repo.find_tree(index.write_tree().unwrap()).unwrap();
+		let parent = self.repo.head().ok().map(|head| head.peel_to_commit().unwrap());
+		let parents: Vec<_> = parent.iter().collect();
+		self.repo.commit(Some("HEAD"), &signature, &signature, message, &tree, &parents).unwrap()
+	}
+	
+	// Runs the binary inside the repository.
+	pub fn run(&self, arguments: &[&str]) -> Output {
+		self.run_in(&self.root, arguments)
+	}
+	
+	pub fn run_in(&self, folder: &Path, arguments: &[&str]) -> Output {
+		self.command(folder, arguments).output().unwrap()
+	}
+	
+	pub fn run_with_stdin(&self, arguments: &[&str], input: &str) -> Output {
+		let mut child = self.command(&self.root, arguments)
+			.stdin(Stdio::piped())
+			.stdout(Stdio::piped())
+			.stderr(Stdio::piped())
+			.spawn()
+			.unwrap();
+		child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+		child.wait_with_output().unwrap()
+	}
+	
+	fn command(&self, folder: &Path, arguments: &[&str]) -> Command {
+		let mut command = Command::new(BINARY);
+		command
+			.args(["--color", "never"])
+			.args(arguments)
+			.current_dir(folder)
+			.env_remove("NO_COLOR");
+		command
+	}
+}
+
+impl Drop for Fixture {
+	fn drop(&mut self) {
+		let _ = fs::remove_dir_all(&self.root);
+	}
+}
+
+pub fn stdout(output: &Output) -> String {
+	String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+pub fn stderr(output: &Output) -> String {
+	String::from_utf8(output.stderr.clone()).unwrap()
+}
@@ -0,0 +1,83 @@
+// The '.unitymetadiff' file in the repository root, and how the command line overrides it.
+mod common;
+
+use common::{guid, stderr, stdout, Fixture};
+
+// One commit with a single asset in each of the folders 'A' and 'B', then the config file is written (untracked).
+fn fixture_with_config(name: &str, config: &str) -> (Fixture, String) {
+	let fixture = Fixture::new(name);
+	fixture.asset("Assets/Base.png", &guid('9'));
+	let first = fixture.commit("first");
+	fixture.asset("Assets/A/x.png", &guid('1'));
+	fixture.asset("Assets/B/y.png", &guid('2'));
+	let second = fixture.commit("second");
+	fixture.write(".unitymetadiff", config);
+	(fixture, format!("{first}..{second}"))
+}
+
+fn line_a() -> String {
+	format!("A {} Assets/A/x.png\n", guid('1'))
+}
+
+fn line_b() -> String {
+	format!("A {} Assets/B/y.png\n", guid('2'))
+}
+
+#[test]
+fn config_provides_defaults() {
+	let (fixture, range) = fixture_with_config("config-defaults", "# Only folder A.\nsubdir = Assets/A\nformat=porcelain\n");
+	let output = fixture.run(&[&range]);
+	assert_eq!(stdout(&output), line_a());
+}
+
+#[test]
+fn command_line_subdir_replaces_config_subdir() {
+	let (fixture, range) = fixture_with_config("config-subdir", "subdir=Assets/A\nformat=porcelain\n");
+	let output = fixture.run(&[&range, "--subdir", "Assets/B"]);
+	assert_eq!(stdout(&output), line_b());
+}
+
+#[test]
+fn command_line_exclude_replaces_config_exclude() {
+	let (fixture, range) = fixture_with_config("config-exclude", "exclude=Assets/B\nformat=porcelain\n");
+	assert_eq!(stdout(&fixture.run(&[&range])), line_a());
+	assert_eq!(stdout(&fixture.run(&[&range, "--exclude", "Assets/A"])), line_b());
+}
+
+#[test]
+fn command_line_extra_output_replaces_config_extra_output() {
+	let (fixture, range) = fixture_with_config("config-extra-output", "format=porcelain:from-config.txt\n");
+	let output = fixture.run(&[&range, "--format", "porcelain:from-command-line.txt"]);
+	assert!(output.status.success());
+	assert!(!fixture.path("from-config.txt").exists());
+	assert_eq!(std::fs::read_to_string(fixture.path("from-command-line.txt")).unwrap(), line_a() + &line_b());
+}
+
+#[test]
+fn command_line_overrides_single_value_options() {
+	let (fixture, range) = fixture_with_config("config-format", "format=json\n");
+	let output = fixture.run(&[&range, "--format", "porcelain"]);
+	assert_eq!(stdout(&output), line_a() + &line_b());
+}
+
+#[test]
+fn config_format_json_without_command_line_format() {
+	let (fixture, range) = fixture_with_config("config-json", "format=json\n");
+	let output = fixture.run(&[&range]);
+	assert!(output.status.success());
+	let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+	assert_eq!(json["added"][0]["guid"], guid('1'));
+	assert_eq!(json["added"][1]["guid"], guid('2'));
+}
+
+#[test]
+fn mode_options_are_rejected_in_the_config() {
+	for line in ["batch", "watch", "interactive", "output = changes.txt"] {
+		let (fixture, range) = fixture_with_config("config-mode", &format!("format=porcelain\n{line}\n"));
+		let output = fixture.run(&[&range]);
+		assert_eq!(output.status.code(), Some(1), "{line}");
+		let key = line.split(' ').next().unwrap();
+		assert!(stderr(&output).contains(&format!("Option '--{key}' in line 2 of '")), "{line}: {}", stderr(&output));
+		assert!(stdout(&output).is_empty(), "{line}");
+	}
+}
@@ -0,0 +1,131 @@
+// Which meta files the command line options let through: '--subdir', '--exclude' and '--no-untracked'.
+mod common;
+
+use common::{guid, meta_content, stdout, Fixture, EXIT_CODE_NO_CHANGES};
+
+// Two commits: 'Base' stays, 'Temp/Bush' moves to 'Rock' and three assets get added - two of them into 'Temp' folders.
+fn fixture_with_temp_folders(name: &str) -> (Fixture, String) {
+	let fixture = Fixture::new(name);
+	fixture.asset("Assets/Base.png", &guid('5'));
+	fixture.asset("Assets/Temp/Bush.png", &guid('4'));
+	let first = fixture.commit("first");
+	fixture.rename("Assets/Temp/Bush.png.meta", "Assets/Rock.png.meta");
+	fixture.rename("Assets/Temp/Bush.png", "Assets/Rock.png");
+	fixture.asset("Assets/Keep.png", &guid('1'));
+	fixture.asset("Assets/Temp/Gen.png", &guid('2'));
+	fixture.asset("Assets/Deep/Temp/Gen2.png", &guid('3'));
+	let second = fixture.commit("second");
+	(fixture, format!("{first}..{second}"))
+}
+
+#[test]
+fn exclude_drops_temp_folders_at_any_depth() {
+	let (fixture, range) = fixture_with_temp_folders("exclude-temp");
+	let output = fixture.run(&[&range, "--format", "porcelain", "--exclude", "**/Temp/**"]);
+	assert_eq!(output.status.code(), Some(EXIT_CODE_NO_CHANGES));
+	let stdout = stdout(&output);
+	assert!(!stdout.contains("Temp"), "{stdout}");
+	assert!(stdout.contains(&format!("A {} Assets/Keep.png\n", guid('1'))), "{stdout}");
+}
+
+#[test]
+fn exclude_drops_the_excluded_side_of_a_rename() {
+	let (fixture, range) = fixture_with_temp_folders("exclude-rename");
+	let output = fixture.run(&[&range, "--format", "porcelain", "--exclude", "**/Temp/**"]);
+	// The rename out of 'Temp' only leaves the addition of 'Rock'.
+	assert_eq!(stdout(&output), format!(
+		"A {} Assets/Keep.png\nA {} Assets/Rock.png\n", guid('1'), guid('4'),
+	));
+}
+
+#[test]
+fn without_exclude_the_temp_folders_are_listed() {
+	let (fixture, range) = fixture_with_temp_folders("no-exclude");
+	let output = fixture.run(&[&range, "--format", "porcelain"]);
+	assert_eq!(stdout(&output), format!(
+		"A {} Assets/Keep.png\nA {} Assets/Temp/Gen.png\nA {} Assets/Deep/Temp/Gen2.png\nR {} Assets/Temp/Bush.png\tAssets/Rock.png\n",
+		guid('1'), guid('2'), guid('3'), guid('4'),
+	));
+}
+
+// Meta files of different asset types, which Git does not pair up as rename or copy.
+fn distinct_meta(digit: char) -> String {
+	meta_content(&guid(digit)) + &format!("{}\n", "importer setting ".repeat(8).replace("setting", &digit.to_string())).repeat(6)
+}
+
+// Changes in three folders, and renames into and out of 'Characters'.
+fn fixture_with_folders(name: &str) -> (Fixture, [String; 2]) {
+	let fixture = Fixture::new(name);
+	fixture.asset("Assets/Characters/Old.png", &guid('4'));
+	fixture.asset("Assets/Props/Leaving.png", &guid('5'));
+	let first = fixture.commit("first");
+	fixture.write("Assets/Characters/Hero.png.meta", distinct_meta('1'));
+	fixture.write("Assets/Props/Box.png.meta", distinct_meta('2'));
+	fixture.write("Assets/Scenes/Main.unity.meta", distinct_meta('3'));
+	fixture.rename("Assets/Characters/Old.png.meta", "Assets/Props/Old.png.meta");
+	fixture.rename("Assets/Props/Leaving.png.meta", "Assets/Characters/Leaving.png.meta");
+	let second = fixture.commit("second");
+	(fixture, [first.to_string(), second.to_string()])
+}
+
+#[test]
+fn subdir_keeps_only_matching_changes() {
+	let (fixture, [first, second]) = fixture_with_folders("subdir");
+	let output = fixture.run(&[&first, &second, "--subdir", "Assets/Characters"]);
+	let stdout = stdout(&output);
+	// Renames match with either their old or their new path.
+	assert!(stdout.contains(&format!("Hero.png: ADDED {}", guid('1'))), "{stdout}");
+	assert!(stdout.contains("Old.png: => 'Assets/Props/Old.png'"), "{stdout}");
+	assert!(stdout.contains("Leaving.png: <= 'Assets/Props/Leaving.png'"), "{stdout}");
+	assert!(!stdout.contains("Box.png") && !stdout.contains("Main.unity"), "{stdout}");
+	assert!(stdout.ends_with("Summary: 1 added, 0 removed, 2 renamed, 0 modified, 0 GUID conflicts\n"), "{stdout}");
+}
+
+#[test]
+fn subdir_is_repeatable() {
+	let (fixture, [first, second]) = fixture_with_folders("subdir-repeated");
+	let output = fixture.run(&[&first, &second, "--subdir", "Assets/Props", "--subdir", "Assets/Scenes"]);
+	let stdout = stdout(&output);
+	assert!(stdout.contains(&format!("Box.png: ADDED {}", guid('2'))), "{stdout}");
+	assert!(stdout.contains(&format!("Main.unity: ADDED {}", guid('3'))), "{stdout}");
+	assert!(!stdout.contains("Hero"), "{stdout}");
+}
+
+#[test]
+fn subdir_is_a_path_prefix_not_a_string_prefix() {
+	let (fixture, [first, second]) = fixture_with_folders("subdir-prefix");
+	let output = fixture.run(&[&first, &second, "--subdir", "Assets/Char"]);
+	let stdout = stdout(&output);
+	assert!(stdout.contains("Changed meta files: 0\n"), "{stdout}");
+	assert!(stdout.ends_with("Summary: 0 added, 0 removed, 0 renamed, 0 modified, 0 GUID conflicts\n"), "{stdout}");
+}
+
+#[test]
+fn unrelated_side_of_a_paired_delta_is_dropped() {
+	let fixture = Fixture::new("subdir-paired");
+	fixture.asset("Assets/Props/Box.png", &guid('1'));
+	let first = fixture.commit("first");
+	// Git pairs the similar meta files as rename, but the GUIDs are unrelated.
+	fixture.remove("Assets/Props/Box.png.meta");
+	fixture.asset("Assets/Characters/Hero.png", &guid('2'));
+	let second = fixture.commit("second");
+	let output = fixture.run(&[&first.to_string(), &second.to_string(), "--subdir", "Assets/Characters"]);
+	let stdout = stdout(&output);
+	assert!(stdout.contains(&format!("Hero.png: ADDED {}", guid('2'))), "{stdout}");
+	assert!(!stdout.contains("Box"), "{stdout}");
+	assert!(stdout.ends_with("Summary: 1 added, 0 removed, 0 renamed, 0 modified, 0 GUID conflicts\n"), "{stdout}");
+}
+
+#[test]
+fn untracked_meta_files_are_included_by_default() {
+	let fixture = Fixture::new("untracked");
+	fixture.asset("Assets/Base.png", &guid('5'));
+	fixture.commit("first");
+	fixture.asset("Assets/Deep/Folder/New.png", &guid('1'));
+	// Tracked changes stay, with and without the option.
+	fixture.remove("Assets/Base.png.meta");
+	let output = fixture.run(&["--format", "porcelain"]);
+	assert_eq!(stdout(&output), format!("A {} Assets/Deep/Folder/New.png\nD {} Assets/Base.png\n", guid('1'), guid('5')));
+	let output = fixture.run(&["--format", "porcelain", "--no-untracked"]);
+	assert_eq!(stdout(&output), format!("D {} Assets/Base.png\n", guid('5')));
+}
@@ -0,0 +1,34 @@
+// The crate root exports, as another tool embedding the analysis would use them.
+mod common;
+
+use common::{guid, Fixture};
+use unity_engine_meta_git_differ::data::path_tree_storage::TreePrintOptions;
+use unity_engine_meta_git_differ::data::uuid_storage::ChangeKind;
+use unity_engine_meta_git_differ::output::color::AnsiStripper;
+use unity_engine_meta_git_differ::{analyze, PathTreeStorage, Uuid, UuidStorage};
+
+#[test]
+fn analyze_a_commit_diff() {
+	let fixture = Fixture::new("library");
+	fixture.asset("Assets/Kept.png", &guid('1'));
+	fixture.asset("Assets/Gone.png", &guid('2'));
+	let first = fixture.commit("first");
+	fixture.remove("Assets/Gone.png.meta");
+	fixture.asset("Assets/New.png", &guid('3'));
+	let second = fixture.commit("second");
+	let from = fixture.repo.find_commit(first).unwrap().tree().unwrap();
+	let to = fixture.repo.find_commit(second).unwrap().tree().unwrap();
+	let diff = fixture.repo.diff_tree_to_tree(Some(&from), Some(&to), None).unwrap();
+	
+	let result = analyze(&fixture.repo, &diff).unwrap();
+	let storage: &UuidStorage = &result.uuid_storage;
+	let kinds: Vec<(Uuid, ChangeKind)> = storage.iter().map(|(uuid, entry)| (*uuid, entry.classify())).collect();
+	let uuid = |digit: char| -> Uuid { guid(digit).parse().unwrap() };
+	assert_eq!(kinds, vec![(uuid('2'), ChangeKind::Removed), (uuid('3'), ChangeKind::Added)]);
+	
+	let tree: &PathTreeStorage = &result.addition_tree;
+	let mut written = Vec::new();
+	tree.write_tree(&mut AnsiStripper::new(&mut written), storage, true, &TreePrintOptions::default()).unwrap();
+	assert_eq!(String::from_utf8(written).unwrap(), format!("└─Assets: (+1 -0 ~0)\n  └─New.png: ADDED {}\n", guid('3')));
+}
+
@@ -0,0 +1,63 @@
+// '--recurse-submodules': the meta files of changed submodules, below the submodule folder.
+mod common;
+
+use git2::{IndexEntry, IndexTime};
+use common::{guid, stderr, stdout, Fixture};
+
+// The submodule repository lives outside of the outer one, its folder 'Packages/Shared' only has a '.git' file pointing to it.
+// Thus the outer repository records a submodule commit, but never sees the files inside.
+fn link_submodule(fixture: &Fixture, submodule: &Fixture, path: &str) {
+	fixture.write(&format!("{path}/.git"), format!("gitdir: {}\n", submodule.repo.path().display()));
+	let commit = submodule.repo.head().unwrap().target().unwrap();
+	let mut index = fixture.repo.index().unwrap();
+	index.add(&IndexEntry {
+		ctime: IndexTime::new(0, 0), mtime: IndexTime::new(0, 0),
+		dev: 0, ino: 0, mode: 0o160000, uid: 0, gid: 0, file_size: 0,
+		id: commit, flags: path.len() as u16, flags_extended: 0,
+		path: path.as_bytes().to_vec(),
+	}).unwrap();
+	index.write().unwrap();
+}
+
+// The submodule renames 'Tree' and adds 'Rock', the outer repository only adds 'Sky'.
+fn fixture_with_submodule(name: &str) -> (Fixture, Fixture, String) {
+	let fixture = Fixture::new(name);
+	let submodule = Fixture::new(&format!("{name}-inner"));
+	submodule.asset("Assets/Tree.png", &guid('1'));
+	submodule.commit("first");
+	fixture.asset("Assets/Base.png", &guid('9'));
+	link_submodule(&fixture, &submodule, "Packages/Shared");
+	let first = fixture.commit("first");
+	submodule.rename("Assets/Tree.png.meta", "Assets/Forest.png.meta");
+	submodule.asset("Assets/Rock.png", &guid('2'));
+	submodule.commit("second");
+	fixture.asset("Assets/Sky.png", &guid('3'));
+	link_submodule(&fixture, &submodule, "Packages/Shared");
+	let second = fixture.commit("second");
+	(fixture, submodule, format!("{first}..{second}"))
+}
+
+#[test]
+fn submodule_changes_are_prefixed_with_its_folder() {
+	let (fixture, _submodule, range) = fixture_with_submodule("submodule-changes");
+	let output = fixture.run(&[&range, "--format", "porcelain", "--recurse-submodules"]);
+	assert_eq!(stdout(&output), format!(
+		"R {} Packages/Shared/Assets/Tree.png\tPackages/Shared/Assets/Forest.png\nA {} Packages/Shared/Assets/Rock.png\nA {} Assets/Sky.png\n",
+		guid('1'), guid('2'), guid('3'),
+	));
+	assert!(!stderr(&output).contains("Skipping submodule"), "{}", stderr(&output));
+	// Without the option, the submodule is not looked into.
+	let output = fixture.run(&[&range, "--format", "porcelain"]);
+	assert_eq!(stdout(&output), format!("A {} Assets/Sky.png\n", guid('3')));
+}
+
+#[test]
+fn uninitialized_submodule_is_a_warning() {
+	let (fixture, _submodule, range) = fixture_with_submodule("submodule-uninitialized");
+	fixture.remove("Packages/Shared/.git");
+	let output = fixture.run(&[&range, "--format", "porcelain", "--recurse-submodules"]);
+	assert!(output.status.success());
+	assert_eq!(stdout(&output), format!("A {} Assets/Sky.png\n", guid('3')));
+	let stderr = stderr(&output);
+	assert!(stderr.contains("Skipping submodule 'Packages/Shared': it is not initialized or could not be opened (run 'git submodule update --init')"), "{stderr}");
+}